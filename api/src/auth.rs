@@ -1,9 +1,9 @@
-use convex_client::leptos::Mutation;
 use leptos::server;
 
 use common::user::User;
 use leptos::prelude::ServerFnError;
-use serde::Serialize;
+
+pub use crate::generated::user::Create as CreateUser;
 
 #[server]
 pub async fn get_user() -> Result<Option<User>, ServerFnError> {
@@ -34,19 +34,19 @@ pub async fn google_auth() -> Result<String, ServerFnError> {
     Ok(url)
 }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct CreateUser {
-    auth: i64,
-    name: String,
-    image_url: String,
-}
-
-impl Mutation for CreateUser {
-    type Output = ();
+/// A token identifying the signed-in user to Convex, or `None` when signed
+/// out. Each call mints a fresh one.
+#[server]
+pub async fn convex_token() -> Result<Option<String>, ServerFnError> {
+    use auth::{auth, identity_tokens};
 
-    fn name(&self) -> String {
-        "user:create".into()
-    }
+    let Some(user) = auth().await?.current_user else {
+        return Ok(None);
+    };
+    let token = identity_tokens()?
+        .mint(user.user().id)
+        .map_err(|e| ServerFnError::new(format!("Failed to mint the Convex token: {e}")))?;
+    Ok(Some(token))
 }
 
 #[server]
@@ -104,10 +104,10 @@ pub async fn handle_google_redirect(
     code: String,
 ) -> Result<u64, ServerFnError> {
     use crate::auth::User;
-    use auth::auth;
     use auth::clients::GoogleAuth;
     use auth::clients::TokenResponse;
-    use common::state::convex;
+    use auth::{auth, identity_tokens};
+    use common::state::convex_http;
     use common::state::pool;
     use common::user::ssr::SqlCsrfToken;
 
@@ -152,12 +152,15 @@ pub async fn handle_google_redirect(
             .execute(&pool)
             .await?;
         let user = User::get_from_email(&email, &pool).await.unwrap();
-        let mut client = convex()?;
+        let token = identity_tokens()?
+            .mint(user.id)
+            .map_err(|e| ServerFnError::new(format!("Failed to mint the Convex token: {e}")))?;
+        let mut client = convex_http()?;
+        client.set_auth(Some(token));
         client
             .mutation(CreateUser {
-                auth: user.id,
                 name: username,
-                image_url: image,
+                image_url: Some(image),
             })
             .await
             .or(Err(ServerFnError::new("The creation of the user fail")))?;
@@ -0,0 +1,1581 @@
+// @generated by convex-codegen from `npx convex function-spec`. Do not edit;
+// regenerate it after changing the functions in `convex/`.
+
+pub mod category {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::Mutation;
+    use serde::{Deserialize, Serialize};
+
+    /// The `category:create` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Create {
+        pub name: String,
+        pub server: Id<tables::Servers>,
+    }
+
+    impl Create {
+        pub const PATH: &str = "category:create";
+    }
+
+    impl Mutation for Create {
+        type Output = Id<tables::Categories>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod channel {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `channel:get` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Get {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+    }
+
+    impl Get {
+        pub const PATH: &str = "channel:get";
+    }
+
+    impl<T> Query<T> for Get
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `channel:create` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Create {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub category: Option<Id<tables::Categories>>,
+        pub name: String,
+        pub server: Id<tables::Servers>,
+    }
+
+    impl Create {
+        pub const PATH: &str = "channel:create";
+    }
+
+    impl Mutation for Create {
+        type Output = Id<tables::Channels>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod files {
+    use convex_client::leptos::Mutation;
+    use serde::{Deserialize, Serialize};
+
+    /// The `files:generateUploadUrl` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GenerateUploadUrl {}
+
+    impl GenerateUploadUrl {
+        pub const PATH: &str = "files:generateUploadUrl";
+    }
+
+    impl Mutation for GenerateUploadUrl {
+        type Output = String;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod friends {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `friends:sendFriendRequest` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SendFriendRequest {
+        #[serde(rename = "receiverId")]
+        pub receiver_id: Id<tables::Users>,
+    }
+
+    impl SendFriendRequest {
+        pub const PATH: &str = "friends:sendFriendRequest";
+    }
+
+    impl Mutation for SendFriendRequest {
+        type Output = serde_json::Value;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `friends:acceptFriendRequest` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AcceptFriendRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: Id<tables::Friends>,
+    }
+
+    impl AcceptFriendRequest {
+        pub const PATH: &str = "friends:acceptFriendRequest";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AcceptFriendRequestOutput {
+        pub success: bool,
+    }
+
+    impl Mutation for AcceptFriendRequest {
+        type Output = AcceptFriendRequestOutput;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `friends:declineFriendRequest` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct DeclineFriendRequest {
+        #[serde(rename = "friendshipId")]
+        pub friendship_id: Id<tables::Friends>,
+    }
+
+    impl DeclineFriendRequest {
+        pub const PATH: &str = "friends:declineFriendRequest";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct DeclineFriendRequestOutput {
+        pub success: bool,
+    }
+
+    impl Mutation for DeclineFriendRequest {
+        type Output = DeclineFriendRequestOutput;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `friends:getFriends` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetFriends {}
+
+    impl GetFriends {
+        pub const PATH: &str = "friends:getFriends";
+    }
+
+    impl<T> Query<T> for GetFriends
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `friends:getPendingFriendRequests` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetPendingFriendRequests {}
+
+    impl GetPendingFriendRequests {
+        pub const PATH: &str = "friends:getPendingFriendRequests";
+    }
+
+    impl<T> Query<T> for GetPendingFriendRequests
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `friends:getSentFriendRequests` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetSentFriendRequests {}
+
+    impl GetSentFriendRequests {
+        pub const PATH: &str = "friends:getSentFriendRequests";
+    }
+
+    impl<T> Query<T> for GetSentFriendRequests
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `friends:getFriendshipStatus` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetFriendshipStatus {
+        #[serde(rename = "userId")]
+        pub user_id: Id<tables::Users>,
+    }
+
+    impl GetFriendshipStatus {
+        pub const PATH: &str = "friends:getFriendshipStatus";
+    }
+
+    impl<T> Query<T> for GetFriendshipStatus
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod invitations {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `invitations:createInvitation` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CreateInvitation {
+        #[serde(rename = "expiresInMinutes")]
+        pub expires_in_minutes: f64,
+        pub member: Id<tables::Members>,
+        pub server: Id<tables::Servers>,
+    }
+
+    impl CreateInvitation {
+        pub const PATH: &str = "invitations:createInvitation";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CreateInvitationOutput {
+        #[serde(rename = "invitationCode")]
+        pub invitation_code: String,
+        #[serde(rename = "invitationId")]
+        pub invitation_id: Id<tables::Invitations>,
+    }
+
+    impl Mutation for CreateInvitation {
+        type Output = Option<CreateInvitationOutput>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `invitations:validateInvitation` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ValidateInvitation {
+        #[serde(rename = "invitationCode")]
+        pub invitation_code: String,
+    }
+
+    impl ValidateInvitation {
+        pub const PATH: &str = "invitations:validateInvitation";
+    }
+
+    impl<T> Query<T> for ValidateInvitation
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `invitations:getInvitation` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetInvitation {
+        #[serde(rename = "invitationCode")]
+        pub invitation_code: String,
+    }
+
+    impl GetInvitation {
+        pub const PATH: &str = "invitations:getInvitation";
+    }
+
+    impl<T> Query<T> for GetInvitation
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `invitations:deleteInvitation` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct DeleteInvitation {
+        #[serde(rename = "invitationId")]
+        pub invitation_id: Id<tables::Invitations>,
+    }
+
+    impl DeleteInvitation {
+        pub const PATH: &str = "invitations:deleteInvitation";
+    }
+
+    impl Mutation for DeleteInvitation {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `invitations:joinServerWithInvitation` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct JoinServerWithInvitation {
+        #[serde(rename = "invitationCode")]
+        pub invitation_code: String,
+    }
+
+    impl JoinServerWithInvitation {
+        pub const PATH: &str = "invitations:joinServerWithInvitation";
+    }
+
+    impl Mutation for JoinServerWithInvitation {
+        type Output = Option<Id<tables::Members>>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod member {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `member:getOnlineMembersByRole` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetOnlineMembersByRole {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub role: Option<Id<tables::Roles>>,
+        pub server: Id<tables::Servers>,
+    }
+
+    impl GetOnlineMembersByRole {
+        pub const PATH: &str = "member:getOnlineMembersByRole";
+    }
+
+    impl<T> Query<T> for GetOnlineMembersByRole
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `member:getOfflineMembers` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetOfflineMembers {
+        pub server: Id<tables::Servers>,
+    }
+
+    impl GetOfflineMembers {
+        pub const PATH: &str = "member:getOfflineMembers";
+    }
+
+    impl<T> Query<T> for GetOfflineMembers
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `member:getMembersByIds` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetMembersByIds {
+        #[serde(rename = "memberIds")]
+        pub member_ids: Vec<Id<tables::Members>>,
+    }
+
+    impl GetMembersByIds {
+        pub const PATH: &str = "member:getMembersByIds";
+    }
+
+    impl<T> Query<T> for GetMembersByIds
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `member:setLastVisitedChannel` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SetLastVisitedChannel {
+        pub channel: Id<tables::Channels>,
+        pub member: Id<tables::Members>,
+    }
+
+    impl SetLastVisitedChannel {
+        pub const PATH: &str = "member:setLastVisitedChannel";
+    }
+
+    impl Mutation for SetLastVisitedChannel {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `member:getLastVisitedChannel` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetLastVisitedChannel {
+        pub member: Id<tables::Members>,
+    }
+
+    impl GetLastVisitedChannel {
+        pub const PATH: &str = "member:getLastVisitedChannel";
+    }
+
+    impl<T> Query<T> for GetLastVisitedChannel
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod messages {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, PaginatedQuery, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `messages:createMessage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CreateMessage {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(
+            rename = "clientMessageId",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub client_message_id: Option<String>,
+        pub content: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub mention_everyone: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub mention_roles: Option<Vec<Id<tables::Roles>>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub pinned: Option<bool>,
+        #[serde(
+            rename = "referenceId",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub reference_id: Option<Id<tables::Messages>>,
+    }
+
+    impl CreateMessage {
+        pub const PATH: &str = "messages:createMessage";
+    }
+
+    impl Mutation for CreateMessage {
+        type Output = Id<tables::Messages>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:getMessagesInChannel` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetMessagesInChannel {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl GetMessagesInChannel {
+        pub const PATH: &str = "messages:getMessagesInChannel";
+    }
+
+    impl<T> Query<T> for GetMessagesInChannel
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:getPaginatedMessagesInChannel` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetPaginatedMessagesInChannel {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl GetPaginatedMessagesInChannel {
+        pub const PATH: &str = "messages:getPaginatedMessagesInChannel";
+    }
+
+    impl<T> PaginatedQuery<T> for GetPaginatedMessagesInChannel
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:addAttachmentToMessage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AddAttachmentToMessage {
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+        pub name: String,
+        #[serde(rename = "storageId")]
+        pub storage_id: Id<tables::Storage>,
+    }
+
+    impl AddAttachmentToMessage {
+        pub const PATH: &str = "messages:addAttachmentToMessage";
+    }
+
+    impl Mutation for AddAttachmentToMessage {
+        type Output = Id<tables::Attachments>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:deleteMessage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct DeleteMessage {
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl DeleteMessage {
+        pub const PATH: &str = "messages:deleteMessage";
+    }
+
+    impl Mutation for DeleteMessage {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:updateMessage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct UpdateMessage {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub content: Option<String>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub pinned: Option<bool>,
+    }
+
+    impl UpdateMessage {
+        pub const PATH: &str = "messages:updateMessage";
+    }
+
+    impl Mutation for UpdateMessage {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:addReaction` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AddReaction {
+        pub emoji: String,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl AddReaction {
+        pub const PATH: &str = "messages:addReaction";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AddReactionOutput {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub reason: Option<String>,
+        pub success: bool,
+    }
+
+    impl Mutation for AddReaction {
+        type Output = AddReactionOutput;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:removeReaction` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveReaction {
+        pub emoji: String,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl RemoveReaction {
+        pub const PATH: &str = "messages:removeReaction";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveReactionOutput {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub reason: Option<String>,
+        pub success: bool,
+    }
+
+    impl Mutation for RemoveReaction {
+        type Output = RemoveReactionOutput;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:pinMessage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PinMessage {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl PinMessage {
+        pub const PATH: &str = "messages:pinMessage";
+    }
+
+    impl Mutation for PinMessage {
+        type Output = Id<tables::PinnedMessages>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:unpinMessage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct UnpinMessage {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl UnpinMessage {
+        pub const PATH: &str = "messages:unpinMessage";
+    }
+
+    impl Mutation for UnpinMessage {
+        type Output = bool;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `messages:getPinnedMessages` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetPinnedMessages {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl GetPinnedMessages {
+        pub const PATH: &str = "messages:getPinnedMessages";
+    }
+
+    impl<T> Query<T> for GetPinnedMessages
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod presence {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `presence:heartbeat` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Heartbeat {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub interval: Option<f64>,
+        #[serde(rename = "sessionId")]
+        pub session_id: String,
+    }
+
+    impl Heartbeat {
+        pub const PATH: &str = "presence:heartbeat";
+    }
+
+    impl Mutation for Heartbeat {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `presence:getStatus` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetStatus {
+        #[serde(rename = "userId")]
+        pub user_id: Id<tables::Users>,
+    }
+
+    impl GetStatus {
+        pub const PATH: &str = "presence:getStatus";
+    }
+
+    impl<T> Query<T> for GetStatus
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum PatchUserStatusStatus {
+        #[serde(rename = "Online")]
+        Online,
+        #[serde(rename = "Idle")]
+        Idle,
+        #[serde(rename = "NotDisturb")]
+        NotDisturb,
+        #[serde(rename = "Invisible")]
+        Invisible,
+    }
+
+    /// The `presence:patchUserStatus` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PatchUserStatus {
+        pub status: PatchUserStatusStatus,
+    }
+
+    impl PatchUserStatus {
+        pub const PATH: &str = "presence:patchUserStatus";
+    }
+
+    impl Mutation for PatchUserStatus {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod private_conversations {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `privateConversations:createOrGetConversation` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CreateOrGetConversation {
+        #[serde(rename = "member2Id")]
+        pub member2_id: Id<tables::Users>,
+    }
+
+    impl CreateOrGetConversation {
+        pub const PATH: &str = "privateConversations:createOrGetConversation";
+    }
+
+    impl Mutation for CreateOrGetConversation {
+        type Output = Id<tables::Conversations>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `privateConversations:sendPrivateMessage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SendPrivateMessage {
+        pub content: String,
+        #[serde(rename = "conversationId")]
+        pub conversation_id: Id<tables::Conversations>,
+        #[serde(
+            rename = "referenceId",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub reference_id: Option<Id<tables::PrivateMessages>>,
+    }
+
+    impl SendPrivateMessage {
+        pub const PATH: &str = "privateConversations:sendPrivateMessage";
+    }
+
+    impl Mutation for SendPrivateMessage {
+        type Output = Id<tables::PrivateMessages>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `privateConversations:getPrivateMessages` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetPrivateMessages {
+        #[serde(rename = "conversationId")]
+        pub conversation_id: Id<tables::Conversations>,
+    }
+
+    impl GetPrivateMessages {
+        pub const PATH: &str = "privateConversations:getPrivateMessages";
+    }
+
+    impl<T> Query<T> for GetPrivateMessages
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `privateConversations:markPrivateMessagesRead` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct MarkPrivateMessagesRead {
+        #[serde(rename = "conversationId")]
+        pub conversation_id: Id<tables::Conversations>,
+        #[serde(rename = "lastReadMessageId")]
+        pub last_read_message_id: Id<tables::PrivateMessages>,
+    }
+
+    impl MarkPrivateMessagesRead {
+        pub const PATH: &str = "privateConversations:markPrivateMessagesRead";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct MarkPrivateMessagesReadOutput {
+        pub success: bool,
+    }
+
+    impl Mutation for MarkPrivateMessagesRead {
+        type Output = MarkPrivateMessagesReadOutput;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `privateConversations:getLastReadMessage` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetLastReadMessage {
+        #[serde(rename = "conversationId")]
+        pub conversation_id: Id<tables::Conversations>,
+    }
+
+    impl GetLastReadMessage {
+        pub const PATH: &str = "privateConversations:getLastReadMessage";
+    }
+
+    impl<T> Query<T> for GetLastReadMessage
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `privateConversations:getMyConversations` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetMyConversations {}
+
+    impl GetMyConversations {
+        pub const PATH: &str = "privateConversations:getMyConversations";
+    }
+
+    impl<T> Query<T> for GetMyConversations
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod reaction {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `reaction:getMemberEmojis` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetMemberEmojis {
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl GetMemberEmojis {
+        pub const PATH: &str = "reaction:getMemberEmojis";
+    }
+
+    impl<T> Query<T> for GetMemberEmojis
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `reaction:addReaction` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AddReaction {
+        pub emoji: String,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl AddReaction {
+        pub const PATH: &str = "reaction:addReaction";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AddReactionOutput {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub reason: Option<String>,
+        pub success: bool,
+    }
+
+    impl Mutation for AddReaction {
+        type Output = AddReactionOutput;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `reaction:removeReaction` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveReaction {
+        pub emoji: String,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl RemoveReaction {
+        pub const PATH: &str = "reaction:removeReaction";
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveReactionOutput {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub reason: Option<String>,
+        pub success: bool,
+    }
+
+    impl Mutation for RemoveReaction {
+        type Output = RemoveReactionOutput;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod roles {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::Query;
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `roles:serverRoles` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ServerRoles {
+        pub server: Id<tables::Servers>,
+    }
+
+    impl ServerRoles {
+        pub const PATH: &str = "roles:serverRoles";
+    }
+
+    impl<T> Query<T> for ServerRoles
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod server {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum CreateType {
+        #[serde(rename = "public")]
+        Public,
+        #[serde(rename = "private")]
+        Private,
+    }
+
+    /// The `server:create` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Create {
+        pub name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub storage: Option<Id<tables::Storage>>,
+        pub r#type: CreateType,
+    }
+
+    impl Create {
+        pub const PATH: &str = "server:create";
+    }
+
+    impl Mutation for Create {
+        type Output = Id<tables::Servers>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:getChannels` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetChannels {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub category: Option<Id<tables::Categories>>,
+        pub server: Id<tables::Servers>,
+    }
+
+    impl GetChannels {
+        pub const PATH: &str = "server:getChannels";
+    }
+
+    impl<T> Query<T> for GetChannels
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:getCategories` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetCategories {
+        pub server: Id<tables::Servers>,
+    }
+
+    impl GetCategories {
+        pub const PATH: &str = "server:getCategories";
+    }
+
+    impl<T> Query<T> for GetCategories
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:setServerBannerUrl` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SetServerBannerUrl {
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+        #[serde(rename = "storageId")]
+        pub storage_id: Id<tables::Storage>,
+    }
+
+    impl SetServerBannerUrl {
+        pub const PATH: &str = "server:setServerBannerUrl";
+    }
+
+    impl Mutation for SetServerBannerUrl {
+        type Output = Option<String>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:setServerImageUrl` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SetServerImageUrl {
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+        #[serde(rename = "storageId")]
+        pub storage_id: Id<tables::Storage>,
+    }
+
+    impl SetServerImageUrl {
+        pub const PATH: &str = "server:setServerImageUrl";
+    }
+
+    impl Mutation for SetServerImageUrl {
+        type Output = Option<String>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:updateServerDescription` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct UpdateServerDescription {
+        pub description: String,
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+    }
+
+    impl UpdateServerDescription {
+        pub const PATH: &str = "server:updateServerDescription";
+    }
+
+    impl Mutation for UpdateServerDescription {
+        type Output = bool;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:removeServerImage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveServerImage {
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+    }
+
+    impl RemoveServerImage {
+        pub const PATH: &str = "server:removeServerImage";
+    }
+
+    impl Mutation for RemoveServerImage {
+        type Output = Option<bool>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:removeServerBanner` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveServerBanner {
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+    }
+
+    impl RemoveServerBanner {
+        pub const PATH: &str = "server:removeServerBanner";
+    }
+
+    impl Mutation for RemoveServerBanner {
+        type Output = Option<bool>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:getPublicServers` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetPublicServers {}
+
+    impl GetPublicServers {
+        pub const PATH: &str = "server:getPublicServers";
+    }
+
+    impl<T> Query<T> for GetPublicServers
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `server:joinServer` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct JoinServer {
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+    }
+
+    impl JoinServer {
+        pub const PATH: &str = "server:joinServer";
+    }
+
+    impl Mutation for JoinServer {
+        type Output = Id<tables::Members>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod task {
+    use convex_client::leptos::Query;
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `task:get` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Get(pub serde_json::Value);
+
+    impl Get {
+        pub const PATH: &str = "task:get";
+    }
+
+    impl<T> Query<T> for Get
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod unread_messages {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `unreadMessages:initializeMemberChannelLastReadOnJoin` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct InitializeMemberChannelLastReadOnJoin {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl InitializeMemberChannelLastReadOnJoin {
+        pub const PATH: &str = "unreadMessages:initializeMemberChannelLastReadOnJoin";
+    }
+
+    impl Mutation for InitializeMemberChannelLastReadOnJoin {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `unreadMessages:updateMemberChannelLastRead` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct UpdateMemberChannelLastRead {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+        #[serde(rename = "messageId")]
+        pub message_id: Id<tables::Messages>,
+    }
+
+    impl UpdateMemberChannelLastRead {
+        pub const PATH: &str = "unreadMessages:updateMemberChannelLastRead";
+    }
+
+    impl Mutation for UpdateMemberChannelLastRead {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `unreadMessages:getUnreadMessagesCountInChannel` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetUnreadMessagesCountInChannel {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl GetUnreadMessagesCountInChannel {
+        pub const PATH: &str = "unreadMessages:getUnreadMessagesCountInChannel";
+    }
+
+    impl<T> Query<T> for GetUnreadMessagesCountInChannel
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `unreadMessages:getUnreadCountsForAllChannelsForMember` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetUnreadCountsForAllChannelsForMember {
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl GetUnreadCountsForAllChannelsForMember {
+        pub const PATH: &str = "unreadMessages:getUnreadCountsForAllChannelsForMember";
+    }
+
+    impl<T> Query<T> for GetUnreadCountsForAllChannelsForMember
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `unreadMessages:getLastReadMessageId` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetLastReadMessageId {
+        #[serde(rename = "channelId")]
+        pub channel_id: Id<tables::Channels>,
+        #[serde(rename = "memberId")]
+        pub member_id: Id<tables::Members>,
+    }
+
+    impl GetLastReadMessageId {
+        pub const PATH: &str = "unreadMessages:getLastReadMessageId";
+    }
+
+    impl<T> Query<T> for GetLastReadMessageId
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
+
+pub mod user {
+    use common::convex::{Id, tables};
+    use convex_client::leptos::{Mutation, Query};
+    use serde::de::DeserializeOwned;
+    use serde::{Deserialize, Serialize};
+
+    /// The `user:getServers` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetServers {}
+
+    impl GetServers {
+        pub const PATH: &str = "user:getServers";
+    }
+
+    impl<T> Query<T> for GetServers
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:getUser` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetUser {}
+
+    impl GetUser {
+        pub const PATH: &str = "user:getUser";
+    }
+
+    impl<T> Query<T> for GetUser
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:getMemberForServerByUser` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetMemberForServerByUser {
+        #[serde(rename = "serverId")]
+        pub server_id: Id<tables::Servers>,
+    }
+
+    impl GetMemberForServerByUser {
+        pub const PATH: &str = "user:getMemberForServerByUser";
+    }
+
+    impl<T> Query<T> for GetMemberForServerByUser
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:getMembers` query.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct GetMembers {
+        pub user: Id<tables::Users>,
+    }
+
+    impl GetMembers {
+        pub const PATH: &str = "user:getMembers";
+    }
+
+    impl<T> Query<T> for GetMembers
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:create` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Create {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub image_url: Option<String>,
+        pub name: String,
+    }
+
+    impl Create {
+        pub const PATH: &str = "user:create";
+    }
+
+    impl Mutation for Create {
+        type Output = ();
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:setBannerUrl` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SetBannerUrl {
+        #[serde(rename = "storageId")]
+        pub storage_id: Id<tables::Storage>,
+    }
+
+    impl SetBannerUrl {
+        pub const PATH: &str = "user:setBannerUrl";
+    }
+
+    impl Mutation for SetBannerUrl {
+        type Output = Option<String>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:setImageUrl` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SetImageUrl {
+        #[serde(rename = "storageId")]
+        pub storage_id: Id<tables::Storage>,
+    }
+
+    impl SetImageUrl {
+        pub const PATH: &str = "user:setImageUrl";
+    }
+
+    impl Mutation for SetImageUrl {
+        type Output = Option<String>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:removeUserImage` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveUserImage {}
+
+    impl RemoveUserImage {
+        pub const PATH: &str = "user:removeUserImage";
+    }
+
+    impl Mutation for RemoveUserImage {
+        type Output = Option<bool>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+
+    /// The `user:removeUserBanner` mutation.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RemoveUserBanner {}
+
+    impl RemoveUserBanner {
+        pub const PATH: &str = "user:removeUserBanner";
+    }
+
+    impl Mutation for RemoveUserBanner {
+        type Output = Option<bool>;
+
+        fn name(&self) -> String {
+            Self::PATH.to_string()
+        }
+    }
+}
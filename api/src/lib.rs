@@ -2,6 +2,7 @@ pub mod auth;
 pub mod category;
 pub mod channel;
 pub mod files;
+pub mod generated;
 pub mod presence;
 pub mod server;
 pub mod sidebar;
@@ -1,15 +1,21 @@
 use common::convex::PresenceStatus;
-use convex_client::leptos::Query;
-use serde::Serialize;
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct GetUserStatus {
-    #[serde(rename = "userId")]
-    pub user: String,
-}
+/// Reads as an `Option<`[`PresenceStatus`]`>`.
+pub use crate::generated::presence::GetStatus as GetUserStatus;
+pub use crate::generated::presence::{PatchUserStatus, PatchUserStatusStatus};
 
-impl Query<Option<PresenceStatus>> for GetUserStatus {
-    fn name(&self) -> String {
-        "presence:getStatus".to_string()
+impl PatchUserStatus {
+    /// Set the signed-in user's status to `status`, or `None` for
+    /// [`PresenceStatus::Offline`], which follows from their sessions
+    /// instead.
+    pub fn new(status: PresenceStatus) -> Option<Self> {
+        let status = match status {
+            PresenceStatus::Online => PatchUserStatusStatus::Online,
+            PresenceStatus::Idle => PatchUserStatusStatus::Idle,
+            PresenceStatus::NotDisturb => PatchUserStatusStatus::NotDisturb,
+            PresenceStatus::Invisible => PatchUserStatusStatus::Invisible,
+            PresenceStatus::Offline => return None,
+        };
+        Some(Self { status })
     }
 }
@@ -1,7 +1,8 @@
 pub mod form;
 
-use api::auth::{get_user, GoogleAuth, HandleGoogleRedirect, Logout, RefreshToken};
+use api::auth::{convex_token, get_user, GoogleAuth, HandleGoogleRedirect, Logout, RefreshToken};
 use common::user::User as Auth;
+use convex_client::leptos::ConvexProviderWithAuth;
 use leptos::context::Provider;
 use leptos::prelude::*;
 use leptos_router::hooks::{use_navigate, use_query};
@@ -118,6 +119,23 @@ pub fn AuthProvider(children: Children) -> impl IntoView {
     }
 }
 
+/// Provides the Convex client, authenticated as the signed-in user with the
+/// identity tokens the server mints for them.
+#[component]
+pub fn ConvexAuthProvider(children: Children) -> impl IntoView {
+    let AuthContext { auth, .. } = use_auth();
+    let is_authenticated = Signal::derive(move || matches!(auth.get(), Some(Ok(Some(_)))));
+
+    view! {
+        <ConvexProviderWithAuth
+            is_authenticated
+            fetch_access_token=|_force_refresh| async { convex_token().await.ok().flatten() }
+        >
+            {children()}
+        </ConvexProviderWithAuth>
+    }
+}
+
 #[component]
 pub fn LogOut(
     #[prop(optional, into)] variant: Signal<ButtonVariants>,
@@ -1,3 +1,5 @@
+use common::convex::tables::Storage;
+use common::convex::Id;
 use gloo_file::{Blob, File};
 use serde::Deserialize;
 
@@ -16,5 +18,5 @@ pub async fn upload_file(file: &File, url: String) -> anyhow::Result<UploadResul
 #[derive(Debug, Deserialize)]
 pub struct UploadResult {
     #[serde(rename = "storageId")]
-    pub storage_id: String,
+    pub storage_id: Id<Storage>,
 }
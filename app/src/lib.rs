@@ -4,7 +4,6 @@ mod components;
 mod routes;
 
 use capi_ui::floating_tree::provide_floating_tree;
-use convex_client::leptos::ConvexProvider;
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
 use leptos_router::{
@@ -15,7 +14,7 @@ use routes::Landing;
 
 use self::{
     components::{
-        auth::{use_auth, AuthProvider},
+        auth::{use_auth, AuthProvider, ConvexAuthProvider},
         ui::theme::ThemeProvider,
     },
     routes::{
@@ -65,7 +64,7 @@ pub fn App() -> impl IntoView {
 
         <ThemeProvider>
             <AuthProvider>
-                <ConvexProvider>
+                <ConvexAuthProvider>
                     <Toasts>
                         <Router>
                             <main id="app">
@@ -117,7 +116,7 @@ pub fn App() -> impl IntoView {
                             </main>
                         </Router>
                     </Toasts>
-                </ConvexProvider>
+                </ConvexAuthProvider>
             </AuthProvider>
         </ThemeProvider>
     }
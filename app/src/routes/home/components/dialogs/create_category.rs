@@ -1,9 +1,9 @@
 use std::time::Duration;
 
+use api::generated::category::Create as CreateCategory;
 use common::convex::Server;
-use convex_client::leptos::{Mutation, UseMutation};
+use convex_client::leptos::UseMutation;
 use leptos::prelude::*;
-use serde::Serialize;
 
 use crate::components::auth::use_auth;
 use icons::IconLoader;
@@ -14,21 +14,6 @@ use capi_ui::dialog::*;
 use capi_ui::input::Input;
 use capi_ui::label::Label;
 
-#[derive(Debug, Serialize, Clone)]
-pub struct CreateCategory {
-    name: String,
-    server: String,
-    auth: i64,
-}
-
-impl Mutation for CreateCategory {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "category:create".into()
-    }
-}
-
 #[component]
 pub fn CreateCategoryDialog(open: RwSignal<bool>, server: Signal<Option<Server>>) -> impl IntoView {
     let create_category = UseMutation::new::<CreateCategory>();
@@ -110,8 +95,8 @@ pub fn CreateCategoryDialog(open: RwSignal<bool>, server: Signal<Option<Server>>
                         on:click=move |_| {
                             if !name.get().is_empty() {
                                 if let Some(server) = server.get() {
-                                    if let Some(user) = auth.get().and_then(|res|res.ok()).flatten() {
-                                        let input = CreateCategory { name: name.get(), server: server.id, auth: user.id };
+                                    if auth.get().and_then(|res|res.ok()).flatten().is_some() {
+                                        let input = CreateCategory { name: name.get(), server: server.id };
                                         create_category.dispatch(input.clone());
                                     }
                                 }
@@ -1,8 +1,7 @@
+use api::generated::channel::Create as CreateChannel;
 use common::convex::{Category, Server};
-use convex_client::leptos::Mutation;
 use convex_client::leptos::UseMutation;
 use leptos::prelude::*;
-use serde::Serialize;
 use std::time::Duration;
 
 use crate::components::auth::use_auth;
@@ -15,23 +14,6 @@ use capi_ui::label::*;
 use icons::IconChevronDown;
 use icons::IconLoader;
 
-#[derive(Debug, Serialize, Clone)]
-pub struct CreateChannel {
-    name: String,
-    server: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    category: Option<String>,
-    auth: i64,
-}
-
-impl Mutation for CreateChannel {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "channel:create".into()
-    }
-}
-
 #[component]
 pub fn CreateChannelDialog(
     open: RwSignal<bool>,
@@ -166,8 +148,8 @@ pub fn CreateChannelDialog(
                         on:click=move |_| {
                             if !name.get().is_empty() {
                                 if let Some(server) = server.get() {
-                                    if let Some(user) = auth.get().and_then(|res|res.ok()).flatten() {
-                                        let input = CreateChannel { name: name.get(), server: server.id , category: selected_category.get().map(|category| category.id), auth: user.id };
+                                    if auth.get().and_then(|res|res.ok()).flatten().is_some() {
+                                        let input = CreateChannel { name: name.get(), server: server.id , category: selected_category.get().map(|category| category.id) };
                                         create_channel.dispatch(input);
                                     }
                                 }
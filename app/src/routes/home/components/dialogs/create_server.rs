@@ -1,14 +1,15 @@
 use std::time::Duration;
 
 use api::files::GenerateUploadUrl;
+use api::generated::server::{Create as CreateServer, CreateType};
 use chrono::Utc;
-use common::convex::ServerType;
+use common::convex::tables::Storage;
+use common::convex::Id;
 use common::files::{read_file, ClientFile};
 use convex_client::leptos::{Mutation, UseMutation};
 use gloo_file::File;
 use leptos::task::spawn_local;
 use leptos::{html, prelude::*};
-use serde::Serialize;
 use wasm_bindgen::JsCast;
 use web_sys::{Event, HtmlInputElement};
 
@@ -22,24 +23,6 @@ use capi_ui::input::Input;
 use capi_ui::label::*;
 use icons::{IconImage, IconLoader, IconX};
 
-#[derive(Debug, Serialize, Clone)]
-pub struct CreateServer {
-    name: String,
-    auth: i64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    storage: Option<String>,
-    #[serde(rename = "type")]
-    _type: ServerType,
-}
-
-impl Mutation for CreateServer {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "server:create".into()
-    }
-}
-
 #[component]
 pub fn CreateServerDialog(open: RwSignal<bool>) -> impl IntoView {
     let auth = use_auth().auth;
@@ -79,11 +62,11 @@ pub fn CreateServerDialog(open: RwSignal<bool>) -> impl IntoView {
             let file_opt = file_opt.to_owned();
             let mut client_mut = client.to_owned();
             async move {
-                if let Some(Ok(Some(auth_data))) = auth {
-                    let mut storage_id: Option<String> = None;
+                if let Some(Ok(Some(_))) = auth {
+                    let mut storage_id: Option<Id<Storage>> = None;
                     if let Some(file) = file_opt {
-                        let upload_url = GenerateUploadUrl { auth: auth_data.id };
-                        if let Ok(Some(url)) = upload_url.run(&mut client_mut).await {
+                        let upload_url = GenerateUploadUrl {};
+                        if let Ok(url) = upload_url.run(&mut client_mut).await {
                             if let Ok(UploadResult {
                                 storage_id: uploaded_id,
                             }) = upload_file(&file, url).await
@@ -95,12 +78,11 @@ pub fn CreateServerDialog(open: RwSignal<bool>) -> impl IntoView {
 
                     let create_server_input = CreateServer {
                         name: server_name,
-                        auth: auth_data.id,
                         storage: storage_id,
-                        _type: if is_private.get() {
-                            ServerType::Private
+                        r#type: if is_private.get() {
+                            CreateType::Private
                         } else {
-                            ServerType::Public
+                            CreateType::Public
                         },
                     };
                     let _ = create_server_input.run(&mut client_mut).await;
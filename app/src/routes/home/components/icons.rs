@@ -1,8 +1,8 @@
-use convex_client::leptos::{Mutation, UseMutation};
+use api::generated::invitations::JoinServerWithInvitation;
+use convex_client::leptos::UseMutation;
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_router::hooks::use_location;
-use serde::Serialize;
 
 use crate::components::ui::sidebar::*;
 use crate::routes::home::components::dialogs::create_server::CreateServerDialog;
@@ -19,20 +19,6 @@ use icons::{
 
 use super::sidebar::{ServerData, SideBarOption};
 
-#[derive(Debug, Serialize, Clone)]
-struct JoinWithInvitation {
-    invitation: String,
-    user: String,
-}
-
-impl Mutation for JoinWithInvitation {
-    type Output = Option<String>;
-
-    fn name(&self) -> String {
-        "invitations:joinServerWithInvitation".into()
-    }
-}
-
 #[component]
 pub fn SidebarIcons(
     data: Signal<Option<Vec<ServerData>>>,
@@ -227,7 +213,7 @@ pub fn ServerMenu(
 #[component]
 pub fn JoinServerDialog(open: RwSignal<bool>) -> impl IntoView {
     let user = use_profile();
-    let join_server = UseMutation::new();
+    let join_server = UseMutation::new::<JoinServerWithInvitation>();
     let (name, set_name) = signal(String::default());
     let pending = join_server.pending();
     view! {
@@ -256,10 +242,9 @@ pub fn JoinServerDialog(open: RwSignal<bool>) -> impl IntoView {
                     <button
                         on:click=move |_| {
                             if !name.get().is_empty() {
-                                if let Some(user) = user.get() {
-                                    join_server.dispatch(JoinWithInvitation {
-                                        invitation: name.get(),
-                                        user: user.id
+                                if user.get().is_some() {
+                                    join_server.dispatch(JoinServerWithInvitation {
+                                        invitation_code: name.get(),
                                     });
                                 }
                             }
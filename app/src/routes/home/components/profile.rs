@@ -1,9 +1,8 @@
 use api::auth::Logout;
-use api::presence::GetUserStatus;
+use api::presence::{GetUserStatus, PatchUserStatus};
 use common::convex::PresenceStatus;
-use convex_client::leptos::{Mutation, UseMutation, UseQuery};
+use convex_client::leptos::{UseMutation, UseQuery};
 use leptos::prelude::*;
-use serde::Serialize;
 use strum::IntoEnumIterator;
 
 use crate::components::auth::use_auth;
@@ -15,30 +14,18 @@ use capi_ui::button::*;
 use capi_ui::dropwdown::*;
 use icons::{IconHeadphones, IconLogOut, IconMic, IconSettings};
 
-#[derive(Debug, Clone, Serialize)]
-pub struct SetUserStatus {
-    auth: i64,
-    status: PresenceStatus,
-}
-
-impl Mutation for SetUserStatus {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "presence:patchUserStatus".into()
-    }
-}
-
 #[component]
 pub fn Profile() -> impl IntoView {
     let user = use_profile();
 
-    let status = UseQuery::new(move || user.get().map(|user| GetUserStatus { user: user.id }));
+    let status = UseQuery::new::<Option<PresenceStatus>, _>(move || {
+        user.get().map(|user| GetUserStatus { user_id: user.id })
+    });
     let auth_context = use_auth();
     let auth = auth_context.auth;
     let log_out = auth_context.log_out;
 
-    let set_status = UseMutation::new::<SetUserStatus>();
+    let set_status = UseMutation::new::<PatchUserStatus>();
 
     let open_user_settings = RwSignal::new(false);
 
@@ -113,8 +100,10 @@ pub fn Profile() -> impl IntoView {
                                                         PresenceStatus::iter().map(|status| {
                                                             view!{
                                                                 <DropdownMenuRadioItem value=status.to_string() on:click=move |_| {
-                                                                    if let Some(auth)= auth.get().and_then(|res| res.ok()).flatten() {
-                                                                        set_status.dispatch(SetUserStatus { auth: auth.id, status });
+                                                                    if auth.get().and_then(|res| res.ok()).flatten().is_some() {
+                                                                        if let Some(patch) = PatchUserStatus::new(status) {
+                                                                            set_status.dispatch(patch);
+                                                                        }
                                                                     }
                                                                 }>
                                                                     {status.to_string()}
@@ -45,15 +45,14 @@ pub fn ProfileBanner(children: Children, server: Signal<Option<Server>>) -> impl
         let file = file.to_owned();
         let server = server.get();
         async move {
-            if let (Some(Ok(Some(auth_data))), Some(server)) = (auth, server) {
-                let upload_url = GenerateUploadUrl { auth: auth_data.id };
+            if let (Some(Ok(Some(_))), Some(server)) = (auth, server) {
+                let upload_url = GenerateUploadUrl {};
                 let url = upload_url.run(&mut client_mut).await;
-                if let Ok(Some(url)) = url {
+                if let Ok(url) = url {
                     if let Ok(UploadResult { storage_id }) = upload_file(&file, url).await {
                         let set_image = SetServerBannerUrl {
-                            auth: auth_data.id,
-                            storage: storage_id,
-                            server: server.id,
+                            storage_id,
+                            server_id: server.id,
                         };
                         let _ = set_image.run(&mut client_mut).await;
                     }
@@ -69,10 +68,9 @@ pub fn ProfileBanner(children: Children, server: Signal<Option<Server>>) -> impl
     let remove_user_banner = UseMutation::new::<RemoveServerBanner>();
 
     let remove_user_banner = Callback::new(move |()| {
-        if let (Some(Ok(Some(auth))), Some(server)) = (auth.get(), server.get()) {
+        if let (Some(Ok(Some(_))), Some(server)) = (auth.get(), server.get()) {
             remove_user_banner.dispatch(RemoveServerBanner {
-                auth: auth.id,
-                server: server.id,
+                server_id: server.id,
             });
         }
     });
@@ -165,15 +163,14 @@ pub fn ProfileImageSetting(server: Signal<Option<Server>>) -> impl IntoView {
         let file = file.to_owned();
         let server = server.get();
         async move {
-            if let (Some(Ok(Some(auth_data))), Some(server)) = (auth, server) {
-                let upload_url = GenerateUploadUrl { auth: auth_data.id };
+            if let (Some(Ok(Some(_))), Some(server)) = (auth, server) {
+                let upload_url = GenerateUploadUrl {};
                 let url = upload_url.run(&mut client_mut).await;
-                if let Ok(Some(url)) = url {
+                if let Ok(url) = url {
                     if let Ok(UploadResult { storage_id }) = upload_file(&file, url).await {
                         let set_image = SetServerImageUrl {
-                            auth: auth_data.id,
-                            storage: storage_id,
-                            server: server.id,
+                            storage_id,
+                            server_id: server.id,
                         };
                         let _ = set_image.run(&mut client_mut).await;
                     }
@@ -189,10 +186,9 @@ pub fn ProfileImageSetting(server: Signal<Option<Server>>) -> impl IntoView {
     let remove_server_image = UseMutation::new::<RemoveServerImage>();
 
     let remove_server_image = Callback::new(move |()| {
-        if let (Some(Ok(Some(auth))), Some(server)) = (auth.get(), server.get()) {
+        if let (Some(Ok(Some(_))), Some(server)) = (auth.get(), server.get()) {
             remove_server_image.dispatch(RemoveServerImage {
-                auth: auth.id,
-                server: server.id,
+                server_id: server.id,
             });
         }
     });
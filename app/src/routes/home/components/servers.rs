@@ -1,10 +1,11 @@
 use api::category::GetCategories;
-use common::convex::{Member, Role, Server};
-use convex_client::leptos::{Query, UseQuery};
+use api::generated::member::GetLastVisitedChannel;
+use common::convex::tables::Channels;
+use common::convex::{Category, Id, Member, Role, Server};
+use convex_client::leptos::UseQuery;
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_router::hooks::use_params_map;
-use serde::Serialize;
 
 use crate::components::auth::use_auth;
 use crate::components::roles::RolesProvider;
@@ -46,18 +47,6 @@ pub fn ServersItems(
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
-pub struct GetLastVisitedChannel {
-    auth: i64,
-    member: String,
-}
-
-impl Query<Option<String>> for GetLastVisitedChannel {
-    fn name(&self) -> String {
-        "member:getLastVisitedChannel".into()
-    }
-}
-
 #[component]
 pub fn ServerItem(
     server: Server,
@@ -70,18 +59,17 @@ pub fn ServerItem(
     let member = RwSignal::new(member);
     let auth = use_auth();
     let roles = RwSignal::new(Some(roles));
-    let categories = UseQuery::new(move || {
+    let categories = UseQuery::new::<Vec<Category>, _>(move || {
         Some(GetCategories {
             server: server.get().id,
         })
     });
-    let last_visited_channel = UseQuery::new(move || {
+    let last_visited_channel = UseQuery::new::<Option<Id<Channels>>, _>(move || {
         auth.auth
             .get()
             .and_then(|auth| auth.ok())
             .flatten()
-            .map(|auth| GetLastVisitedChannel {
-                auth: auth.id,
+            .map(|_| GetLastVisitedChannel {
                 member: member.get().id,
             })
     });
@@ -90,7 +78,7 @@ pub fn ServerItem(
         params
             .get()
             .get("server")
-            .is_some_and(|s| s == server.get().id)
+            .is_some_and(|s| s == server.get().id.as_str())
     });
     let href = move || {
         if let Some(last) = last_visited_channel
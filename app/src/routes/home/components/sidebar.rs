@@ -24,11 +24,11 @@ pub fn SideBar(
 ) -> impl IntoView {
     let auth = use_auth().auth;
 
-    let data = UseQuery::new(move || {
+    let data = UseQuery::new::<Vec<ServerData>, _>(move || {
         auth.get()
             .and_then(|res| res.ok())
             .flatten()
-            .map(|auth| GetServers { auth: auth.id })
+            .map(|_| GetServers {})
     });
 
     let data = Signal::derive(move || data.get().and_then(|res| res.ok()));
@@ -61,15 +61,12 @@ pub fn Profile() -> impl IntoView {
         let mut client_mut = client.to_owned();
         let file = file.to_owned();
         async move {
-            if let Some(Ok(Some(auth_data))) = auth {
-                let upload_url = GenerateUploadUrl { auth: auth_data.id };
+            if let Some(Ok(Some(_))) = auth {
+                let upload_url = GenerateUploadUrl {};
                 let url = upload_url.run(&mut client_mut).await;
-                if let Ok(Some(url)) = url {
+                if let Ok(url) = url {
                     if let Ok(UploadResult { storage_id }) = upload_file(&file, url).await {
-                        let set_image = SetImageUrl {
-                            auth: auth_data.id,
-                            storage_id,
-                        };
+                        let set_image = SetImageUrl { storage_id };
                         let _ = set_image.run(&mut client_mut).await;
                     }
                 }
@@ -84,8 +81,8 @@ pub fn Profile() -> impl IntoView {
     let remove_user_image = UseMutation::new::<RemoveUserImage>();
 
     let remove_user_image = Callback::new(move |_| {
-        if let Some(Ok(Some(auth))) = auth.get() {
-            remove_user_image.dispatch(RemoveUserImage { auth: auth.id });
+        if let Some(Ok(Some(_))) = auth.get() {
+            remove_user_image.dispatch(RemoveUserImage {});
         }
     });
 
@@ -125,15 +122,12 @@ pub fn ProfileBanner(
         let mut client_mut = client.to_owned();
         let file = file.to_owned();
         async move {
-            if let Some(Ok(Some(auth_data))) = auth {
-                let upload_url = GenerateUploadUrl { auth: auth_data.id };
+            if let Some(Ok(Some(_))) = auth {
+                let upload_url = GenerateUploadUrl {};
                 let url = upload_url.run(&mut client_mut).await;
-                if let Ok(Some(url)) = url {
+                if let Ok(url) = url {
                     if let Ok(UploadResult { storage_id }) = upload_file(&file, url).await {
-                        let set_image = SetBannerUrl {
-                            auth: auth_data.id,
-                            storage_id,
-                        };
+                        let set_image = SetBannerUrl { storage_id };
                         let _ = set_image.run(&mut client_mut).await;
                     }
                 }
@@ -148,8 +142,8 @@ pub fn ProfileBanner(
     let remove_user_banner = UseMutation::new::<RemoveUserBanner>();
 
     let remove_user_banner = Callback::new(move |()| {
-        if let Some(Ok(Some(auth))) = auth.get() {
-            remove_user_banner.dispatch(RemoveUserBanner { auth: auth.id });
+        if let Some(Ok(Some(_))) = auth.get() {
+            remove_user_banner.dispatch(RemoveUserBanner {});
         }
     });
 
@@ -1,3 +1,4 @@
+use convex_client::ConvexClientError;
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_router::hooks::use_location;
@@ -9,7 +10,7 @@ use super::ConversationDetails;
 
 #[component]
 pub fn ConversationItems(
-    conversations: ReadSignal<Option<Result<Vec<ConversationDetails>, String>>>,
+    conversations: Signal<Option<Result<Vec<ConversationDetails>, ConvexClientError>>>,
 ) -> impl IntoView {
     let location = use_location();
     let path = location.pathname;
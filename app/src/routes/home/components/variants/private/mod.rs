@@ -1,6 +1,7 @@
 mod conversations;
 
-use convex_client::leptos::{Query, UseQuery};
+use api::generated::private_conversations::GetMyConversations;
+use convex_client::leptos::UseQuery;
 use icons::IconContact;
 use leptos::prelude::*;
 use leptos_router::components::A;
@@ -12,11 +13,6 @@ use crate::components::ui::sidebar::*;
 
 use self::conversations::ConversationItems;
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
-pub struct GetMyConversations {
-    pub auth: i64,
-}
-
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OtherMemberDetails {
     pub _id: String,
@@ -43,20 +39,14 @@ pub struct ConversationDetails {
     pub unread_count: f64,
 }
 
-impl Query<Vec<ConversationDetails>> for GetMyConversations {
-    fn name(&self) -> String {
-        "privateConversations:getMyConversations".to_string()
-    }
-}
-
 #[component]
 pub fn PrivateSideBar() -> impl IntoView {
     let auth = use_auth().auth;
-    let conversations = UseQuery::new(move || {
+    let conversations = UseQuery::new::<Vec<ConversationDetails>, _>(move || {
         auth.get()
             .and_then(|auth| auth.ok())
             .flatten()
-            .map(|auth| GetMyConversations { auth: auth.id })
+            .map(|_| GetMyConversations {})
     });
     view! {
         <SidebarContent>
@@ -1,4 +1,5 @@
 use common::convex::Channel;
+use convex_client::ConvexClientError;
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_router::hooks::use_location;
@@ -10,7 +11,9 @@ use capi_ui::dropwdown::*;
 use icons::{IconEllipsis, IconTrash};
 
 #[component]
-pub fn ChannelsItems(channels: ReadSignal<Option<Result<Vec<Channel>, String>>>) -> impl IntoView {
+pub fn ChannelsItems(
+    channels: Signal<Option<Result<Vec<Channel>, ConvexClientError>>>,
+) -> impl IntoView {
     let location = use_location();
     let path = location.pathname;
     let current_channel = Memo::new(move |_| {
@@ -44,7 +47,7 @@ pub fn ChannelItem(channel: Channel, current_channel: Memo<Option<String>>) -> i
                     is_active=Signal::derive(
                         move || {
                             current_channel.get().is_some_and(|curr| {
-                                 id.get_value() == curr
+                                 id.get_value().as_str() == curr
                             })
                         }
                     )
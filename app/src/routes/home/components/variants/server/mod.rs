@@ -6,11 +6,10 @@ use api::category::GetCategories;
 use api::channel::GetChannels;
 use api::server::ServerData;
 use common::convex::{Category, Member, Server};
-use convex_client::leptos::{Mutation, UseQuery};
+use convex_client::leptos::UseQuery;
 use leptos::prelude::*;
 use leptos_dom::log;
 use leptos_router::hooks::use_location;
-use serde::Serialize;
 
 use crate::components::roles::*;
 use crate::components::ui::sidebar::*;
@@ -39,7 +38,7 @@ pub fn ServerSideBar(data: Signal<Option<Vec<ServerData>>>) -> impl IntoView {
             .unwrap_or_default();
         data.get().and_then(|data| {
             data.iter()
-                .find(|ServerData { server, .. }| server.id == id)
+                .find(|ServerData { server, .. }| server.id.as_str() == id)
                 .map(|data| data.server.clone())
         })
     });
@@ -53,7 +52,7 @@ pub fn ServerSideBar(data: Signal<Option<Vec<ServerData>>>) -> impl IntoView {
             .unwrap_or_default();
         data.get().and_then(|data| {
             data.iter()
-                .find(|ServerData { server, .. }| server.id == id)
+                .find(|ServerData { server, .. }| server.id.as_str() == id)
                 .map(|data| data.member.clone())
         })
     });
@@ -67,7 +66,7 @@ pub fn ServerSideBar(data: Signal<Option<Vec<ServerData>>>) -> impl IntoView {
             .unwrap_or_default();
         data.get().and_then(|data| {
             data.iter()
-                .find(|ServerData { server, .. }| server.id == id)
+                .find(|ServerData { server, .. }| server.id.as_str() == id)
                 .map(|data| data.roles.clone())
         })
     });
@@ -190,36 +189,20 @@ pub fn SideBarContextMenu(
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct CreateInvitation {
-    server: String,
-    member: String,
-    #[serde(rename = "expiresInMinutes")]
-    expires: f64,
-}
-
-impl Mutation for CreateInvitation {
-    type Output = String;
-
-    fn name(&self) -> String {
-        "invitations:createInvitation".into()
-    }
-}
-
 #[component]
 pub fn InvitationDialog(
     open: RwSignal<bool>,
     server: Signal<Option<Server>>,
     member: Signal<Option<Member>>,
 ) -> impl IntoView {
-    // let invitation = UseMutation::new();
+    // let invitation = UseMutation::new::<api::generated::invitations::CreateInvitation>();
     //
     // Effect::new(move |_| {
     //     if let (Some(server), Some(member)) = (server.get(), member.get()) {
     //         invitation.dispatch(CreateInvitation {
     //             server: server.id,
     //             member: member.id,
-    //             expires: 1.0 * 60.0 * 7.0,
+    //             expires_in_minutes: 1.0 * 60.0 * 7.0,
     //         });
     //     }
     // });
@@ -3,14 +3,14 @@ pub mod private;
 pub mod server;
 pub mod servers;
 
+use api::generated::presence::Heartbeat;
 use api::user::GetUser;
 use common::convex::User;
-use convex_client::leptos::{Mutation, UseMutation, UseQuery};
+use convex_client::leptos::{UseMutation, UseQuery};
 use leptos::prelude::*;
 use leptos_router::components::Outlet;
 use leptos_router::hooks::use_location;
 use leptos_use::use_interval_fn;
-use serde::Serialize;
 use uuid::Uuid;
 
 use crate::components::auth::use_auth;
@@ -34,34 +34,20 @@ fn get_route_from_path(path: &str) -> SideBarRoute {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct HeartBeat {
-    user: String,
-    #[serde(rename = "sessionId")]
-    session: String,
-}
-
-impl Mutation for HeartBeat {
-    type Output = ();
-    fn name(&self) -> String {
-        "presence:heartbeat".into()
-    }
-}
-
 pub fn use_profile() -> Signal<Option<User>> {
     use_context().expect("should acces to the use profile context")
 }
 
 #[component]
 pub fn Home() -> impl IntoView {
-    let presence = UseMutation::new::<HeartBeat>();
+    let presence = UseMutation::new::<Heartbeat>();
     let auth = use_auth();
-    let user = UseQuery::new(move || {
+    let user = UseQuery::new::<Option<User>, _>(move || {
         auth.auth
             .get()
             .and_then(|res| res.ok())
             .flatten()
-            .map(|user| GetUser { auth: user.id })
+            .map(|_| GetUser {})
     });
     let user = Signal::derive(move || user.get().and_then(|res| res.ok()).flatten());
 
@@ -71,10 +57,10 @@ pub fn Home() -> impl IntoView {
     {
         let _ = use_interval_fn(
             move || {
-                if let Some(user) = user.get() {
-                    presence.dispatch(HeartBeat {
-                        user: user.id,
-                        session: session.get().to_string(),
+                if user.get().is_some() {
+                    presence.dispatch(Heartbeat {
+                        session_id: session.get().to_string(),
+                        interval: None,
                     });
                 }
             },
@@ -1,3 +1,4 @@
+use convex_client::ConvexClientError;
 use leptos::html::Div;
 use leptos::prelude::*;
 use leptos_use::{use_element_bounding, UseElementBoundingReturn};
@@ -7,7 +8,7 @@ use super::PrivateMessageDetails;
 #[component]
 pub fn Messages(
     sender_ref: NodeRef<Div>,
-    messages: ReadSignal<Option<Result<Vec<PrivateMessageDetails>, String>>>,
+    messages: Signal<Option<Result<Vec<PrivateMessageDetails>, ConvexClientError>>>,
 ) -> impl IntoView {
     let style = RwSignal::new(String::default());
     let UseElementBoundingReturn { height, .. } = use_element_bounding(sender_ref);
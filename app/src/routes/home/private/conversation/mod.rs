@@ -1,7 +1,10 @@
 mod messages;
 mod sender;
 
-use convex_client::leptos::{Query, UseQuery};
+use api::generated::private_conversations::GetPrivateMessages;
+use common::convex::tables::Conversations;
+use common::convex::Id;
+use convex_client::leptos::UseQuery;
 use leptos::prelude::*;
 use leptos_router::hooks::use_location;
 use serde::{Deserialize, Serialize};
@@ -28,32 +31,6 @@ pub struct PrivateMessageDetails {
     pub sender_image_url: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
-pub struct GetPrivateMessages {
-    #[serde(rename = "conversationId")]
-    pub conversation_id: String,
-    pub auth: i64,
-}
-
-impl Query<Vec<PrivateMessageDetails>> for GetPrivateMessages {
-    fn name(&self) -> String {
-        "privateConversations:getPrivateMessages".to_string()
-    }
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq)]
-pub struct GetLastReadMessage {
-    #[serde(rename = "conversationId")]
-    pub conversation_id: String,
-    pub auth: i64,
-}
-
-impl Query<Option<String>> for GetLastReadMessage {
-    fn name(&self) -> String {
-        "privateConversations:getLastReadMessage".to_string()
-    }
-}
-
 #[component]
 pub fn Conversation() -> impl IntoView {
     let location = use_location();
@@ -62,14 +39,13 @@ pub fn Conversation() -> impl IntoView {
         path.get()
             .split('/')
             .nth(3)
-            .map(|conversation| conversation.to_string())
+            .and_then(|conversation| conversation.parse::<Id<Conversations>>().ok())
     });
     let auth = use_auth().auth;
-    let messages = UseQuery::new(move || {
-        let auth = auth.get().and_then(|auth| auth.ok()).flatten()?;
+    let messages = UseQuery::new::<Vec<PrivateMessageDetails>, _>(move || {
+        auth.get().and_then(|auth| auth.ok()).flatten()?;
         let conversation = current_conversation.get()?;
         Some(GetPrivateMessages {
-            auth: auth.id,
             conversation_id: conversation,
         })
     });
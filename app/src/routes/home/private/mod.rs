@@ -1,10 +1,11 @@
 pub mod conversation;
 
+use api::generated::friends::GetFriends;
 use capi_ui::avatar::*;
 use capi_ui::card::*;
 use capi_ui::divider::Separator;
 use capi_ui::Orientation;
-use convex_client::leptos::{Query, UseQuery};
+use convex_client::leptos::UseQuery;
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -45,60 +46,14 @@ pub struct SentFriendRequest {
     pub status: String,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
-pub struct GetFriends {
-    pub auth: i64,
-}
-
-impl Query<Vec<FriendDetails>> for GetFriends {
-    fn name(&self) -> String {
-        "friends:getFriends".to_string()
-    }
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq)]
-pub struct GetPendingFriendRequests {
-    pub auth: i64,
-}
-
-impl Query<Vec<PendingFriendRequest>> for GetPendingFriendRequests {
-    fn name(&self) -> String {
-        "friends:getPendingFriendRequests".to_string()
-    }
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq)]
-pub struct GetSentFriendRequests {
-    pub auth: i64,
-}
-
-impl Query<Vec<SentFriendRequest>> for GetSentFriendRequests {
-    fn name(&self) -> String {
-        "friends:getSentFriendRequests".to_string()
-    }
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq)]
-pub struct GetFriendshipStatus {
-    #[serde(rename = "userId")]
-    pub user_id: String,
-    pub auth: i64,
-}
-
-impl Query<String> for GetFriendshipStatus {
-    fn name(&self) -> String {
-        "friends:getFriendshipStatus".to_string()
-    }
-}
-
 #[component]
 pub fn Friends() -> impl IntoView {
     let auth = use_auth().auth;
-    let friends = UseQuery::new(move || {
+    let friends = UseQuery::new::<Vec<FriendDetails>, _>(move || {
         auth.get()
             .and_then(|auth| auth.ok())
             .flatten()
-            .map(|auth| GetFriends { auth: auth.id })
+            .map(|_| GetFriends {})
     });
     view! {
         <Header/>
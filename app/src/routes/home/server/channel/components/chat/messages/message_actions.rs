@@ -1,3 +1,4 @@
+use api::generated::reaction::AddReaction;
 use common::convex::ChannelMessage;
 use common::convex::Member;
 use convex_client::leptos::UseMutation;
@@ -7,7 +8,6 @@ use icons::IconSmile;
 use leptos::prelude::*;
 
 use crate::components::emojis::EmojiSelector;
-use crate::routes::server::channel::components::chat::messages::message_reactions::AddReaction;
 use crate::routes::server::channel::components::chat::messages::pin::PinMessageButton;
 use crate::routes::server::channel::components::chat::ChatContext;
 use capi_ui::button::*;
@@ -24,8 +24,8 @@ pub fn MessageActions(
     let on_select_emoji = Callback::new(move |emoji: &'static Emoji| {
         if let Some(member) = member.get() {
             add_reaction.dispatch(AddReaction {
-                message: msg.get_value().id,
-                member: member.id,
+                message_id: msg.get_value().id,
+                member_id: member.id,
                 emoji: emoji.to_string(),
             });
         }
@@ -32,7 +32,7 @@ pub fn MessageHeader(member: Member, date: f64) -> impl IntoView {
 
     let status = UseQuery::new(move || {
         Some(GetUserStatus {
-            user: member.get_value().user,
+            user_id: member.get_value().user,
         })
     });
 
@@ -1,3 +1,4 @@
+use api::generated::reaction::AddReaction;
 use common::convex::{ChannelMessage, Member};
 use convex_client::leptos::UseMutation;
 use emojis::Emoji;
@@ -11,7 +12,6 @@ use super::message_reactions::MessageReactions;
 use super::message_reference::ReferencedMessageDisplay;
 use crate::components::emojis::EmojiSelector;
 use crate::routes::server::channel::components::chat::messages::message_actions::MessageActions;
-use crate::routes::server::channel::components::chat::messages::message_reactions::AddReaction;
 use crate::routes::server::channel::components::chat::ChatContext;
 use capi_ui::context::*;
 use icons::IconCornerUpLeft;
@@ -64,8 +64,8 @@ pub fn MessageItem(
     let on_select_emoji = Callback::new(move |emoji: &'static Emoji| {
         if let Some(member) = member.get() {
             add_reaction.dispatch(AddReaction {
-                message: msg.get_value().id,
-                member: member.id,
+                message_id: msg.get_value().id,
+                member_id: member.id,
                 emoji: emoji.to_string(),
             });
         }
@@ -91,7 +91,7 @@ pub fn MessageItem(
                     data-[highlight=true]:bg-purple/10 data-[highlight=true]:border-l-purple
                     border-l border-l-transparent data-[context=true]:bg-accent/50 hover:bg-accent/50 px-8 group min-h-9 flex flex-col justify-center relative"
                 {..}
-                id=msg.get_value().id.clone()
+                id=msg.get_value().id.to_string()
                 data-response=move || msg_ref.get().is_some_and(|msg_ref| msg_ref.id == msg.get_value().id).to_string()
                 data-highlight=move || context.target_message_id.get().is_some_and(|id| id == msg.get_value().id).to_string()
                 data-context=move || context_open.get().to_string()
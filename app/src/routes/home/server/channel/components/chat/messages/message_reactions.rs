@@ -1,7 +1,7 @@
+use api::generated::reaction::{AddReaction, RemoveReaction};
 use common::convex::{ChannelMessage, Member};
-use convex_client::leptos::{Mutation, UseMutation};
+use convex_client::leptos::UseMutation;
 use leptos::prelude::*;
-use serde::Serialize;
 
 use capi_ui::button::*;
 
@@ -35,14 +35,14 @@ pub fn MessageReactions(
                                     if let Some(member) = member.get() {
                                         if reaction.has_reacted {
                                             remove_reaction.dispatch(RemoveReaction {
-                                                message: msg.get_value().id,
-                                                member: member.id,
+                                                message_id: msg.get_value().id,
+                                                member_id: member.id,
                                                 emoji: emoji.get_value(),
                                             });
                                         } else {
                                             add_reaction.dispatch(AddReaction {
-                                                message: msg.get_value().id,
-                                                member: member.id,
+                                                message_id: msg.get_value().id,
+                                                member_id: member.id,
                                                 emoji: emoji.get_value()
                                             });
                                         }
@@ -65,37 +65,3 @@ pub fn MessageReactions(
         </Show>
     }
 }
-
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct AddReaction {
-    #[serde(rename = "messageId")]
-    pub message: String,
-    #[serde(rename = "memberId")]
-    pub member: String,
-    pub emoji: String,
-}
-
-impl Mutation for AddReaction {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "reaction:addReaction".into()
-    }
-}
-
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct RemoveReaction {
-    #[serde(rename = "messageId")]
-    message: String,
-    #[serde(rename = "memberId")]
-    member: String,
-    emoji: String,
-}
-
-impl Mutation for RemoveReaction {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "reaction:removeReaction".into()
-    }
-}
@@ -9,8 +9,10 @@ mod message_reference;
 mod pin;
 mod utils;
 
+use api::generated::unread_messages::UpdateMemberChannelLastRead;
 use common::convex::{Channel, ChannelMessage, Member};
-use convex_client::leptos::{Mutation, UseMutation};
+use convex_client::leptos::UseMutation;
+use convex_client::ConvexClientError;
 use leptos::html::Div;
 use leptos::prelude::*;
 use leptos_dom::warn;
@@ -20,29 +22,14 @@ use leptos_use::{
 
 use capi_ui::divider::Separator;
 use capi_ui::label::Label;
-use serde::Serialize;
 
 use self::group::MessageGroup;
 
 use super::MessageDisplayItem;
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
-pub struct UpdateMemberChannelLastRead {
-    #[serde(rename = "channelId")]
-    pub channel: String,
-    #[serde(rename = "messageId")]
-    pub message: String,
-    #[serde(rename = "memberId")]
-    pub member: String,
-}
-
-impl Mutation for UpdateMemberChannelLastRead {
-    fn name(&self) -> String {
-        "unreadMessages:updateMemberChannelLastRead".to_string()
-    }
-
-    type Output = ();
-}
+/// Distance from the top of the chat, in pixels, under which older messages
+/// start loading.
+const LOAD_MORE_THRESHOLD: i32 = 200;
 
 #[component]
 fn DateSeparator(date_string: String) -> impl IntoView {
@@ -61,7 +48,8 @@ pub fn Messages(
     sender_ref: NodeRef<Div>,
     channel: Signal<Option<Channel>>,
     member: Signal<Option<Member>>,
-    last_read_updated: ReadSignal<Option<Result<Option<ChannelMessage>, String>>>,
+    last_read_updated: Signal<Option<Result<Option<ChannelMessage>, ConvexClientError>>>,
+    load_more: Callback<()>,
 ) -> impl IntoView {
     let style = RwSignal::new(String::default());
     #[cfg(feature = "hydrate")]
@@ -97,15 +85,22 @@ pub fn Messages(
             let member = member.get_untracked()?;
             let channel = channel.get_untracked()?;
             update_last_read.dispatch(UpdateMemberChannelLastRead {
-                channel: channel.id,
-                message: last_read.id,
-                member: member.id,
+                channel_id: channel.id,
+                message_id: last_read.id,
+                member_id: member.id,
             });
         }
         None
     });
     view! {
-        <div style=move || style.get()  class="flex min-h-0 flex-1 flex-col overflow-auto pt-4 scrollbar-thin scrollbar-track-background pb-[var(--sender-height)]">
+        <div
+            style=move || style.get()
+            on:scroll=move |ev| {
+                if event_target::<web_sys::Element>(&ev).scroll_top() < LOAD_MORE_THRESHOLD {
+                    load_more.run(());
+                }
+            }
+            class="flex min-h-0 flex-1 flex-col overflow-auto pt-4 scrollbar-thin scrollbar-track-background pb-[var(--sender-height)]">
             {
                 move || {
                     messages.get().into_iter().map(|item| {
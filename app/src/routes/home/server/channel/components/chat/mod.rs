@@ -5,60 +5,36 @@ mod unread;
 
 use std::collections::{HashMap, HashSet};
 
+use api::generated::member::GetMembersByIds;
+use api::generated::messages::GetPaginatedMessagesInChannel;
+use api::generated::reaction::GetMemberEmojis;
+use api::generated::unread_messages::{GetLastReadMessageId, GetUnreadMessagesCountInChannel};
 use chrono::{DateTime, Local, NaiveDate};
 use common::files::ClientFile;
-use convex_client::leptos::{Query, UseQuery};
+use convex_client::leptos::{use_queries, PaginationStatus, UsePaginatedQuery, UseQuery};
 use leptos::prelude::*;
 
-use common::convex::{Channel, ChannelMessage, Member};
+use common::convex::tables::{self, Members};
+use common::convex::{Channel, ChannelMessage, Id, Member};
 use leptos::context::Provider;
-use serde::Serialize;
 
 use self::messages::Messages;
 use self::sender::Sender;
 use self::unread::UnreadMessagesButton;
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
-pub struct GetMemberEmojis {
-    #[serde(rename = "memberId")]
-    member: String,
-}
-
-impl Query<Vec<String>> for GetMemberEmojis {
-    fn name(&self) -> String {
-        "reaction:getMemberEmojis".into()
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct ChatContext {
     pub member: Signal<Option<Member>>,
     pub msg_reference: RwSignal<Option<ChannelMessage>>,
     pub attachments: RwSignal<Vec<ClientFile>>,
-    pub cached_members: Memo<Option<HashMap<String, Member>>>,
-    pub target_message_id: RwSignal<Option<String>>,
+    pub cached_members: Memo<Option<HashMap<Id<Members>, Member>>>,
+    pub target_message_id: RwSignal<Option<Id<tables::Messages>>>,
     pub reactions: Signal<Option<Vec<String>>>,
 }
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct GetMessagesInChannel {
-    #[serde(rename(serialize = "channelId"))]
-    channel: String,
-    #[serde(rename(serialize = "memberId"))]
-    member: String,
-}
-
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct GetMembersById {
-    #[serde(rename(serialize = "memberIds"))]
-    members: Vec<String>,
-}
-
-impl Query<Vec<Member>> for GetMembersById {
-    fn name(&self) -> String {
-        "member:getMembersByIds".to_string()
-    }
-}
+/// Number of messages loaded when opening a channel and on each scroll to the
+/// top of the chat.
+const MESSAGES_PAGE_SIZE: usize = 50;
 
 #[derive(Debug, PartialEq, Clone)]
 enum MessageDisplayItem {
@@ -67,15 +43,9 @@ enum MessageDisplayItem {
     UnreadSeparator,
 }
 
-impl Query<Vec<ChannelMessage>> for GetMessagesInChannel {
-    fn name(&self) -> String {
-        "messages:getMessagesInChannel".to_string()
-    }
-}
-
 #[derive(Debug, PartialEq, Clone)]
 struct GroupedMessage {
-    author_id: String,
+    author_id: Id<Members>,
     creation_time: f64,
     messages: Vec<ChannelMessage>,
 }
@@ -85,61 +55,47 @@ pub fn get_naive_date_from_convex_timestamp(timestamp_f64: f64) -> Option<NaiveD
     Some(dt.with_timezone(&Local).date_naive())
 }
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct GetLastReadMessageId {
-    #[serde(rename(serialize = "memberId"))]
-    pub member_id: String,
-    #[serde(rename(serialize = "channelId"))]
-    pub channel_id: String,
-}
-
-impl Query<Option<ChannelMessage>> for GetLastReadMessageId {
-    fn name(&self) -> String {
-        "unreadMessages:getLastReadMessageId".to_string()
-    }
-}
-
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct GetUnreadMessagesCountInChannel {
-    #[serde(rename(serialize = "memberId"))]
-    pub member_id: String,
-    #[serde(rename(serialize = "channelId"))]
-    pub channel_id: String,
-}
-
-impl Query<f64> for GetUnreadMessagesCountInChannel {
-    fn name(&self) -> String {
-        "unreadMessages:getUnreadMessagesCountInChannel".to_string()
-    }
-}
-
 #[component]
 pub fn Chat(channel: Signal<Option<Channel>>, member: Signal<Option<Member>>) -> impl IntoView {
-    let messages = UseQuery::new(move || {
-        let member = member.get()?;
-        let channel = channel.get()?;
-        Some(GetMessagesInChannel {
-            channel: channel.id,
-            member: member.id,
-        })
-    });
-    let last_read_message_id = UseQuery::new(move || {
-        let member = member.get()?;
-        let channel = channel.get()?;
-        Some(GetLastReadMessageId {
-            member_id: member.id,
-            channel_id: channel.id,
+    let paginated_messages = UsePaginatedQuery::<ChannelMessage>::new(
+        move || {
+            let member = member.get()?;
+            let channel = channel.get()?;
+            Some(GetPaginatedMessagesInChannel {
+                channel_id: channel.id,
+                member_id: member.id,
+            })
+        },
+        MESSAGES_PAGE_SIZE,
+    );
+    // Pages are loaded newest first, the chat displays them oldest first.
+    let messages = Memo::new(move |_| {
+        paginated_messages.results().get().map(|res| {
+            res.map(|mut messages| {
+                messages.reverse();
+                messages
+            })
         })
     });
-
-    let unread_count = UseQuery::new(move || {
+    // Read together, so that the unread separator and the unread count
+    // always agree.
+    let unread_state = use_queries::<(Option<ChannelMessage>, f64), _>(move || {
         let member = member.get()?;
         let channel = channel.get()?;
-        Some(GetUnreadMessagesCountInChannel {
-            member_id: member.id,
-            channel_id: channel.id,
-        })
+        Some((
+            GetLastReadMessageId {
+                member_id: member.id.clone(),
+                channel_id: channel.id.clone(),
+            },
+            GetUnreadMessagesCountInChannel {
+                member_id: member.id,
+                channel_id: channel.id,
+            },
+        ))
     });
+    let last_read_message_id =
+        Signal::derive(move || unread_state.get().map(|(last_read, _)| last_read));
+    let unread_count = Signal::derive(move || unread_state.get().map(|(_, count)| count));
 
     let display_items_memo = Memo::new(move |_| {
         let msgs = messages.get().and_then(|res| res.ok()).unwrap_or_default();
@@ -229,13 +185,13 @@ pub fn Chat(channel: Signal<Option<Channel>>, member: Signal<Option<Member>>) ->
         unique_senders
     });
 
-    let members_data = UseQuery::new(move || {
-        Some(GetMembersById {
-            members: sender_ids_map.get().iter().cloned().collect(),
+    let members_data = UseQuery::new::<Vec<Member>, _>(move || {
+        Some(GetMembersByIds {
+            member_ids: sender_ids_map.get().iter().cloned().collect(),
         })
     });
 
-    let cached_members: Memo<Option<HashMap<String, Member>>> = Memo::new(move |_| {
+    let cached_members: Memo<Option<HashMap<Id<Members>, Member>>> = Memo::new(move |_| {
         members_data.get().and_then(|res| res.ok()).map(|members| {
             members
                 .into_iter()
@@ -264,10 +220,10 @@ pub fn Chat(channel: Signal<Option<Channel>>, member: Signal<Option<Member>>) ->
             None
         }
     });
-    let member_reactions = UseQuery::new(move || {
-        member
-            .get()
-            .map(|member| GetMemberEmojis { member: member.id })
+    let member_reactions = UseQuery::new::<Vec<String>, _>(move || {
+        member.get().map(|member| GetMemberEmojis {
+            member_id: member.id,
+        })
     });
 
     let reactions = Signal::derive(move || member_reactions.get().and_then(|res| res.ok()));
@@ -282,10 +238,16 @@ pub fn Chat(channel: Signal<Option<Channel>>, member: Signal<Option<Member>>) ->
             .flatten()
     });
 
-    let scroll_to_message_id = Callback::new(move |message_id: String| {
+    let scroll_to_message_id = Callback::new(move |message_id: Id<tables::Messages>| {
         target_message_id.set(Some(message_id));
     });
 
+    let load_more_messages = Callback::new(move |_| {
+        if paginated_messages.status().get_untracked() == PaginationStatus::CanLoadMore {
+            paginated_messages.load_more(MESSAGES_PAGE_SIZE);
+        }
+    });
+
     view! {
         <Provider value=ChatContext {
             member,
@@ -301,7 +263,7 @@ pub fn Chat(channel: Signal<Option<Channel>>, member: Signal<Option<Member>>) ->
                     last_read_message=last_read_message_signal
                     scroll_to_message=scroll_to_message_id
                 />
-                <Messages messages=display_items_memo sender_ref=sender_ref member=member channel=channel last_read_updated=last_read_message_id/>
+                <Messages messages=display_items_memo sender_ref=sender_ref member=member channel=channel last_read_updated=last_read_message_id load_more=load_more_messages/>
                 <Sender channel=channel member=member sender_ref=sender_ref/>
             </div>
         </Provider>
@@ -13,6 +13,7 @@ use icons::{IconPaperClip, IconSend, IconSmile};
 pub fn MessageActionButtons(
     on_send: Callback<()>,
     attachments: RwSignal<Vec<ClientFile>>,
+    #[prop(into)] disabled: Signal<bool>,
 ) -> impl IntoView {
     let file_input_ref: NodeRef<Input> = NodeRef::new();
 
@@ -58,6 +59,7 @@ pub fn MessageActionButtons(
             <Button size=ButtonSizes::Icon
                 variant=ButtonVariants::Secondary
                 class="size-7 text-muted-foreground hover:text-foreground"
+                disabled=disabled
                 on:click=move |_| on_send.run(())
             >
                 <IconSend/>
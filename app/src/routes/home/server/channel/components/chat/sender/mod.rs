@@ -4,9 +4,15 @@ mod input;
 mod msg_ref;
 
 use api::files::GenerateUploadUrl;
+use api::generated::messages::{
+    AddAttachmentToMessage, CreateMessage, GetPaginatedMessagesInChannel,
+};
 use chrono::Utc;
-use common::convex::{Channel, Member};
-use convex_client::leptos::{Mutation, UseMutation};
+use common::convex::tables::{Members, Messages};
+use common::convex::{Channel, ChannelMessage, Id, Member};
+use convex_client::leptos::{
+    use_connection_state, Mutation, OptimisticLocalStore, OptimisticUpdate, UseMutation,
+};
 use gloo_file::File;
 use leptos::html::Div;
 use leptos::prelude::*;
@@ -21,40 +27,74 @@ use self::attachments::AttachmentPreviewList;
 use self::input::MessageInputArea;
 use self::msg_ref::MsgRefDisplay;
 
+/// Sends a message, and shows it in the chat until the server has it.
 #[derive(Debug, Serialize, Clone)]
 pub struct SendMessage {
-    #[serde(rename = "channelId")]
-    channel: String,
-    content: String,
-    #[serde(rename = "senderId")]
-    sender: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "referenceId")]
-    reference: Option<String>,
+    #[serde(flatten)]
+    message: CreateMessage,
+    /// Only used for the optimistic message; the server sends as the member
+    /// the identity token belongs to.
+    #[serde(skip)]
+    sender: Id<Members>,
 }
 
 impl Mutation for SendMessage {
-    type Output = String;
+    type Output = <CreateMessage as Mutation>::Output;
 
     fn name(&self) -> String {
-        "messages:createMessage".into()
+        self.message.name()
     }
-}
 
-#[derive(Debug, Serialize, Clone)]
-pub struct AddAttachment {
-    #[serde(rename = "messageId")]
-    message: String,
-    #[serde(rename = "storageId")]
-    storage: String,
-    name: String,
-}
-
-impl Mutation for AddAttachment {
-    type Output = String;
+    /// Lets the server dedupe a message replayed after a reload.
+    fn idempotency_key(&self) -> Option<String> {
+        self.message.client_message_id.clone()
+    }
 
-    fn name(&self) -> String {
-        "messages:addAttachmentToMessage".into()
+    fn optimistic_update(&self) -> Option<OptimisticUpdate> {
+        let message = self.message.clone();
+        let sender = self.sender.clone();
+        let creation_time = Utc::now().timestamp_millis() as f64;
+        Some(Box::new(move |store: &mut OptimisticLocalStore| {
+            let query = GetPaginatedMessagesInChannel {
+                channel_id: message.channel_id.clone(),
+                member_id: sender.clone(),
+            };
+            // Messages are paginated newest first, so the new message goes at
+            // the start of the first page.
+            store.update_first_page(&query, |messages: &mut Vec<ChannelMessage>| {
+                let referenced_message = message
+                    .reference_id
+                    .as_ref()
+                    .and_then(|reference| messages.iter().find(|msg| &msg.id == reference))
+                    .cloned()
+                    .map(Box::new);
+                messages.insert(
+                    0,
+                    ChannelMessage {
+                        id: Id::local(
+                            message
+                                .client_message_id
+                                .as_deref()
+                                .and_then(|id| uuid::Uuid::parse_str(id).ok())
+                                .unwrap_or_default()
+                                .into_bytes(),
+                        ),
+                        creation_time,
+                        channel: message.channel_id.clone(),
+                        sender: sender.clone(),
+                        referenced_message,
+                        content: message.content.clone(),
+                        pinned: false,
+                        mention_everyone: false,
+                        mention_roles: vec![],
+                        reactions: vec![],
+                        mentions: vec![],
+                        role_mentions: vec![],
+                        attachments: vec![],
+                    },
+                );
+            });
+        }))
     }
 }
 
@@ -66,23 +106,23 @@ pub fn Sender(
 ) -> impl IntoView {
     let send = UseMutation::new::<SendMessage>();
     let auth = use_auth().auth;
-    let add_attachment = UseMutation::with_local_fn::<(Vec<File>, String), _, _, _>(
+    let add_attachment = UseMutation::with_local_fn::<(Vec<File>, Id<Messages>), _, _, _>(
         move |((files, message), client)| {
             let auth = auth.get();
             let mut client_mut = client.to_owned();
             let files = files.to_owned();
             let message = message.to_owned();
             async move {
-                if let Some(Ok(Some(auth_data))) = auth {
+                if let Some(Ok(Some(_))) = auth {
                     for file in files {
-                        let upload_url = GenerateUploadUrl { auth: auth_data.id };
+                        let upload_url = GenerateUploadUrl {};
                         let url = upload_url.run(&mut client_mut).await;
-                        if let Ok(Some(url)) = url {
+                        if let Ok(url) = url {
                             if let Ok(UploadResult { storage_id }) = upload_file(&file, url).await {
-                                let add_attachment = AddAttachment {
+                                let add_attachment = AddAttachmentToMessage {
                                     name: file.name(),
-                                    message: message.clone(),
-                                    storage: storage_id,
+                                    message_id: message.clone(),
+                                    storage_id,
                                 };
                                 let _ = add_attachment.run(&mut client_mut).await;
                             }
@@ -105,17 +145,32 @@ pub fn Sender(
         msg_ref.set(None);
     });
 
+    let connection_state = use_connection_state();
+    let reconnecting = Signal::derive(move || {
+        let state = connection_state.get();
+        state.has_ever_connected && !state.is_connected()
+    });
+
     let on_send_message = Callback::new(move |_| {
+        if reconnecting.get_untracked() {
+            return;
+        }
         if let Some(channel_data) = channel.get() {
             if let Some(member_data) = member.get() {
                 let current_message_content = message.get();
                 if !current_message_content.is_empty() || !attachments.get().is_empty() {
                     let msg_ref_id = msg_ref.get().map(|m| m.id);
                     send.dispatch(SendMessage {
-                        channel: channel_data.id,
-                        content: current_message_content,
+                        message: CreateMessage {
+                            channel_id: channel_data.id,
+                            content: current_message_content,
+                            reference_id: msg_ref_id,
+                            client_message_id: Some(uuid::Uuid::new_v4().to_string()),
+                            mention_everyone: None,
+                            mention_roles: None,
+                            pinned: None,
+                        },
                         sender: member_data.id,
-                        reference: msg_ref_id,
                     });
                 }
             }
@@ -145,7 +200,7 @@ pub fn Sender(
                         })
                         .collect();
 
-                    add_attachment.dispatch_local((gloo_files, message_id.into()));
+                    add_attachment.dispatch_local((gloo_files, message_id.clone()));
                     attachments.set(vec![]);
                 }
             }
@@ -156,6 +211,9 @@ pub fn Sender(
     view! {
         <div class="w-full absolute bottom-0 bg-transparent flex flex-col z-20 isolate" node_ref=sender_ref>
             <div class="w-full px-5">
+                <Show when=move || reconnecting.get()>
+                    <div class="px-2 pb-1 text-xs text-muted-foreground">"Reconnecting…"</div>
+                </Show>
                 <div class="p-1 border border-input rounded-lg backdrop-blur-xs bg-muted/30">
                     <div class="flex flex-col items-center justify-center shadow-xs bg-background text-base rounded-md gap-2 p-2">
                         <MsgRefDisplay msg_ref=msg_ref on_clear_ref=on_clear_msg_ref/>
@@ -169,6 +227,7 @@ pub fn Sender(
                             <MessageActionButtons
                                 on_send=on_send_message
                                 attachments=attachments
+                                disabled=reconnecting
                             />
                         </div>
                     </div>
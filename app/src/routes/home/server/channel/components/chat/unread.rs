@@ -1,12 +1,13 @@
 use chrono::{DateTime, Datelike, Local};
-use common::convex::ChannelMessage;
+use common::convex::tables::Messages;
+use common::convex::{ChannelMessage, Id};
 use leptos::prelude::*;
 
 #[component]
 pub fn UnreadMessagesButton(
     unread_count: Signal<f64>,
     last_read_message: Signal<Option<ChannelMessage>>,
-    scroll_to_message: Callback<String>,
+    scroll_to_message: Callback<Id<Messages>>,
 ) -> impl IntoView {
     view! {
         <Show
@@ -1,42 +1,28 @@
+use api::generated::messages::GetPinnedMessages;
 use capi_ui::avatar::*;
 use capi_ui::button::*;
 use capi_ui::dropwdown::*;
 use chrono::{DateTime, Duration, Local};
 use common::convex::{ChannelMessage, Member};
-use convex_client::leptos::{Query, UseQuery};
+use convex_client::leptos::UseQuery;
 use icons::IconPin;
 use leptos::prelude::*;
 use markdown::Markdown;
-use serde::Serialize;
 
 use crate::routes::server::channel::components::sidebar::card::MemberCard;
 use crate::routes::server::channel::Channel;
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct GetPinnedMessages {
-    #[serde(rename = "channelId")]
-    channel: String,
-    #[serde(rename = "memberId")]
-    member: String,
-}
-
-impl Query<Vec<ChannelMessage>> for GetPinnedMessages {
-    fn name(&self) -> String {
-        "messages:getPinnedMessages".into()
-    }
-}
-
 #[component]
 pub fn PinnedMessages(
     channel: Signal<Option<Channel>>,
     member: Signal<Option<Member>>,
 ) -> impl IntoView {
-    let messages = UseQuery::new(move || {
+    let messages = UseQuery::new::<Vec<ChannelMessage>, _>(move || {
         let member = member.get()?;
         let channel = channel.get()?;
         Some(GetPinnedMessages {
-            channel: channel.id,
-            member: member.id,
+            channel_id: channel.id,
+            member_id: member.id,
         })
     });
     view! {
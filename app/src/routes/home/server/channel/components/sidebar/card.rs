@@ -1,3 +1,4 @@
+use api::generated::friends::SendFriendRequest;
 use capi_ui::avatar::*;
 use capi_ui::badge::*;
 use capi_ui::button::*;
@@ -14,27 +15,10 @@ use convex_client::leptos::Mutation;
 use convex_client::leptos::UseMutation;
 use icons::IconUserPlus;
 use leptos::prelude::*;
-use serde::Deserialize;
-use serde::Serialize;
 use uuid::Uuid;
 
 use crate::components::auth::use_auth;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct SendFriendRequest {
-    auth: i64,
-    #[serde(rename = "receiverId")]
-    receiver: String,
-}
-
-impl Mutation for SendFriendRequest {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "friends:sendFriendRequest".into()
-    }
-}
-
 #[component]
 pub fn MemberCard(
     member: Member,
@@ -91,8 +75,8 @@ pub fn MemberCard(
                                 variant=ButtonVariants::Secondary
                                 size=ButtonSizes::IconXs
                                 on:click=move |_| {
-                                    if let Some(auth) = auth.get().and_then(|auth| auth.ok()).flatten() {
-                                        send_friend_request.dispatch(SendFriendRequest { auth: auth.id, receiver: member.get_value().user });
+                                    if auth.get().and_then(|auth| auth.ok()).flatten().is_some() {
+                                        send_friend_request.dispatch(SendFriendRequest { receiver_id: member.get_value().user });
                                     }
                                 }
                             >
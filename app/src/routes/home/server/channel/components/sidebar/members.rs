@@ -1,6 +1,7 @@
 use api::presence::GetUserStatus;
 use common::convex::Member;
 use convex_client::leptos::UseQuery;
+use convex_client::ConvexClientError;
 use leptos::prelude::*;
 
 use super::card::MemberCard;
@@ -9,7 +10,9 @@ use capi_ui::avatar::*;
 use capi_ui::dropwdown::*;
 
 #[component]
-pub fn MembersItems(members: ReadSignal<Option<Result<Vec<Member>, String>>>) -> impl IntoView {
+pub fn MembersItems(
+    members: Signal<Option<Result<Vec<Member>, ConvexClientError>>>,
+) -> impl IntoView {
     view! {
         <SidebarMenu>
             <For
@@ -20,7 +23,7 @@ pub fn MembersItems(members: ReadSignal<Option<Result<Vec<Member>, String>>>) ->
                     let member = StoredValue::new(member);
                     let status = UseQuery::new(move || {
                         Some(GetUserStatus {
-                            user: member.get_value().user
+                            user_id: member.get_value().user
                         })
                     });
                     view!{
@@ -2,10 +2,11 @@ pub mod card;
 mod members;
 mod roles;
 
-use common::convex::Member;
-use convex_client::leptos::{Query, UseQuery};
+use api::generated::member::{GetOfflineMembers, GetOnlineMembersByRole};
+use common::convex::tables::Servers;
+use common::convex::{Id, Member};
+use convex_client::leptos::UseQuery;
 use leptos::prelude::*;
-use serde::Serialize;
 use tailwind_fuse::tw_merge;
 
 use crate::components::ui::sidebar::*;
@@ -17,41 +18,19 @@ use icons::IconChevronDown;
 use self::members::MembersItems;
 use self::roles::RolesItems;
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct GetOnlineMembersByRole {
-    server: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    role: Option<String>,
-}
-
-impl Query<Vec<Member>> for GetOnlineMembersByRole {
-    fn name(&self) -> String {
-        "member:getOnlineMembersByRole".to_string()
-    }
-}
-
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct GetOfflineMembers {
-    server: String,
-}
-
-impl Query<Vec<Member>> for GetOfflineMembers {
-    fn name(&self) -> String {
-        "member:getOfflineMembers".to_string()
-    }
-}
-
 #[component]
 pub fn MembersSideBar(
-    server: Memo<Option<String>>,
+    server: Memo<Option<Id<Servers>>>,
     member: Signal<Option<Member>>,
 ) -> impl IntoView {
-    let online = UseQuery::new(move || {
+    let online = UseQuery::new::<Vec<Member>, _>(move || {
         server
             .get()
             .map(|server| GetOnlineMembersByRole { server, role: None })
     });
-    let offline = UseQuery::new(move || server.get().map(|server| GetOfflineMembers { server }));
+    let offline = UseQuery::new::<Vec<Member>, _>(move || {
+        server.get().map(|server| GetOfflineMembers { server })
+    });
     let is_offline_open = RwSignal::new(true);
     let is_online_open = RwSignal::new(true);
     view! {
@@ -1,36 +1,27 @@
-use common::convex::Role;
-use convex_client::leptos::{Query, UseQuery};
+use api::generated::member::GetOnlineMembersByRole;
+use api::generated::roles::ServerRoles;
+use common::convex::tables::Servers;
+use common::convex::{Id, Member, Role};
+use convex_client::leptos::UseQuery;
 use leptos::prelude::*;
-use serde::Serialize;
 use tailwind_fuse::tw_merge;
 
 use icons::IconChevronDown;
 use capi_ui::collapsible::*;
 use crate::components::ui::sidebar::*;
 use crate::routes::server::channel::components::sidebar::members::MembersItems;
-use crate::routes::server::channel::components::sidebar::GetOnlineMembersByRole;
-
-#[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct GetServerRoles {
-    server: String,
-}
-
-impl Query<Vec<Role>> for GetServerRoles {
-    fn name(&self) -> String {
-        "roles:serverRoles".to_string()
-    }
-}
 
 #[component]
-pub fn RolesItems(server: Memo<Option<String>>) -> impl IntoView {
-    let roles = UseQuery::new(move || server.get().map(|server| GetServerRoles { server }));
+pub fn RolesItems(server: Memo<Option<Id<Servers>>>) -> impl IntoView {
+    let roles =
+        UseQuery::new::<Vec<Role>, _>(move || server.get().map(|server| ServerRoles { server }));
     view! {
         <Show when=move || roles.get().is_some_and(|res| res.is_ok())>
             <For
                 each=move || roles.get().and_then(|res| res.ok()).unwrap()
                 key=|role| role.id.clone()
                 children=move |role| {
-                    let members = UseQuery::new(move || {
+                    let members = UseQuery::new::<Vec<Member>, _>(move || {
                         server
                             .get()
                             .map(|server| GetOnlineMembersByRole { server, role: Some(role.id.clone()) })
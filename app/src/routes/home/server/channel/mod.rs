@@ -1,10 +1,14 @@
 mod components;
 
-use common::convex::{Channel, Member, Role};
-use convex_client::leptos::{Mutation, Query, UseMutation, UseQuery};
+use api::generated::channel::Get as GetChannel;
+use api::generated::member::SetLastVisitedChannel;
+use api::generated::user::GetMemberForServerByUser;
+use common::convex::tables::{Channels, Servers};
+use common::convex::{Channel, Id, Member, Role};
+use convex_client::leptos::{UseMutation, UseQuery};
 use leptos::prelude::*;
 use leptos_router::hooks::use_location;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 
 use crate::components::auth::use_auth;
 use crate::components::roles::RolesProvider;
@@ -14,55 +18,12 @@ use self::components::chat::Chat;
 use self::components::header::Header;
 use self::components::sidebar::MembersSideBar;
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
-pub struct GetMemberForServerByUser {
-    #[serde(rename = "serverId")]
-    server: String,
-    auth: i64,
-}
-
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct MemberWithRole {
     member: Member,
     roles: Vec<Role>,
 }
 
-impl Query<Option<MemberWithRole>> for GetMemberForServerByUser {
-    fn name(&self) -> String {
-        "user:getMemberForServerByUser".to_string()
-    }
-}
-
-#[derive(Debug, Serialize, Clone, PartialEq)]
-pub struct GetChannel {
-    #[serde(rename = "channelId")]
-    channel: String,
-    #[serde(rename = "serverId")]
-    server: String,
-    auth: i64,
-}
-
-impl Query<Option<Channel>> for GetChannel {
-    fn name(&self) -> String {
-        "channel:get".to_string()
-    }
-}
-
-#[derive(Debug, Serialize, PartialEq, Clone)]
-pub struct SetLastVisitedChannel {
-    auth: i64,
-    member: String,
-    channel: String,
-}
-
-impl Mutation for SetLastVisitedChannel {
-    type Output = ();
-
-    fn name(&self) -> String {
-        "member:setLastVisitedChannel".into()
-    }
-}
-
 #[component]
 pub fn Channel() -> impl IntoView {
     let auth = use_auth().auth;
@@ -73,23 +34,22 @@ pub fn Channel() -> impl IntoView {
         path.get()
             .split('/')
             .nth(2)
-            .map(|server| server.to_string())
+            .and_then(|server| server.parse::<Id<Servers>>().ok())
     });
 
     let channel = Memo::new(move |_| {
         path.get()
             .split('/')
             .nth(3)
-            .map(|channel| channel.to_string())
+            .and_then(|channel| channel.parse::<Id<Channels>>().ok())
     });
 
-    let member_with_role = UseQuery::new(move || {
-        let auth = auth.get().and_then(|res| res.ok()).flatten()?;
+    let member_with_role = UseQuery::new::<Option<MemberWithRole>, _>(move || {
+        auth.get().and_then(|res| res.ok()).flatten()?;
 
-        server.get().map(|server_id| GetMemberForServerByUser {
-            server: server_id,
-            auth: auth.id,
-        })
+        server
+            .get()
+            .map(|server_id| GetMemberForServerByUser { server_id })
     });
 
     let member: Signal<Option<Member>> = Signal::derive(move || {
@@ -108,15 +68,14 @@ pub fn Channel() -> impl IntoView {
             .map(|data| data.roles)
     });
 
-    let channel_query_signal_result = UseQuery::new(move || {
+    let channel_query_signal_result = UseQuery::new::<Option<Channel>, _>(move || {
         let server = server.get()?;
         let channel = channel.get()?;
-        let auth = auth.get().and_then(|res| res.ok()).flatten()?;
+        auth.get().and_then(|res| res.ok()).flatten()?;
 
         Some(GetChannel {
-            server,
-            channel,
-            auth: auth.id,
+            server_id: server,
+            channel_id: channel,
         })
     });
 
@@ -132,11 +91,10 @@ pub fn Channel() -> impl IntoView {
         move || current_channel.get(),
         move |channel, _, _| {
             let auth = auth.get().and_then(|auth| auth.ok()).flatten();
-            if let (Some(auth), Some(channel), Some(member)) = (auth, channel, member.get()) {
+            if let (Some(_), Some(channel), Some(member)) = (auth, channel, member.get()) {
                 set_last_visited_channel.dispatch(SetLastVisitedChannel {
-                    auth: auth.id,
                     member: member.id,
-                    channel: channel.id.to_string(),
+                    channel: channel.id.clone(),
                 });
             }
         },
@@ -1,10 +1,10 @@
-use common::convex::Server;
-use convex_client::leptos::Mutation;
-use convex_client::leptos::Query;
+use api::generated::server::JoinServer;
+use common::convex::tables::Members;
+use common::convex::{Id, Server};
 use convex_client::leptos::UseMutation;
 use convex_client::leptos::UseQuery;
+use convex_client::ConvexClientError;
 use leptos::prelude::*;
-use serde::Serialize;
 
 use crate::components::auth::use_auth;
 use crate::routes::use_profile;
@@ -12,33 +12,6 @@ use capi_ui::avatar::*;
 use capi_ui::button::*;
 use capi_ui::card::*;
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
-pub struct GetPublicServers {
-    pub auth: i64,
-}
-
-impl Query<Vec<Server>> for GetPublicServers {
-    fn name(&self) -> String {
-        "server:getPublicServers".into()
-    }
-}
-
-#[derive(Debug, Serialize, Clone, PartialEq)]
-pub struct JoinServer {
-    #[serde(rename = "serverId")]
-    server: String,
-    #[serde(rename = "userId")]
-    user: String,
-}
-
-impl Mutation for JoinServer {
-    type Output = String;
-
-    fn name(&self) -> String {
-        "server:joinServer".into()
-    }
-}
-
 #[component]
 pub fn Discover(data: Signal<Option<Vec<Server>>>) -> impl IntoView {
     let join_server_action = UseMutation::new::<JoinServer>();
@@ -66,17 +39,16 @@ pub fn Discover(data: Signal<Option<Vec<Server>>>) -> impl IntoView {
 #[component]
 pub fn ServerItem(
     server: Server,
-    join_server_action: Action<JoinServer, Result<String, String>>,
+    join_server_action: Action<JoinServer, Result<Id<Members>, ConvexClientError>>,
     auth_id: Signal<Option<i64>>,
 ) -> impl IntoView {
     let server = StoredValue::new(server);
     let user = use_profile();
 
     let on_join_click = move |_| {
-        if let Some(user) = user.get() {
+        if user.get().is_some() {
             join_server_action.dispatch(JoinServer {
-                server: server.get_value().id,
-                user: user.id.clone(),
+                server_id: server.get_value().id,
             });
         }
     };
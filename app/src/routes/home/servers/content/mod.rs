@@ -1,6 +1,8 @@
 mod discover;
 mod servers;
 
+use api::generated::server::GetPublicServers;
+use common::convex::Server;
 use convex_client::leptos::UseQuery;
 use leptos::prelude::*;
 
@@ -8,18 +10,18 @@ use capi_ui::tabs::TabPanel;
 
 use crate::components::auth::use_auth;
 
-use self::discover::{Discover, GetPublicServers};
+use self::discover::Discover;
 use self::servers::Servers;
 
 #[component]
 pub fn Content() -> impl IntoView {
     let auth = use_auth().auth;
 
-    let data = UseQuery::new(move || {
+    let data = UseQuery::new::<Vec<Server>, _>(move || {
         auth.get()
             .and_then(|res| res.ok())
             .flatten()
-            .map(|auth| GetPublicServers { auth: auth.id })
+            .map(|_| GetPublicServers {})
     });
 
     let data = Signal::derive(move || data.get().and_then(|res| res.ok()));
@@ -13,11 +13,11 @@ use capi_ui::card::*;
 pub fn Servers() -> impl IntoView {
     let auth = use_auth().auth;
 
-    let data = UseQuery::new(move || {
+    let data = UseQuery::new::<Vec<ServerData>, _>(move || {
         auth.get()
             .and_then(|res| res.ok())
             .flatten()
-            .map(|auth| GetServers { auth: auth.id })
+            .map(|_| GetServers {})
     });
 
     let data = Signal::derive(move || data.get().and_then(|res| res.ok()));
@@ -1,10 +1,12 @@
 pub mod clients;
+pub mod tokens;
 
 pub use axum_session_auth::{Authentication, HasPermission};
 use axum_session_sqlx::SessionPgPool;
 use common::user::User;
-use leptos::prelude::ServerFnError;
+use leptos::prelude::{ServerFnError, use_context};
 pub use sqlx::PgPool;
+pub use tokens::IdentityTokens;
 pub type AuthSession = axum_session_auth::AuthSession<AuthUser, i64, SessionPgPool, PgPool>;
 
 pub async fn auth() -> Result<AuthSession, ServerFnError> {
@@ -12,6 +14,10 @@ pub async fn auth() -> Result<AuthSession, ServerFnError> {
     Ok(auth)
 }
 
+pub fn identity_tokens() -> Result<IdentityTokens, ServerFnError> {
+    use_context::<IdentityTokens>().ok_or_else(|| ServerFnError::new("Identity tokens missing."))
+}
+
 use async_trait::async_trait;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -0,0 +1,416 @@
+//! Identity tokens for Convex: short-lived JWTs, signed with RS256, naming the
+//! signed-in user as their `sub`. Convex checks them against the key set the
+//! server publishes at [`JWKS_PATH`].
+
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, anyhow, bail, ensure};
+use rsa::pkcs1v15;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::rand_core::OsRng;
+use rsa::sha2::{Digest, Sha256};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// The audience of the tokens, the `applicationID` of the provider in
+/// `convex/auth.config.ts`.
+pub const AUDIENCE: &str = "convex";
+
+/// Where the server publishes the key set, relative to the issuer.
+pub const JWKS_PATH: &str = "/.well-known/jwks.json";
+
+/// How long a token is valid for by default.
+const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(10 * 60);
+
+const KEY_BITS: usize = 2048;
+
+/// An RSA key to sign tokens with, named by its JWK thumbprint.
+#[derive(Clone)]
+pub struct SigningKey {
+    kid: String,
+    key: pkcs1v15::SigningKey<Sha256>,
+    public: RsaPublicKey,
+}
+
+impl SigningKey {
+    /// Generates a new 2048 bit key.
+    pub fn generate() -> anyhow::Result<Self> {
+        Ok(Self::new(RsaPrivateKey::new(&mut OsRng, KEY_BITS)?))
+    }
+
+    /// Reads a PKCS#8 PEM private key, as written by
+    /// `openssl genpkey -algorithm RSA`.
+    pub fn from_pem(pem: &str) -> anyhow::Result<Self> {
+        let key = RsaPrivateKey::from_pkcs8_pem(pem).context("Invalid RSA private key")?;
+        Ok(Self::new(key))
+    }
+
+    fn new(key: RsaPrivateKey) -> Self {
+        let public = key.to_public_key();
+        Self {
+            kid: thumbprint(&public),
+            key: pkcs1v15::SigningKey::new(key),
+            public,
+        }
+    }
+
+    /// The id of the key, the `kid` of the tokens it signs.
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+}
+
+/// The claims of an identity token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    pub iss: String,
+    /// The id of the user.
+    pub sub: String,
+    pub aud: String,
+    /// When the token was issued, in seconds since the epoch.
+    pub iat: u64,
+    /// When the token expires, in seconds since the epoch.
+    pub exp: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    typ: String,
+    kid: String,
+}
+
+/// Mints identity tokens and publishes the keys that check them.
+///
+/// Tokens are signed with the latest key. [`rotate`](Self::rotate) replaces
+/// it, but keeps publishing the old one until the tokens it signed expire.
+///
+/// Cloning is cheap and shares the keys.
+#[derive(Clone)]
+pub struct IdentityTokens {
+    issuer: String,
+    ttl: Duration,
+    keys: Arc<RwLock<Keys>>,
+}
+
+struct Keys {
+    current: SigningKey,
+    /// Keys rotated out, and when.
+    retired: Vec<(SigningKey, SystemTime)>,
+}
+
+impl IdentityTokens {
+    /// Mints tokens issued by `issuer`, the URL the server is reachable at,
+    /// signed with `key`.
+    pub fn new(issuer: &str, key: SigningKey) -> Self {
+        Self {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            ttl: DEFAULT_TOKEN_TTL,
+            keys: Arc::new(RwLock::new(Keys {
+                current: key,
+                retired: Vec::new(),
+            })),
+        }
+    }
+
+    /// Tokens expire `ttl` after they are minted. Defaults to 10 minutes.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// The URL of the key set, for the `jwks` of the provider in
+    /// `convex/auth.config.ts`.
+    pub fn jwks_url(&self) -> String {
+        format!("{}{JWKS_PATH}", self.issuer)
+    }
+
+    /// Mints a token for the user `user_id`.
+    pub fn mint(&self, user_id: i64) -> anyhow::Result<String> {
+        self.mint_at(user_id, SystemTime::now())
+    }
+
+    /// Checks that `token` was minted here and hasn't expired.
+    pub fn verify(&self, token: &str) -> anyhow::Result<Claims> {
+        self.verify_at(token, SystemTime::now())
+    }
+
+    /// Signs new tokens with `key` from now on.
+    pub fn rotate(&self, key: SigningKey) {
+        self.rotate_at(key, SystemTime::now());
+    }
+
+    /// The JSON Web Key Set of the keys whose tokens may still be valid.
+    pub fn jwks(&self) -> Value {
+        self.jwks_at(SystemTime::now())
+    }
+
+    fn mint_at(&self, user_id: i64, now: SystemTime) -> anyhow::Result<String> {
+        let keys = self.keys.read().expect("identity keys poisoned");
+        let iat = unix_secs(now)?;
+        let header = Header {
+            alg: "RS256".to_string(),
+            typ: "JWT".to_string(),
+            kid: keys.current.kid.clone(),
+        };
+        let claims = Claims {
+            iss: self.issuer.clone(),
+            sub: user_id.to_string(),
+            aud: AUDIENCE.to_string(),
+            iat,
+            exp: iat + self.ttl.as_secs(),
+        };
+        let message = format!(
+            "{}.{}",
+            base64url(&serde_json::to_vec(&header)?),
+            base64url(&serde_json::to_vec(&claims)?)
+        );
+        let signature = keys.current.key.sign(message.as_bytes());
+        Ok(format!("{message}.{}", base64url(&signature.to_bytes())))
+    }
+
+    fn verify_at(&self, token: &str, now: SystemTime) -> anyhow::Result<Claims> {
+        let (message, signature) = token
+            .rsplit_once('.')
+            .ok_or_else(|| anyhow!("Malformed token"))?;
+        let (header, claims) = message
+            .split_once('.')
+            .ok_or_else(|| anyhow!("Malformed token"))?;
+        let header: Header = serde_json::from_slice(&base64url_decode(header)?)?;
+        ensure!(header.alg == "RS256", "Unexpected algorithm {}", header.alg);
+
+        let keys = self.keys.read().expect("identity keys poisoned");
+        let key = self
+            .published(&keys, now)
+            .find(|key| key.kid == header.kid)
+            .ok_or_else(|| anyhow!("Unknown key {}", header.kid))?;
+        let signature = pkcs1v15::Signature::try_from(&base64url_decode(signature)?[..])?;
+        pkcs1v15::VerifyingKey::<Sha256>::new(key.public.clone())
+            .verify(message.as_bytes(), &signature)
+            .context("Invalid signature")?;
+
+        let claims: Claims = serde_json::from_slice(&base64url_decode(claims)?)?;
+        ensure!(
+            claims.iss == self.issuer,
+            "Unexpected issuer {}",
+            claims.iss
+        );
+        ensure!(claims.aud == AUDIENCE, "Unexpected audience {}", claims.aud);
+        if claims.exp <= unix_secs(now)? {
+            bail!("Token expired");
+        }
+        Ok(claims)
+    }
+
+    fn rotate_at(&self, key: SigningKey, now: SystemTime) {
+        let mut keys = self.keys.write().expect("identity keys poisoned");
+        let ttl = self.ttl;
+        keys.retired
+            .retain(|(_, retired_at)| !expired(*retired_at, ttl, now));
+        let previous = std::mem::replace(&mut keys.current, key);
+        keys.retired.push((previous, now));
+    }
+
+    fn jwks_at(&self, now: SystemTime) -> Value {
+        let keys = self.keys.read().expect("identity keys poisoned");
+        let keys: Vec<_> = self
+            .published(&keys, now)
+            .map(|key| {
+                json!({
+                    "kty": "RSA",
+                    "use": "sig",
+                    "alg": "RS256",
+                    "kid": key.kid,
+                    "n": base64url(&key.public.n().to_bytes_be()),
+                    "e": base64url(&key.public.e().to_bytes_be()),
+                })
+            })
+            .collect();
+        json!({ "keys": keys })
+    }
+
+    /// The current key, and the retired ones that signed tokens which may
+    /// not have expired yet.
+    fn published<'a>(
+        &self,
+        keys: &'a Keys,
+        now: SystemTime,
+    ) -> impl Iterator<Item = &'a SigningKey> {
+        let ttl = self.ttl;
+        std::iter::once(&keys.current).chain(
+            keys.retired
+                .iter()
+                .filter(move |(_, retired_at)| !expired(*retired_at, ttl, now))
+                .map(|(key, _)| key),
+        )
+    }
+}
+
+/// Whether every token signed by a key retired at `retired_at` has expired.
+fn expired(retired_at: SystemTime, ttl: Duration, now: SystemTime) -> bool {
+    retired_at + ttl <= now
+}
+
+/// The RFC 7638 thumbprint of `key`.
+fn thumbprint(key: &RsaPublicKey) -> String {
+    let jwk = format!(
+        r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#,
+        base64url(&key.e().to_bytes_be()),
+        base64url(&key.n().to_bytes_be())
+    );
+    base64url(&Sha256::digest(jwk.as_bytes()))
+}
+
+fn unix_secs(time: SystemTime) -> anyhow::Result<u64> {
+    Ok(time.duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+fn base64url(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn base64url_decode(encoded: &str) -> anyhow::Result<Vec<u8>> {
+    base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).context("Malformed token")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use super::*;
+
+    static KEY: LazyLock<SigningKey> = LazyLock::new(|| SigningKey::generate().unwrap());
+    static NEXT_KEY: LazyLock<SigningKey> = LazyLock::new(|| SigningKey::generate().unwrap());
+
+    const TTL: Duration = Duration::from_secs(600);
+
+    fn tokens() -> IdentityTokens {
+        IdentityTokens::new("https://capi.example/", KEY.clone()).with_ttl(TTL)
+    }
+
+    fn published_kids(tokens: &IdentityTokens, now: SystemTime) -> Vec<String> {
+        tokens.jwks_at(now)["keys"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|key| key["kid"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn minted_tokens_name_the_user() {
+        let tokens = tokens();
+        let now = SystemTime::now();
+        let claims = tokens
+            .verify_at(&tokens.mint_at(42, now).unwrap(), now)
+            .unwrap();
+        assert_eq!(claims.sub, "42");
+        assert_eq!(claims.iss, "https://capi.example");
+        assert_eq!(claims.aud, AUDIENCE);
+        assert_eq!(claims.exp - claims.iat, TTL.as_secs());
+        assert_eq!(
+            tokens.jwks_url(),
+            "https://capi.example/.well-known/jwks.json"
+        );
+    }
+
+    #[test]
+    fn tokens_expire_after_their_ttl() {
+        let tokens = tokens();
+        let minted = SystemTime::now();
+        let token = tokens.mint_at(42, minted).unwrap();
+        assert!(
+            tokens
+                .verify_at(&token, minted + TTL - Duration::from_secs(1))
+                .is_ok()
+        );
+        let err = tokens.verify_at(&token, minted + TTL).unwrap_err();
+        assert_eq!(err.to_string(), "Token expired");
+    }
+
+    #[test]
+    fn rotated_keys_are_published_until_their_tokens_expire() {
+        let tokens = tokens();
+        let start = SystemTime::now();
+        let old_token = tokens.mint_at(42, start).unwrap();
+        let rotated = start + Duration::from_secs(60);
+        tokens.rotate_at(NEXT_KEY.clone(), rotated);
+
+        let new_token = tokens.mint_at(42, rotated).unwrap();
+        assert_ne!(
+            old_token.split('.').next(),
+            new_token.split('.').next(),
+            "new tokens are signed with the new key"
+        );
+        assert!(tokens.verify_at(&old_token, rotated).is_ok());
+        assert!(tokens.verify_at(&new_token, rotated).is_ok());
+        assert_eq!(
+            published_kids(&tokens, rotated),
+            [NEXT_KEY.kid(), KEY.kid()]
+        );
+
+        let retired = rotated + TTL;
+        assert_eq!(published_kids(&tokens, retired), [NEXT_KEY.kid()]);
+        let err = tokens.verify_at(&old_token, retired).unwrap_err();
+        assert!(err.to_string().starts_with("Unknown key"), "{err}");
+    }
+
+    #[test]
+    fn rotating_forgets_expired_keys() {
+        let tokens = tokens();
+        let start = SystemTime::now();
+        tokens.rotate_at(NEXT_KEY.clone(), start);
+        tokens.rotate_at(KEY.clone(), start + TTL);
+        let keys = tokens.keys.read().unwrap();
+        assert_eq!(keys.current.kid(), KEY.kid());
+        assert_eq!(keys.retired.len(), 1);
+        assert_eq!(keys.retired[0].0.kid(), NEXT_KEY.kid());
+    }
+
+    #[test]
+    fn tokens_of_other_keys_are_rejected() {
+        let tokens = tokens();
+        let others = IdentityTokens::new("https://capi.example/", NEXT_KEY.clone()).with_ttl(TTL);
+        let now = SystemTime::now();
+        let err = tokens
+            .verify_at(&others.mint_at(42, now).unwrap(), now)
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Unknown key"), "{err}");
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected() {
+        let tokens = tokens();
+        let now = SystemTime::now();
+        let token = tokens.mint_at(42, now).unwrap();
+        let [header, claims, signature]: [&str; 3] =
+            token.split('.').collect::<Vec<_>>().try_into().unwrap();
+        let mut forged: Claims =
+            serde_json::from_slice(&base64url_decode(claims).unwrap()).unwrap();
+        forged.sub = "7".to_string();
+        let forged = format!(
+            "{header}.{}.{signature}",
+            base64url(&serde_json::to_vec(&forged).unwrap())
+        );
+        let err = tokens.verify_at(&forged, now).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid signature");
+    }
+
+    #[test]
+    fn keys_are_read_from_pem() {
+        use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+
+        let pem = KEY.key.as_ref().to_pkcs8_pem(LineEnding::LF).unwrap();
+        let key = SigningKey::from_pem(&pem).unwrap();
+        assert_eq!(key.kid(), KEY.kid());
+        assert!(SigningKey::from_pem("not a key").is_err());
+    }
+}
@@ -0,0 +1,337 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The alphabet Convex encodes IDs in: lowercase Crockford base32.
+const ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+/// An ID encodes the table number as a varint of 1 to 5 bytes, the 16 byte
+/// id of the document within the table and a 2 byte checksum.
+const MIN_DECODED_LEN: usize = 1 + 16 + 2;
+const MAX_DECODED_LEN: usize = 5 + 16 + 2;
+
+/// A Convex table, named by a marker type in [`tables`](super::tables).
+pub trait Table {
+    /// The name of the table in the schema.
+    const NAME: &'static str;
+}
+
+/// The id of a document in the table `T`, e.g. `Id<Members>` for the `_id`
+/// of a member or the `sender` of a message.
+///
+/// Serializes as the plain id string. Parsing and deserializing check that
+/// the string is encoded like a Convex ID; that it names a document of `T`
+/// is up to the server, which rejects arguments of the wrong table.
+pub struct Id<T> {
+    id: String,
+    table: PhantomData<fn() -> T>,
+}
+
+impl<T: Table> Id<T> {
+    /// Parse `id`, failing if it isn't encoded like a Convex ID.
+    pub fn parse(id: impl Into<String>) -> anyhow::Result<Self> {
+        let id = id.into();
+        validate(&id)
+            .map_err(|reason| anyhow::anyhow!("Invalid {} id {id:?}: {reason}", T::NAME))?;
+        Ok(Self {
+            id,
+            table: PhantomData,
+        })
+    }
+
+    /// An id no document has, for records made up on the client, like the
+    /// optimistic copy of a message being sent. Equal `seed`s give equal ids.
+    ///
+    /// It is encoded with table number 0, which no table has, so it parses
+    /// like any other id but never names a document.
+    pub fn local(seed: [u8; 16]) -> Self {
+        let mut bytes = [0; MIN_DECODED_LEN];
+        bytes[1..17].copy_from_slice(&seed);
+        let checksum = fletcher16(&bytes[..17]);
+        bytes[17..].copy_from_slice(&checksum.to_le_bytes());
+        Self {
+            id: encode(&bytes),
+            table: PhantomData,
+        }
+    }
+}
+
+impl<T> Id<T> {
+    /// The id as the server sends it.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Check that `id` decodes to a table number, a document id and a matching
+/// checksum.
+fn validate(id: &str) -> Result<(), &'static str> {
+    let bytes = decode(id)?;
+    let table_number_len = bytes
+        .iter()
+        .take(5)
+        .position(|byte| byte & 0x80 == 0)
+        .map(|last| last + 1)
+        .ok_or("bad table number")?;
+    // The fifth byte of a varint u32 holds its top 4 bits.
+    if table_number_len == 5 && bytes[4] > 0x0f {
+        return Err("bad table number");
+    }
+    if bytes.len() != table_number_len + 16 + 2 {
+        return Err("wrong length");
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 2);
+    if fletcher16(body).to_le_bytes() != checksum {
+        return Err("bad checksum");
+    }
+    Ok(())
+}
+
+/// Decode base32 of a whole number of bytes, as many as an ID encodes.
+fn decode(id: &str) -> Result<Vec<u8>, &'static str> {
+    if let Some(c) = id.bytes().find(|c| !ALPHABET.contains(c)) {
+        return Err(if c.is_ascii_uppercase() {
+            "IDs are lowercase"
+        } else {
+            "not base32"
+        });
+    }
+    let decoded_len = id.len() * 5 / 8;
+    // Base32 of whole bytes leaves fewer than 5 bits of padding.
+    if id.len() != (decoded_len * 8).div_ceil(5)
+        || !(MIN_DECODED_LEN..=MAX_DECODED_LEN).contains(&decoded_len)
+    {
+        return Err("wrong length");
+    }
+    let mut bytes = Vec::with_capacity(decoded_len);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for c in id.bytes() {
+        let value = ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .expect("checked above");
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// The Fletcher-16 checksum an ID ends with, of the bytes before it.
+fn fletcher16(bytes: &[u8]) -> u16 {
+    let (mut sum, mut sum_of_sums) = (0u16, 0u16);
+    for &byte in bytes {
+        sum = (sum + u16::from(byte)) % 255;
+        sum_of_sums = (sum_of_sums + sum) % 255;
+    }
+    (sum_of_sums << 8) | sum
+}
+
+fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            table: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T: Table> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Id<{}>({:?})", T::NAME, self.id)
+    }
+}
+
+impl<T> fmt::Display for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl<T> AsRef<str> for Id<T> {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl<T> From<Id<T>> for String {
+    fn from(id: Id<T>) -> Self {
+        id.id
+    }
+}
+
+impl<T: Table> FromStr for Id<T> {
+    type Err = anyhow::Error;
+
+    fn from_str(id: &str) -> anyhow::Result<Self> {
+        Self::parse(id)
+    }
+}
+
+impl<T: Table> TryFrom<String> for Id<T> {
+    type Error = anyhow::Error;
+
+    fn try_from(id: String) -> anyhow::Result<Self> {
+        Self::parse(id)
+    }
+}
+
+impl<T> Serialize for Id<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.id)
+    }
+}
+
+impl<'de, T: Table> Deserialize<'de> for Id<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let id = String::deserialize(deserializer)?;
+        Self::parse(id).map_err(Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tables::Messages;
+    use super::*;
+
+    /// The encoding of a document of table `table_number`, a varint of at
+    /// most 2 bytes here.
+    fn encoded(table_number: u16, document: [u8; 16]) -> Vec<u8> {
+        let mut bytes = if table_number < 0x80 {
+            vec![table_number as u8]
+        } else {
+            vec![(table_number as u8) | 0x80, (table_number >> 7) as u8]
+        };
+        bytes.extend(document);
+        let checksum = fletcher16(&bytes);
+        bytes.extend(checksum.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn ids_round_trip() {
+        for table_number in [1, 127, 128, 10_001] {
+            let id = encode(&encoded(table_number, [7; 16]));
+            assert_eq!(decode(&id).unwrap(), encoded(table_number, [7; 16]));
+            let parsed = Id::<Messages>::parse(id.clone()).unwrap();
+            assert_eq!(parsed.as_str(), id);
+            let json = serde_json::to_string(&parsed).unwrap();
+            assert_eq!(serde_json::from_str::<Id<Messages>>(&json).unwrap(), parsed);
+        }
+        // Two byte table numbers give the usual 32 character IDs.
+        assert_eq!(encode(&encoded(10_001, [7; 16])).len(), 32);
+    }
+
+    #[test]
+    fn local_ids_parse() {
+        let local = Id::<Messages>::local([42; 16]);
+        assert_eq!(Id::<Messages>::parse(local.as_str()).unwrap(), local);
+        assert_eq!(local, Id::local([42; 16]));
+        assert_ne!(local, Id::local([43; 16]));
+    }
+
+    #[test]
+    fn bad_checksums_are_rejected() {
+        let mut bytes = encoded(10_001, [7; 16]);
+        bytes[5] ^= 1;
+        assert!(validate(&encode(&bytes)).unwrap_err().contains("checksum"));
+
+        let mut bytes = encoded(10_001, [7; 16]);
+        *bytes.last_mut().unwrap() ^= 1;
+        assert!(Id::<Messages>::parse(encode(&bytes)).is_err());
+    }
+
+    #[test]
+    fn wrong_lengths_are_rejected() {
+        let id = encode(&encoded(10_001, [7; 16]));
+        assert_eq!(validate(&id[..31]), Err("wrong length"));
+        assert_eq!(validate(&format!("{id}0")), Err("wrong length"));
+        assert_eq!(validate(""), Err("wrong length"));
+
+        // Whole bytes, but not as many as the table number calls for.
+        let mut bytes = encoded(1, [7; 16]);
+        bytes.insert(1, 0);
+        let checksum = fletcher16(&bytes[..bytes.len() - 2]);
+        let len = bytes.len();
+        bytes[len - 2..].copy_from_slice(&checksum.to_le_bytes());
+        assert_eq!(validate(&encode(&bytes)), Err("wrong length"));
+    }
+
+    #[test]
+    fn table_numbers_past_u32_are_rejected() {
+        let mut bytes = vec![0xff, 0xff, 0xff, 0xff, 0x10];
+        bytes.extend([7; 16]);
+        let checksum = fletcher16(&bytes);
+        bytes.extend(checksum.to_le_bytes());
+        assert_eq!(validate(&encode(&bytes)), Err("bad table number"));
+    }
+
+    #[test]
+    fn wrong_alphabets_are_rejected() {
+        let id = encode(&encoded(10_001, [7; 16]));
+        assert_eq!(validate(&id.to_uppercase()), Err("IDs are lowercase"));
+        // Crockford base32 leaves out i, l, o and u.
+        for c in ['i', 'l', 'o', 'u', '-', '='] {
+            let id = format!("{c}{}", &id[1..]);
+            assert_eq!(validate(&id), Err("not base32"));
+        }
+    }
+}
@@ -4,16 +4,26 @@ use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use strum_macros::{Display, EnumString};
 
+use self::tables::{
+    Attachments, Categories, Channels, Members, Mentions, MessageReactionCounts, Messages,
+    RoleMentions, Roles, Servers, Storage, Users,
+};
+
+mod id;
+pub mod tables;
+
+pub use self::id::{Id, Table};
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Server {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Servers>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
     pub name: String,
     pub image_url: Option<String>,
     #[serde(rename = "defaultRole")]
-    pub default_role: Option<String>,
+    pub default_role: Option<Id<Roles>>,
     pub description: Option<String>,
     #[serde(rename = "bannerUrl")]
     pub banner_url: Option<String>,
@@ -32,7 +42,7 @@ pub enum ServerType {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct User {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Users>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
     pub name: String,
@@ -51,34 +61,34 @@ pub enum ChannelType {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Channel {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Channels>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
     pub name: String,
     #[serde(rename = "type")]
     pub _type: Option<ChannelType>,
-    pub category: Option<String>,
-    pub server: String,
+    pub category: Option<Id<Categories>>,
+    pub server: Id<Servers>,
     pub topic: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Category {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Categories>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
     pub name: String,
-    pub server: String,
+    pub server: Id<Servers>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Invitation {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<tables::Invitations>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub server: String,
+    pub server: Id<Servers>,
     pub invitation: String,
 }
 
@@ -110,19 +120,19 @@ where
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Hash, Eq)]
 pub struct Member {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Members>,
     #[serde(rename = "_creationTime")]
     #[serde(deserialize_with = "deserialize_f64_to_i64")]
     creation_time: i64,
-    pub user: String,
-    pub server: String,
-    pub roles: Vec<String>,
+    pub user: Id<Users>,
+    pub server: Id<Servers>,
+    pub roles: Vec<Id<Roles>>,
     pub name: String,
     pub image_url: Option<String>,
     #[serde(rename = "bannerUrl")]
     pub banner_url: Option<String>,
     #[serde(rename = "mostImportantRole")]
-    pub most_important_role: Option<String>,
+    pub most_important_role: Option<Id<Roles>>,
 }
 
 #[derive(
@@ -172,10 +182,10 @@ pub struct RoleActions {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Invitations {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<tables::Invitations>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub server: String,
+    pub server: Id<Servers>,
     pub invitation: String,
     #[serde(rename = "expiresAt")]
     pub expires_at: f64,
@@ -184,10 +194,10 @@ pub struct Invitations {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Role {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Roles>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub server: String,
+    pub server: Id<Servers>,
     pub name: String,
     #[serde(rename = "isOwner")]
     pub is_owner: bool,
@@ -200,10 +210,10 @@ pub struct Role {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Reaction {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<MessageReactionCounts>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub message: String,
+    pub message: Id<Messages>,
     pub emoji: String,
     pub count: f64,
     #[serde(rename = "hasReacted")]
@@ -213,32 +223,32 @@ pub struct Reaction {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Mention {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Mentions>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub message: String,
-    pub member: String,
+    pub message: Id<Messages>,
+    pub member: Id<Members>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct RoleMention {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<RoleMentions>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub message: String,
-    pub role: String,
+    pub message: Id<Messages>,
+    pub role: Id<Roles>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Attachment {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Attachments>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub message: String,
+    pub message: Id<Messages>,
     #[serde(rename = "storageId")]
-    pub storage_id: String,
+    pub storage_id: Id<Storage>,
     pub url: Option<String>,
     pub metadata: Option<FileMetaData>,
     pub name: Option<String>,
@@ -247,7 +257,7 @@ pub struct Attachment {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct FileMetaData {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Storage>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
     #[serde(rename = "contentType")]
@@ -324,11 +334,11 @@ impl<'de> Deserialize<'de> for FileType {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ChannelMessage {
     #[serde(rename = "_id")]
-    pub id: String,
+    pub id: Id<Messages>,
     #[serde(rename = "_creationTime")]
     pub creation_time: f64,
-    pub channel: String,
-    pub sender: String,
+    pub channel: Id<Channels>,
+    pub sender: Id<Members>,
     #[serde(rename = "referencedMessage")]
     pub referenced_message: Option<Box<ChannelMessage>>,
     pub content: String,
@@ -337,7 +347,7 @@ pub struct ChannelMessage {
     #[serde(rename = "mention_everyone")]
     pub mention_everyone: bool,
     #[serde(rename = "mention_roles")]
-    pub mention_roles: Vec<String>,
+    pub mention_roles: Vec<Id<Roles>>,
     #[serde[default]]
     pub reactions: Vec<Reaction>,
     #[serde[default]]
@@ -0,0 +1,48 @@
+//! Marker types for the tables of the schema, naming the table of an [`Id`].
+
+use super::Table;
+
+#[cfg(doc)]
+use super::Id;
+
+macro_rules! tables {
+    ($($marker:ident => $name:literal,)+) => {
+        $(
+            #[doc = concat!("The `", $name, "` table.")]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub enum $marker {}
+
+            impl Table for $marker {
+                const NAME: &'static str = $name;
+            }
+        )+
+    };
+}
+
+tables! {
+    Attachments => "attachments",
+    Categories => "categories",
+    Channels => "channels",
+    Conversations => "conversations",
+    Friends => "friends",
+    Invitations => "invitations",
+    LastVisitedChannels => "lastVisitedChannels",
+    MemberChannelLastReads => "memberChannelLastReads",
+    MemberReactions => "memberReactions",
+    Members => "members",
+    Mentions => "mentions",
+    MessageReactionCounts => "messageReactionCounts",
+    Messages => "messages",
+    PinnedMessages => "pinnedMessages",
+    PrivateMessageReads => "privateMessageReads",
+    PrivateMessages => "privateMessages",
+    RoleMentions => "role_mentions",
+    Roles => "roles",
+    Servers => "servers",
+    SessionTimeouts => "sessionTimeouts",
+    Sessions => "sessions",
+    Storage => "_storage",
+    Tasks => "tasks",
+    UserStatus => "userStatus",
+    Users => "users",
+}
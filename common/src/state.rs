@@ -1,5 +1,5 @@
 use axum::extract::FromRef;
-use convex_client::server::ConvexClient;
+use convex_client::server::{ConvexClient, ConvexHttpClient};
 use leptos::prelude::{LeptosOptions, ServerFnError, use_context};
 use leptos_axum::AxumRouteListing;
 use sqlx::PgPool;
@@ -11,6 +11,9 @@ pub struct AppState {
     pub leptos_options: LeptosOptions,
     pub pool: PgPool,
     pub convex: ConvexClient,
+    /// For server functions calling Convex once, without going through the
+    /// shared connection of `convex`.
+    pub convex_http: ConvexHttpClient,
     pub routes: Vec<AxumRouteListing>,
 }
 
@@ -25,3 +28,9 @@ pub fn convex() -> Result<ConvexClient, ServerFnError> {
         .ok_or_else(|| ServerFnError::new("Pool missing."))?
         .convex)
 }
+
+pub fn convex_http() -> Result<ConvexHttpClient, ServerFnError> {
+    Ok(use_context::<AppState>()
+        .ok_or_else(|| ServerFnError::new("Convex HTTP client missing."))?
+        .convex_http)
+}
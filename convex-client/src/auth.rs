@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::{self, BoxFuture};
+use leptos::logging::log;
+use serde_json::Value;
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::runtime::Runtime;
+
+/// How long before a token expires the client fetches a fresh one.
+const REFRESH_LEEWAY: Duration = Duration::from_secs(10);
+
+/// Fetches the token to authenticate with, or `None` once signed out.
+/// `force_refresh` asks for a token that wasn't cached, as the previous one
+/// expired or was rejected.
+pub(crate) type TokenFetcher =
+    Arc<dyn Fn(bool) -> BoxFuture<'static, Option<String>> + Send + Sync>;
+
+/// The worker's side of a client authenticated through a [`TokenFetcher`]:
+/// it fetches the first token, a fresh one shortly before each expires, and
+/// one more when the deployment rejects the current one.
+pub(crate) struct AuthRefresher {
+    fetch_token: TokenFetcher,
+    fetch: Option<BoxFuture<'static, Option<String>>>,
+    /// When to fetch a fresh token, shortly before the current one expires.
+    refresh_at: Option<Instant>,
+    /// Whether the current token replaces a rejected one and hasn't been
+    /// accepted yet.
+    retrying: bool,
+}
+
+impl AuthRefresher {
+    pub fn new(fetch_token: TokenFetcher) -> Self {
+        let fetch = fetch_token(false);
+        Self {
+            fetch_token,
+            fetch: Some(fetch),
+            refresh_at: None,
+            retrying: false,
+        }
+    }
+
+    /// Resolves with the next token to authenticate with.
+    ///
+    /// Cancel safe: a fetch in flight carries on at the next call.
+    pub async fn next_token<R: Runtime>(&mut self) -> Option<String> {
+        if self.fetch.is_none() {
+            match self.refresh_at {
+                Some(refresh_at) => {
+                    R::sleep(refresh_at.saturating_duration_since(Instant::now())).await
+                }
+                None => future::pending().await,
+            }
+            self.fetch = Some((self.fetch_token)(true));
+        }
+        let token = self.fetch.as_mut().expect("fetch started above").await;
+        self.fetch = None;
+        self.refresh_at = token.as_deref().and_then(refresh_time);
+        token
+    }
+
+    /// The deployment rejected the current token. Returns whether it is
+    /// worth fetching a fresh one, which is then under way, or the rejected
+    /// token was already a fresh one.
+    pub fn retry(&mut self) -> bool {
+        if self.retrying {
+            return false;
+        }
+        self.retrying = true;
+        self.refresh_at = None;
+        self.fetch = Some((self.fetch_token)(true));
+        true
+    }
+
+    /// The deployment accepted the current token.
+    pub fn confirm(&mut self) {
+        self.retrying = false;
+    }
+}
+
+/// When to refresh `token`, from the `exp` claim of a JWT. `None` for
+/// tokens that aren't JWTs or expire too soon to be refreshed ahead of time.
+fn refresh_time(token: &str) -> Option<Instant> {
+    let payload = token.split('.').nth(1)?;
+    let payload =
+        base64::decode_config(payload.trim_end_matches('='), base64::URL_SAFE_NO_PAD).ok()?;
+    let claims: Value = serde_json::from_slice(&payload).ok()?;
+    let expires_at = UNIX_EPOCH + Duration::from_secs(claims["exp"].as_u64()?);
+    let valid_for = expires_at.duration_since(SystemTime::now()).ok()?;
+    let Some(refresh_in) = valid_for.checked_sub(REFRESH_LEEWAY) else {
+        log!("Auth token expires in {valid_for:?}, too soon to refresh it ahead of time.");
+        return None;
+    };
+    Some(Instant::now() + refresh_in)
+}
+
+/// [`AuthRefresher::next_token`] of `auth`, or never without one.
+pub(crate) async fn next_token<R: Runtime>(auth: &mut Option<AuthRefresher>) -> Option<String> {
+    match auth {
+        Some(auth) => auth.next_token::<R>().await,
+        None => future::pending().await,
+    }
+}
@@ -1,21 +1,32 @@
+pub mod mutation_store;
+pub mod optimistic_update;
 pub mod query_result;
 pub mod request_manager;
+#[cfg(test)]
+mod tests;
 
 use std::cmp;
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
 
 use convex_sync_types::{
     AuthenticationToken, CanonicalizedUdfPath, ClientMessage, IdentityVersion, QueryId,
-    QuerySetModification, QuerySetVersion, SessionRequestSeqNumber, StateModification,
-    StateVersion, Timestamp, UdfPath,
+    QuerySetModification, QuerySetVersion, SerializedQueryJournal, SessionRequestSeqNumber,
+    StateModification, StateVersion, Timestamp, UdfPath,
 };
 use futures::channel::oneshot;
-use leptos::logging::{error, log};
+use leptos::logging::error;
 use serde_json::{Value, json};
+use web_time::Instant;
 
-use crate::ConvexError;
+use crate::logs::{FunctionKind, LogLine, LogSink, default_log_sink};
+use crate::observer::ClientObserver;
 use crate::websocket::{ReconnectProtocolReason, ServerMessage};
+use crate::{AuthError, ConvexError};
 
+use self::mutation_store::{InMemoryMutationStore, MutationStore, PersistedMutation};
+use self::optimistic_update::{OptimisticLocalStore, OptimisticUpdate};
 use self::query_result::{FunctionResult, QueryResults};
 use self::request_manager::{RequestId, RequestManager, RequestType};
 
@@ -25,6 +36,17 @@ pub struct SubscriberId(QueryId, usize);
 #[derive(Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
 struct QueryToken(String);
 
+/// A server message that failed the protocol and calls for more than a plain
+/// reconnect.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerError {
+    /// An auth token was rejected. If it is the `current` one, it was
+    /// dropped, so the client reconnects unauthenticated.
+    Auth { error: AuthError, current: bool },
+    /// The deployment won't serve this client; reconnecting won't help.
+    Fatal(String),
+}
+
 #[derive(Clone, Debug)]
 struct LocalQuery {
     id: QueryId,
@@ -32,13 +54,20 @@ struct LocalQuery {
     args: Value,
     num_subscribers: usize,            // TODO: remove
     next_subscriber_id_counter: usize, // New: provides unique IDs for each subscription instance
+    /// When the last subscriber dropped, if the query is being kept alive
+    /// without any.
+    idle_since: Option<Instant>,
+    /// The journal of the latest result, sent back when the query is
+    /// re-added on reconnect so that the server resumes it consistently,
+    /// e.g. with the same page boundaries. `None` until a result arrives.
+    journal: Option<SerializedQueryJournal>,
 }
 
 #[derive(Clone, Debug)]
 struct Query {
     result: FunctionResult,
-    _udf_path: CanonicalizedUdfPath,
-    _args: Value,
+    udf_path: CanonicalizedUdfPath,
+    args: Value,
 }
 
 #[derive(Debug)]
@@ -55,7 +84,12 @@ impl RemoteQuerySet {
         }
     }
 
-    fn transition(&mut self, transition: ServerMessage) -> Result<(), ReconnectProtocolReason> {
+    /// Apply a transition, returning the journals and log lines of the
+    /// queries it updated.
+    fn transition(
+        &mut self,
+        transition: ServerMessage,
+    ) -> Result<Vec<(QueryId, SerializedQueryJournal, Vec<String>)>, ReconnectProtocolReason> {
         let ServerMessage::Transition {
             start_version,
             end_version,
@@ -72,30 +106,26 @@ impl RemoteQuerySet {
             );
             return Err("StartVersionMismatch".into());
         }
+        let mut updates = Vec::new();
         for modification in modifications {
             match modification {
                 StateModification::QueryUpdated {
                     query_id,
                     value,
                     log_lines,
-                    journal: _,
+                    journal,
                 } => {
-                    for log_line in log_lines.0 {
-                        log!("{}", log_line);
-                    }
                     self.remote_query_set
                         .insert(query_id, FunctionResult::Value(value));
+                    updates.push((query_id, journal, log_lines.0));
                 }
                 StateModification::QueryFailed {
                     query_id,
                     error_message,
                     log_lines,
-                    journal: _,
+                    journal,
                     error_data,
                 } => {
-                    for log_line in log_lines.0 {
-                        log!("{}", log_line);
-                    }
                     let function_result = match error_data {
                         Some(v) => FunctionResult::ConvexError(ConvexError {
                             message: error_message,
@@ -104,6 +134,7 @@ impl RemoteQuerySet {
                         None => FunctionResult::ErrorMessage(error_message),
                     };
                     self.remote_query_set.insert(query_id, function_result);
+                    updates.push((query_id, journal, log_lines.0));
                 }
                 StateModification::QueryRemoved { query_id } => {
                     self.remote_query_set.remove(&query_id);
@@ -111,24 +142,67 @@ impl RemoteQuerySet {
             }
         }
         self.version = end_version;
-        Ok(())
+        Ok(updates)
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default)]
 struct OptimisticQueryResults {
     query_results: BTreeMap<QueryId, Query>,
+    optimistic_updates: Vec<(RequestId, OptimisticUpdate)>,
+}
+
+impl std::fmt::Debug for OptimisticQueryResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OptimisticQueryResults")
+            .field("query_results", &self.query_results)
+            .field(
+                "optimistic_updates",
+                &self
+                    .optimistic_updates
+                    .iter()
+                    .map(|(request_id, _)| request_id)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
 }
 
 impl OptimisticQueryResults {
+    /// Apply a new optimistic update on top of the current results and keep it
+    /// around so it can be replayed on future server transitions.
+    fn apply_optimistic_update(
+        &mut self,
+        update: OptimisticUpdate,
+        request_id: RequestId,
+    ) -> BTreeMap<QueryId, FunctionResult> {
+        let mut store = OptimisticLocalStore::new(&mut self.query_results);
+        update(&mut store);
+        let modified_queries = store.modified_queries();
+        self.optimistic_updates.push((request_id, update));
+
+        modified_queries
+            .into_iter()
+            .filter_map(|query_id| Some((query_id, self.query_result(query_id)?)))
+            .collect()
+    }
+
     fn ingest_query_results_from_server(
         &mut self,
         server_query_results: BTreeMap<QueryId, Query>,
-        _optimistic_updates_to_drop: BTreeSet<RequestId>,
+        optimistic_updates_to_drop: BTreeSet<RequestId>,
     ) -> BTreeMap<QueryId, FunctionResult> {
-        // TODO: use optimistic_updates_to_drop
-        let old_query_results = self.query_results.clone();
-        self.query_results = server_query_results;
+        self.optimistic_updates
+            .retain(|(request_id, _)| !optimistic_updates_to_drop.contains(request_id));
+
+        let old_query_results = std::mem::replace(&mut self.query_results, server_query_results);
+        // Replay the updates that are still pending on top of the fresh server
+        // results, in the order their mutations were issued.
+        let mut store = OptimisticLocalStore::new(&mut self.query_results);
+        for (_, update) in self.optimistic_updates.iter() {
+            update(&mut store);
+        }
+
         let mut changed_queries = BTreeMap::new();
         for (query_id, query) in self.query_results.iter() {
             let old_query = old_query_results.get(query_id);
@@ -157,6 +231,32 @@ struct LocalSyncState {
     latest_results: QueryResults,
     auth_token: AuthenticationToken,
     identity_version: IdentityVersion,
+    keep_alive: QueryKeepAlive,
+    /// Queries without subscribers that are still subscribed on the server,
+    /// least recently used first.
+    idle_queries: VecDeque<QueryToken>,
+    /// Query set modifications not sent yet. They go out together in the
+    /// next [`take_query_set_modification`](Self::take_query_set_modification).
+    pending_modifications: Vec<QuerySetModification>,
+    /// The queries removed by `pending_modifications`, to take back if they
+    /// are subscribed to again before it is sent.
+    pending_removals: BTreeMap<QueryToken, LocalQuery>,
+}
+
+/// How long queries stay subscribed after their last subscriber drops, so
+/// that subscribing again shows the cached result right away.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QueryKeepAlive {
+    pub duration: Duration,
+    /// The most idle queries to keep. Past it, the least recently used are
+    /// unsubscribed early.
+    pub max_idle_queries: usize,
+}
+
+impl QueryKeepAlive {
+    fn enabled(&self) -> bool {
+        !self.duration.is_zero() && self.max_idle_queries > 0
+    }
 }
 
 fn serialize_path_and_args(udf_path: UdfPath, args: Value) -> QueryToken {
@@ -170,15 +270,26 @@ fn serialize_path_and_args(udf_path: UdfPath, args: Value) -> QueryToken {
 }
 
 impl LocalSyncState {
-    fn subscribe(
-        &mut self,
-        udf_path: UdfPath,
-        args: Value,
-    ) -> (Option<ClientMessage>, SubscriberId) {
+    fn subscribe(&mut self, udf_path: UdfPath, args: Value) -> SubscriberId {
         let canonicalized_udf_path = udf_path.clone().canonicalize();
         let query_token = serialize_path_and_args(udf_path.clone(), args.clone());
 
+        if let Some(mut removed) = self.pending_removals.remove(&query_token) {
+            // The server never learned about the removal.
+            let query_id = removed.id;
+            self.pending_modifications.retain(|modification| {
+                !matches!(modification, QuerySetModification::Remove { query_id: id } if *id == query_id)
+            });
+            removed.num_subscribers = 0;
+            removed.idle_since = None;
+            self.query_set.insert(query_token.clone(), removed);
+            self.query_id_to_token.insert(query_id, query_token.clone());
+        }
+
         if let Some(existing_entry) = self.query_set.get_mut(&query_token) {
+            if existing_entry.idle_since.take().is_some() {
+                self.idle_queries.retain(|token| *token != query_token);
+            }
             existing_entry.num_subscribers += 1;
             let query_id = existing_entry.id;
             // Use the unique counter for this specific query
@@ -188,27 +299,19 @@ impl LocalSyncState {
             let subscription = SubscriberId(query_id, subscriber_counter);
             let prev = self.latest_results.subscribers.insert(subscription);
             assert!(prev.is_none(), "INTERNAL BUG: Subscriber ID already taken.");
-            return (None, subscription);
+            return subscription;
         }
 
         let query_id = self.next_query_id;
         self.next_query_id = QueryId::new(self.next_query_id.get_id() + 1);
-        let base_version = self.query_set_version;
-        self.query_set_version += 1;
-        let new_version = self.query_set_version;
-
-        let add = QuerySetModification::Add(convex_sync_types::Query {
-            query_id,
-            udf_path,
-            args: vec![args.clone()],
-            journal: None,
-            component_path: None,
-        });
-        let message = ClientMessage::ModifyQuerySet {
-            base_version,
-            new_version,
-            modifications: vec![add],
-        };
+        self.pending_modifications
+            .push(QuerySetModification::Add(convex_sync_types::Query {
+                query_id,
+                udf_path,
+                args: vec![args.clone()],
+                journal: None,
+                component_path: None,
+            }));
 
         // For a new query, the first subscriber gets ID 0, and the counter is set to 1 for the next one.
         let query = LocalQuery {
@@ -217,6 +320,8 @@ impl LocalSyncState {
             args,
             num_subscribers: 1,
             next_subscriber_id_counter: 1, // Start counter at 1 for the next subscriber
+            idle_since: None,
+            journal: None,
         };
 
         self.query_set.insert(query_token.clone(), query);
@@ -224,10 +329,10 @@ impl LocalSyncState {
         let subscription = SubscriberId(query_id, 0); // The very first subscriber for this query_id
         let prev = self.latest_results.subscribers.insert(subscription);
         assert!(prev.is_none(), "INTERNAL BUG: Subscriber ID already taken.");
-        (Some(message), subscription)
+        subscription
     }
 
-    fn remove_subscriber(&mut self, subscriber_id: SubscriberId) -> Option<ClientMessage> {
+    fn remove_subscriber(&mut self, subscriber_id: SubscriberId) {
         let query_id = self
             .latest_results
             .subscribers
@@ -246,20 +351,81 @@ impl LocalSyncState {
         // Update local state
         if local_query.num_subscribers > 1 {
             local_query.num_subscribers -= 1;
-            return None;
+            return;
         }
-        self.query_set.remove(&query_token);
-        self.query_id_to_token.remove(&query_id);
+        if !self.keep_alive.enabled() {
+            self.remove_queries(vec![query_token]);
+            return;
+        }
+        local_query.num_subscribers = 0;
+        local_query.idle_since = Some(Instant::now());
+        self.idle_queries.push_back(query_token);
+        let num_evicted = self
+            .idle_queries
+            .len()
+            .saturating_sub(self.keep_alive.max_idle_queries);
+        let evicted = self.idle_queries.drain(..num_evicted).collect();
+        self.remove_queries(evicted)
+    }
+
+    /// When the next idle query runs out of keep-alive time.
+    fn next_idle_expiry(&self) -> Option<Instant> {
+        self.idle_queries
+            .iter()
+            .filter_map(|token| self.query_set.get(token)?.idle_since)
+            .min()
+            .map(|idle_since| idle_since + self.keep_alive.duration)
+    }
+
+    fn expire_idle_queries(&mut self, now: Instant) {
+        let (expired, live): (Vec<_>, Vec<_>) = std::mem::take(&mut self.idle_queries)
+            .into_iter()
+            .partition(|token| {
+                self.query_set
+                    .get(token)
+                    .and_then(|query| query.idle_since)
+                    .is_none_or(|idle_since| idle_since + self.keep_alive.duration <= now)
+            });
+        self.idle_queries = live.into();
+        self.remove_queries(expired);
+    }
+
+    /// Forget the queries of `query_tokens` and queue their removal, or drop
+    /// their pending addition if the server doesn't know about them yet.
+    fn remove_queries(&mut self, query_tokens: Vec<QueryToken>) {
+        for query_token in query_tokens {
+            let Some(query) = self.query_set.remove(&query_token) else {
+                continue;
+            };
+            let query_id = query.id;
+            self.query_id_to_token.remove(&query_id);
+            let num_pending = self.pending_modifications.len();
+            self.pending_modifications.retain(|modification| {
+                !matches!(modification, QuerySetModification::Add(added) if added.query_id == query_id)
+            });
+            if self.pending_modifications.len() == num_pending {
+                self.pending_modifications
+                    .push(QuerySetModification::Remove { query_id });
+                self.pending_removals.insert(query_token, query);
+            }
+        }
+    }
 
+    /// The modifications queued since the last call, as one query set
+    /// version bump.
+    fn take_query_set_modification(&mut self) -> Option<ClientMessage> {
+        if self.pending_modifications.is_empty() {
+            return None;
+        }
+        self.pending_removals.clear();
         let base_version = self.query_set_version;
         self.query_set_version += 1;
         let new_version = self.query_set_version;
 
-        let remove = QuerySetModification::Remove { query_id };
         Some(ClientMessage::ModifyQuerySet {
             base_version,
             new_version,
-            modifications: vec![remove],
+            modifications: std::mem::take(&mut self.pending_modifications),
         })
     }
 
@@ -276,6 +442,10 @@ impl LocalSyncState {
         )
     }
 
+    fn local_query(&self, query_id: QueryId) -> Option<&LocalQuery> {
+        self.query_set.get(&self.query_token(query_id)?)
+    }
+
     fn query_path(&self, query_id: QueryId) -> Option<CanonicalizedUdfPath> {
         Some(
             self.query_set
@@ -285,6 +455,25 @@ impl LocalSyncState {
         )
     }
 
+    fn save_journal(&mut self, query_id: QueryId, journal: SerializedQueryJournal) {
+        let Some(query_token) = self.query_token(query_id) else {
+            return;
+        };
+        if let Some(local_query) = self.query_set.get_mut(&query_token) {
+            local_query.journal = Some(journal);
+        }
+    }
+
+    /// Stop sending the token the server rejected at `base_version`, unless
+    /// it was replaced since. Returns whether it was dropped.
+    fn clear_rejected_auth(&mut self, base_version: Option<IdentityVersion>) -> bool {
+        let current = base_version.is_none_or(|version| version + 1 == self.identity_version);
+        if current {
+            self.auth_token = AuthenticationToken::None;
+        }
+        current
+    }
+
     fn set_auth(&mut self, token: AuthenticationToken) -> ClientMessage {
         self.auth_token = token.clone();
         let base_version = self.identity_version;
@@ -304,7 +493,7 @@ impl LocalSyncState {
                 query_id: local_query.id,
                 udf_path: local_query.canonicalized_udf_path.clone().into(),
                 args: vec![json_args],
-                journal: None,
+                journal: local_query.journal.clone(),
                 component_path: None,
             });
             modifications.push(add)
@@ -338,6 +527,15 @@ pub struct BaseConvexClient {
     next_request_id: SessionRequestSeqNumber,
     outgoing_message_queue: VecDeque<ClientMessage>,
     max_observed_timestamp: Option<Timestamp>,
+    mutation_store: Box<dyn MutationStore>,
+    /// Idempotency keys of the mutations in `mutation_store` that this
+    /// session sent or will send.
+    persisted_mutations: BTreeMap<RequestId, String>,
+    server_error: Option<ServerError>,
+    observer: Option<Arc<dyn ClientObserver>>,
+    log_sink: Arc<dyn LogSink>,
+    /// Queries subscribed to since the server last sent them a result.
+    awaiting_first_result: BTreeSet<QueryId>,
 }
 
 impl BaseConvexClient {
@@ -357,17 +555,66 @@ impl BaseConvexClient {
             next_request_id,
             outgoing_message_queue: VecDeque::new(),
             max_observed_timestamp: None,
+            mutation_store: Box::new(InMemoryMutationStore::default()),
+            persisted_mutations: BTreeMap::new(),
+            server_error: None,
+            observer: None,
+            log_sink: default_log_sink(),
+            awaiting_first_result: BTreeSet::new(),
         }
     }
 
-    /// Update state to be subscribed to a query and add subscription request to
-    /// the outgoing message queue.
+    /// Report what the client does to `observer`.
+    pub fn set_observer(&mut self, observer: Arc<dyn ClientObserver>) {
+        self.observer = Some(observer);
+    }
+
+    pub fn observer(&self) -> Option<&dyn ClientObserver> {
+        self.observer.as_deref()
+    }
+
+    /// Send the lines the functions log to `log_sink`.
+    pub fn set_log_sink(&mut self, log_sink: Arc<dyn LogSink>) {
+        self.log_sink = log_sink;
+    }
+
+    /// Keep mutations with an idempotency key in `store` until the server
+    /// acknowledges them, and queue the mutations left in it by a previous
+    /// session, oldest first.
     ///
-    /// After calling this, it is highly recommended to loop on
-    /// [`pop_next_message`](Self::pop_next_message()) to flush websocket
-    /// messages to the server.
+    /// Replayed mutations have no optimistic update and nobody waits on their
+    /// result.
+    pub fn set_mutation_store(&mut self, store: Box<dyn MutationStore>) {
+        self.mutation_store = store;
+        for persisted in self.mutation_store.load() {
+            if self
+                .persisted_mutations
+                .values()
+                .any(|key| *key == persisted.key)
+            {
+                continue;
+            }
+            let udf_path = match persisted.udf_path.parse() {
+                Ok(udf_path) => udf_path,
+                Err(err) => {
+                    error!("Dropping persisted mutation {}: {err}", persisted.udf_path);
+                    self.mutation_store.remove(&persisted.key);
+                    continue;
+                }
+            };
+            let _ = self.mutation(udf_path, persisted.args, None, Some(persisted.key));
+        }
+    }
+
+    /// Update state to be subscribed to a query.
+    ///
+    /// Subscriptions and unsubscriptions are batched: the ones made between
+    /// two calls to [`pop_next_message`](Self::pop_next_message()) go out as
+    /// a single query set modification, in which subscribing to a query and
+    /// unsubscribing from it cancel out. Loop on it after calling this to
+    /// flush websocket messages to the server.
     pub fn subscribe(&mut self, udf_path: UdfPath, args: Value) -> SubscriberId {
-        let (modification, subscription) = self.state.subscribe(udf_path, args);
+        let subscription = self.state.subscribe(udf_path, args);
         let query_id = subscription.0;
         if let Some(cached_result) = self.get_query(query_id) {
             self.state
@@ -375,24 +622,53 @@ impl BaseConvexClient {
                 .results
                 .insert(query_id, cached_result);
         }
-        if let Some(modification) = modification {
-            self.outgoing_message_queue.push_back(modification);
-        }
         subscription
     }
 
-    /// Update state to be unsubscribed to a query and add unsubscription
-    /// request to the outgoing message queue.
+    /// Update state to be unsubscribed to a query, batched like
+    /// [`subscribe`](Self::subscribe()).
     ///
     /// After calling this, it is highly recommended to loop on
     /// [`pop_next_message`](Self::pop_next_message()) to flush websocket
     /// messages to the server.
     pub fn unsubscribe(&mut self, subscriber_id: SubscriberId) {
-        let unsubscribe_message = self.state.remove_subscriber(subscriber_id);
+        self.state.remove_subscriber(subscriber_id);
+    }
 
-        if let Some(message) = unsubscribe_message {
-            self.outgoing_message_queue.push_back(message);
+    /// Use `result`, rendered on the server, as the value of the query of
+    /// `subscriber_id` until the deployment sends one.
+    ///
+    /// Ignored if the client already has a result for the query.
+    pub fn seed_query(&mut self, subscriber_id: SubscriberId, result: FunctionResult) {
+        let query_id = subscriber_id.0;
+        if self.get_query(query_id).is_some()
+            || self.state.latest_results.results.contains_key(&query_id)
+        {
+            return;
         }
+        self.state.latest_results.results.insert(query_id, result);
+    }
+
+    /// Keep queries subscribed for a while after their last subscriber
+    /// drops. Call [`expire_idle_queries`](Self::expire_idle_queries()) once
+    /// [`next_idle_expiry`](Self::next_idle_expiry()) has passed to
+    /// unsubscribe from them.
+    pub fn set_query_keep_alive(&mut self, keep_alive: QueryKeepAlive) {
+        self.state.keep_alive = keep_alive;
+    }
+
+    /// When the next query kept alive without subscribers should be dropped.
+    pub fn next_idle_expiry(&self) -> Option<Instant> {
+        self.state.next_idle_expiry()
+    }
+
+    /// Unsubscribe from the queries whose keep-alive has run out.
+    ///
+    /// After calling this, it is highly recommended to loop on
+    /// [`pop_next_message`](Self::pop_next_message()) to flush websocket
+    /// messages to the server.
+    pub fn expire_idle_queries(&mut self) {
+        self.state.expire_idle_queries(Instant::now());
     }
 
     /// Return the local value of a query.
@@ -402,6 +678,14 @@ impl BaseConvexClient {
 
     /// Track mutation and add mutation request to the outgoing message queue.
     ///
+    /// If an [`OptimisticUpdate`] is given, it is applied to the local query
+    /// results right away and kept until the server has observed the
+    /// mutation. Check [`latest_results`](Self::latest_results()) afterwards
+    /// for the patched values.
+    ///
+    /// With an `idempotency_key`, the mutation is kept in the
+    /// [`MutationStore`] until the server acknowledges it.
+    ///
     /// After calling this, it is highly recommended to loop on
     /// [`pop_next_message`](Self::pop_next_message()) to flush websocket
     /// messages to the server.
@@ -409,9 +693,20 @@ impl BaseConvexClient {
         &mut self,
         udf_path: UdfPath,
         args: Value,
-    ) -> oneshot::Receiver<FunctionResult> {
+        optimistic_update: Option<OptimisticUpdate>,
+        idempotency_key: Option<String>,
+    ) -> (RequestId, oneshot::Receiver<FunctionResult>) {
         let request_id = self.next_request_id;
         self.next_request_id = request_id + 1;
+        if let Some(key) = idempotency_key {
+            self.mutation_store.insert(PersistedMutation {
+                key: key.clone(),
+                udf_path: udf_path.to_string(),
+                args: args.clone(),
+            });
+            self.persisted_mutations
+                .insert(RequestId::new(request_id), key);
+        }
         let message = ClientMessage::Mutation {
             request_id,
             udf_path,
@@ -424,8 +719,16 @@ impl BaseConvexClient {
             RequestId::new(request_id),
             RequestType::Mutation,
         );
+        if let Some(optimistic_update) = optimistic_update {
+            let changed_queries = self
+                .optimistic_query_results
+                .apply_optimistic_update(optimistic_update, RequestId::new(request_id));
+            for (id, result) in changed_queries {
+                self.state.latest_results.results.insert(id, result);
+            }
+        }
         self.outgoing_message_queue.push_back(message);
-        result_receiver
+        (RequestId::new(request_id), result_receiver)
     }
 
     /// Track action and add action request to the outgoing message queue.
@@ -433,7 +736,11 @@ impl BaseConvexClient {
     /// After calling this, it is highly recommended to loop on
     /// [`pop_next_message`](Self::pop_next_message()) to flush websocket
     /// messages to the server.
-    pub fn action(&mut self, udf_path: UdfPath, args: Value) -> oneshot::Receiver<FunctionResult> {
+    pub fn action(
+        &mut self,
+        udf_path: UdfPath,
+        args: Value,
+    ) -> (RequestId, oneshot::Receiver<FunctionResult>) {
         let request_id = self.next_request_id;
         self.next_request_id = request_id + 1;
         let message = ClientMessage::Action {
@@ -449,7 +756,39 @@ impl BaseConvexClient {
             RequestType::Action,
         );
         self.outgoing_message_queue.push_back(message);
-        result_receiver
+        (RequestId::new(request_id), result_receiver)
+    }
+
+    /// Drop the mutation or action `request_id` if it has not been popped
+    /// from the outgoing message queue yet, rolling back its optimistic
+    /// update. Requests already sent keep going; only their result is
+    /// discarded.
+    ///
+    /// Returns the latest query results if the rollback changed them.
+    pub fn cancel(
+        &mut self,
+        request_id: RequestId,
+    ) -> Result<Option<QueryResults>, ReconnectProtocolReason> {
+        if !self.request_manager.cancel(&request_id) {
+            return Ok(None);
+        }
+        self.forget_persisted_mutation(request_id);
+        self.outgoing_message_queue.retain(|message| {
+            !matches!(
+                message,
+                ClientMessage::Mutation { request_id: id, .. }
+                    | ClientMessage::Action { request_id: id, .. }
+                    if RequestId::new(*id) == request_id
+            )
+        });
+        let changed_query_ids = self.on_query_result_changes(BTreeSet::from([request_id]))?;
+        if changed_query_ids.is_empty() {
+            return Ok(None);
+        }
+        for (id, result) in changed_query_ids {
+            self.state.latest_results.results.insert(id, result);
+        }
+        Ok(Some(self.state.latest_results.clone()))
     }
 
     /// Set auth on the sync protocol.
@@ -464,7 +803,118 @@ impl BaseConvexClient {
     /// has no awareness of websockets. After popping the next message, it is
     /// the caller's responsibility to actually send it.
     pub fn pop_next_message(&mut self) -> Option<ClientMessage> {
-        self.outgoing_message_queue.pop_front()
+        if let Some(message) = self.take_query_set_modification() {
+            self.outgoing_message_queue.push_back(message);
+        }
+        let message = self.outgoing_message_queue.pop_front()?;
+        match &message {
+            ClientMessage::Mutation {
+                request_id,
+                udf_path,
+                ..
+            } => {
+                let request_id = RequestId::new(*request_id);
+                if self.request_manager.mark_sent(&request_id)
+                    && let Some(observer) = &self.observer
+                {
+                    observer.on_mutation_sent(request_id, &udf_path.clone().canonicalize());
+                }
+            }
+            ClientMessage::Action { request_id, .. } => {
+                self.request_manager.mark_sent(&RequestId::new(*request_id));
+            }
+            _ => {}
+        }
+        Some(message)
+    }
+
+    /// The batched subscriptions and unsubscriptions as one
+    /// `ModifyQuerySet`, if there are any.
+    fn take_query_set_modification(&mut self) -> Option<ClientMessage> {
+        let message = self.state.take_query_set_modification()?;
+        let ClientMessage::ModifyQuerySet { modifications, .. } = &message else {
+            return Some(message);
+        };
+        for modification in modifications {
+            match modification {
+                QuerySetModification::Add(query) => {
+                    self.awaiting_first_result.insert(query.query_id);
+                    if let Some(observer) = &self.observer {
+                        observer
+                            .on_subscribe(query.query_id, &query.udf_path.clone().canonicalize());
+                    }
+                }
+                QuerySetModification::Remove { query_id } => {
+                    self.awaiting_first_result.remove(query_id);
+                    if let Some(observer) = &self.observer {
+                        observer.on_unsubscribe(*query_id);
+                    }
+                }
+            }
+        }
+        Some(message)
+    }
+
+    fn log_query_lines(&self, query_id: QueryId, log_lines: Vec<String>) {
+        let Some(query) = self.state.local_query(query_id) else {
+            return;
+        };
+        for log_line in log_lines {
+            self.log(LogLine::new(
+                FunctionKind::Query,
+                query.canonicalized_udf_path.clone(),
+                &query.args,
+                log_line,
+            ));
+        }
+    }
+
+    fn log_request_lines(&self, request_id: RequestId, log_lines: Vec<String>) {
+        let Some(request) = self.request_manager.request(&request_id) else {
+            return;
+        };
+        let (kind, udf_path, args) = match &request.message {
+            ClientMessage::Mutation { udf_path, args, .. } => {
+                (FunctionKind::Mutation, udf_path, args)
+            }
+            ClientMessage::Action { udf_path, args, .. } => (FunctionKind::Action, udf_path, args),
+            _ => return,
+        };
+        let args = args.first().unwrap_or(&Value::Null);
+        for log_line in log_lines {
+            self.log(LogLine::new(
+                kind,
+                udf_path.clone().canonicalize(),
+                args,
+                log_line,
+            ));
+        }
+    }
+
+    fn log(&self, line: LogLine) {
+        if let Some(observer) = &self.observer {
+            observer.on_log_line(&line);
+        }
+        self.log_sink.log(line);
+    }
+
+    /// Report the completed requests among `request_ids` that are mutations.
+    fn observe_completed_mutations<'a>(
+        &self,
+        request_ids: impl IntoIterator<Item = &'a RequestId>,
+    ) {
+        let Some(observer) = &self.observer else {
+            return;
+        };
+        for request_id in request_ids {
+            observer.on_mutation_observed(*request_id);
+        }
+    }
+
+    fn forget_persisted_mutation(&mut self, request_id: RequestId) {
+        if let Some(key) = self.persisted_mutations.remove(&request_id) {
+            self.mutation_store.remove(&key);
+        }
     }
 
     fn observe_timestamp(&mut self, ts: Timestamp) {
@@ -475,6 +925,11 @@ impl BaseConvexClient {
         }
     }
 
+    /// Returns the number of mutations sent but not yet observed by the client.
+    pub fn num_inflight_mutations(&self) -> usize {
+        self.request_manager.num_ongoing(RequestType::Mutation)
+    }
+
     /// Returns the maximum timestamp observed by the client.
     pub fn max_observed_timestamp(&self) -> Option<Timestamp> {
         self.max_observed_timestamp
@@ -488,14 +943,35 @@ impl BaseConvexClient {
         match message {
             ServerMessage::Transition { end_version, .. } => {
                 self.observe_timestamp(end_version.ts);
-                self.remote_query_set.transition(message)?;
+                let updates = self.remote_query_set.transition(message)?;
+                if let Some(observer) = &self.observer {
+                    observer.on_transition(end_version.ts, updates.len());
+                }
+                for (query_id, journal, log_lines) in updates {
+                    self.state.save_journal(query_id, journal);
+                    self.log_query_lines(query_id, log_lines);
+                    if self.awaiting_first_result.remove(&query_id)
+                        && let Some(observer) = &self.observer
+                    {
+                        observer.on_first_result(query_id);
+                    }
+                }
                 let completed_requests = self
                     .request_manager
                     .remove_and_notify_completed(end_version.ts);
+                self.observe_completed_mutations(&completed_requests);
                 let changed_query_ids = self.on_query_result_changes(completed_requests)?;
                 for (id, result) in changed_query_ids {
                     self.state.latest_results.results.insert(id, result);
                 }
+                // Every result the server holds is current as of this
+                // transition, updated by it or not.
+                self.state.latest_results.versions = self
+                    .remote_query_set
+                    .remote_query_set
+                    .keys()
+                    .map(|query_id| (*query_id, end_version))
+                    .collect();
                 return Ok(Some(self.state.latest_results.clone()));
             }
             ServerMessage::MutationResponse {
@@ -504,47 +980,65 @@ impl BaseConvexClient {
                 ts,
                 log_lines,
             } => {
-                for log_line in log_lines.0 {
-                    log!("{}", log_line);
-                }
-
                 if let Some(ts) = ts {
                     self.observe_timestamp(ts);
                 }
                 let request_id = RequestId::new(request_id);
-                self.request_manager.update_request(
+                self.log_request_lines(request_id, log_lines.0);
+                // The server ran the mutation, so it must not be replayed.
+                self.forget_persisted_mutation(request_id);
+                if let Some(observer) = &self.observer {
+                    observer.on_mutation_acknowledged(request_id);
+                }
+                let completed_request = self.request_manager.update_request(
                     &request_id,
                     RequestType::Mutation,
                     result.into(),
                     ts,
                 )?;
+                self.observe_completed_mutations(completed_request.iter());
+                // A failed mutation will never be observed in a transition, so
+                // roll back its optimistic update now.
+                if let Some(completed_request) = completed_request {
+                    let changed_query_ids =
+                        self.on_query_result_changes(BTreeSet::from([completed_request]))?;
+                    if !changed_query_ids.is_empty() {
+                        for (id, result) in changed_query_ids {
+                            self.state.latest_results.results.insert(id, result);
+                        }
+                        return Ok(Some(self.state.latest_results.clone()));
+                    }
+                }
             }
             ServerMessage::AuthError {
                 error_message,
                 base_version,
-                ..
+                auth_update_attempted,
             } => {
-                // tracing::error!(
-                //     "AuthError: {error_message} for identity version {base_version:?}. Restarting \
-                //      protocol."
-                // );
-                return Err(format!(
-                    "AuthError: {error_message} for identity version {base_version:?}"
-                ));
+                let current = self.state.clear_rejected_auth(base_version);
+                let reason =
+                    format!("AuthError: {error_message} for identity version {base_version:?}");
+                self.server_error = Some(ServerError::Auth {
+                    error: AuthError {
+                        message: error_message,
+                        auth_update_attempted,
+                    },
+                    current,
+                });
+                return Err(reason);
             }
             ServerMessage::FatalError { error_message } => {
-                // tracing::error!("FatalError: {error_message}. Restarting protocol.");
-                return Err(format!("FatalError: {error_message}"));
+                let reason = format!("FatalError: {error_message}");
+                self.server_error = Some(ServerError::Fatal(error_message));
+                return Err(reason);
             }
             ServerMessage::ActionResponse {
                 request_id,
                 result,
                 log_lines,
             } => {
-                for log_line in log_lines.0 {
-                    log!("{}", log_line);
-                }
                 let request_id = RequestId::new(request_id);
+                self.log_request_lines(request_id, log_lines.0);
                 self.request_manager.update_request(
                     &request_id,
                     RequestType::Action,
@@ -559,6 +1053,18 @@ impl BaseConvexClient {
         Ok(None)
     }
 
+    /// The [`ServerError`] behind the last failed [`receive_message`](Self::receive_message),
+    /// if it was one.
+    pub fn take_server_error(&mut self) -> Option<ServerError> {
+        self.server_error.take()
+    }
+
+    /// Whether the server has accepted the current auth token.
+    pub fn auth_confirmed(&self) -> bool {
+        self.state.auth_token != AuthenticationToken::None
+            && self.remote_query_set.version.identity >= self.state.identity_version
+    }
+
     /// Grab a snapshot of the latest query results to all subscribed queries.
     pub fn latest_results(&self) -> &QueryResults {
         &self.state.latest_results
@@ -567,6 +1073,9 @@ impl BaseConvexClient {
     /// Resend all subscribed queries and ongoing mutations. Should be used once
     /// the websocket closes and reconnects.
     pub fn resend_ongoing_queries_mutations(&mut self) {
+        // The restart covers the batched modifications, but the observer
+        // still hears about them.
+        let _ = self.take_query_set_modification();
         let state_restart_messages = self.state.restart();
         let mut ongoing_mutation_messages = self.request_manager.restart();
 
@@ -585,12 +1094,12 @@ impl BaseConvexClient {
         let remote_query_results = &self.remote_query_set.remote_query_set;
         let mut query_id_to_value = BTreeMap::new();
         for (query_id, result) in remote_query_results.iter() {
-            let Some(_udf_path) = self.state.query_path(*query_id) else {
+            let Some(udf_path) = self.state.query_path(*query_id) else {
                 // It's possible that we've already unsubscribed to this query but
                 // the server hasn't learned about that yet. If so, ignore this one.
                 continue;
             };
-            let _args = self
+            let args = self
                 .state
                 .query_args(*query_id)
                 .expect("INTERNAL BUG: Query args exist, but not query path.");
@@ -598,8 +1107,8 @@ impl BaseConvexClient {
                 *query_id,
                 Query {
                     result: result.clone(),
-                    _udf_path,
-                    _args,
+                    udf_path,
+                    args,
                 },
             );
         }
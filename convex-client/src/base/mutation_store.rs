@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A mutation kept in a [`MutationStore`] until the server acknowledges it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PersistedMutation {
+    /// The idempotency key of the mutation. Unlike request ids, which start
+    /// over with every session, it identifies the mutation across reloads.
+    pub key: String,
+    pub udf_path: String,
+    pub args: Value,
+}
+
+/// Where the client keeps the mutations the server has not acknowledged yet.
+///
+/// Only mutations with an idempotency key are stored. They are replayed in
+/// order when a new client is built on the same store, for example after a
+/// page reload, so the server may see a mutation twice and must dedupe it
+/// by its key.
+pub trait MutationStore: Send {
+    /// The stored mutations, oldest first.
+    fn load(&self) -> Vec<PersistedMutation>;
+    /// Store `mutation`, replacing any mutation with the same key.
+    fn insert(&mut self, mutation: PersistedMutation);
+    fn remove(&mut self, key: &str);
+}
+
+/// A [`MutationStore`] that only lives as long as the client, the default.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryMutationStore {
+    mutations: Vec<PersistedMutation>,
+}
+
+impl InMemoryMutationStore {
+    pub fn new(mutations: Vec<PersistedMutation>) -> Self {
+        Self { mutations }
+    }
+}
+
+impl MutationStore for InMemoryMutationStore {
+    fn load(&self) -> Vec<PersistedMutation> {
+        self.mutations.clone()
+    }
+
+    fn insert(&mut self, mutation: PersistedMutation) {
+        match self
+            .mutations
+            .iter_mut()
+            .find(|stored| stored.key == mutation.key)
+        {
+            Some(stored) => *stored = mutation,
+            None => self.mutations.push(mutation),
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.mutations.retain(|stored| stored.key != key);
+    }
+}
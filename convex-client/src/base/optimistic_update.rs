@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use convex_sync_types::{CanonicalizedUdfPath, QueryId, UdfPath};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::leptos::Query as ConvexQuery;
+use crate::value::{from_json, to_json};
+
+use super::Query;
+use super::query_result::FunctionResult;
+
+/// A closure that patches the local query results while a mutation is in
+/// flight.
+///
+/// The update is applied as soon as the mutation is queued and replayed on top
+/// of every new server transition until the server reports the mutation as
+/// observed, at which point it is dropped. Because of this replay it must be
+/// deterministic: capture everything it needs (ids, timestamps) up front.
+pub type OptimisticUpdate = Box<dyn Fn(&mut OptimisticLocalStore) + Send>;
+
+/// A view over the client's local query results that an [`OptimisticUpdate`]
+/// can read from and write to.
+///
+/// Only queries that are currently subscribed and have a result can be read
+/// or patched.
+pub struct OptimisticLocalStore<'a> {
+    query_results: &'a mut BTreeMap<QueryId, Query>,
+    modified_queries: BTreeSet<QueryId>,
+}
+
+impl<'a> OptimisticLocalStore<'a> {
+    pub(super) fn new(query_results: &'a mut BTreeMap<QueryId, Query>) -> Self {
+        Self {
+            query_results,
+            modified_queries: BTreeSet::new(),
+        }
+    }
+
+    pub(super) fn modified_queries(self) -> BTreeSet<QueryId> {
+        self.modified_queries
+    }
+
+    fn query_ids(&self, udf_path: UdfPath, args: &Value) -> Vec<QueryId> {
+        let udf_path: CanonicalizedUdfPath = udf_path.canonicalize();
+        self.query_results
+            .iter()
+            .filter(|(_, query)| query.udf_path == udf_path && &query.args == args)
+            .map(|(query_id, _)| *query_id)
+            .collect()
+    }
+
+    /// Return the current value of the query `udf_path` called with `args`, if
+    /// it is subscribed and did not fail.
+    pub fn get_query(&self, udf_path: UdfPath, args: &Value) -> Option<Value> {
+        let query_id = *self.query_ids(udf_path, args).first()?;
+        match &self.query_results.get(&query_id)?.result {
+            FunctionResult::Value(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Return the arguments and current value of every subscribed query to
+    /// `udf_path`, whatever its arguments.
+    pub fn get_all_queries(&self, udf_path: UdfPath) -> Vec<(Value, Option<Value>)> {
+        let udf_path: CanonicalizedUdfPath = udf_path.canonicalize();
+        self.query_results
+            .values()
+            .filter(|query| query.udf_path == udf_path)
+            .map(|query| {
+                let value = match &query.result {
+                    FunctionResult::Value(value) => Some(value.clone()),
+                    _ => None,
+                };
+                (query.args.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Overwrite the value of the query `udf_path` called with `args`.
+    ///
+    /// This is a no-op if the query is not subscribed.
+    pub fn set_query(&mut self, udf_path: UdfPath, args: &Value, value: Value) {
+        for query_id in self.query_ids(udf_path, args) {
+            if let Some(query) = self.query_results.get_mut(&query_id) {
+                query.result = FunctionResult::Value(value.clone());
+                self.modified_queries.insert(query_id);
+            }
+        }
+    }
+
+    /// Typed version of [`get_query`](Self::get_query).
+    pub fn query<F, Q>(&self, query: &Q) -> Option<F>
+    where
+        F: DeserializeOwned + Send + Sync + 'static,
+        Q: ConvexQuery<F> + Serialize,
+    {
+        let udf_path = query.name().parse().ok()?;
+        let args = query.args().ok()?;
+        from_json(self.get_query(udf_path, &args)?).ok()
+    }
+
+    /// Typed version of [`set_query`](Self::set_query).
+    pub fn set<F, Q>(&mut self, query: &Q, value: &F)
+    where
+        F: DeserializeOwned + Serialize + Send + Sync + 'static,
+        Q: ConvexQuery<F> + Serialize,
+    {
+        let (Ok(udf_path), Ok(args), Ok(value)) =
+            (query.name().parse(), query.args(), to_json(value))
+        else {
+            return;
+        };
+        self.set_query(udf_path, &args, value);
+    }
+}
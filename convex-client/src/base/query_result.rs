@@ -1,7 +1,8 @@
-use convex_sync_types::{QueryId, types::ErrorPayload};
+use convex_sync_types::{QueryId, StateVersion, types::ErrorPayload};
 use imbl::{OrdMap, OrdSet};
 
-use crate::ConvexError;
+use crate::{ConvexClientError, ConvexError};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::SubscriberId;
@@ -9,7 +10,7 @@ use super::SubscriberId;
 /// Result of a Convex function (query/mutation/action).
 ///
 /// The function returns a Convex value or an error message string.
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum FunctionResult {
     /// The Convex value returned on a successful run of a Convex function
     Value(Value),
@@ -46,6 +47,18 @@ impl From<FunctionResult> for Result<Value, ErrorPayload<Value>> {
     }
 }
 
+impl FunctionResult {
+    /// The returned value, or the function's error as a
+    /// [`ConvexClientError`].
+    pub fn into_result(self) -> Result<Value, ConvexClientError> {
+        match self {
+            FunctionResult::Value(value) => Ok(value),
+            FunctionResult::ErrorMessage(message) => Err(ConvexClientError::Server(message)),
+            FunctionResult::ConvexError(error) => Err(ConvexClientError::Convex(error)),
+        }
+    }
+}
+
 impl std::fmt::Debug for FunctionResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -63,6 +76,9 @@ impl std::fmt::Debug for FunctionResult {
 #[derive(Clone, Default, Debug)]
 pub struct QueryResults {
     pub(super) results: OrdMap<QueryId, FunctionResult>,
+    /// The server version each result received in this session is current
+    /// at.
+    pub(super) versions: OrdMap<QueryId, StateVersion>,
     pub(super) subscribers: OrdSet<SubscriberId>,
 }
 
@@ -75,6 +91,20 @@ impl QueryResults {
         self.results.get(&subscriber_id.0)
     }
 
+    /// The server [`StateVersion`] the result for `subscriber_id` is current
+    /// at, i.e. the version of the latest transition the client received.
+    ///
+    /// Results with the same version reflect the same server state, so they
+    /// are consistent with each other. `None` until the server sends the
+    /// result, e.g. while a result seeded from server rendering stands in
+    /// for it.
+    pub fn version(&self, subscriber_id: &SubscriberId) -> Option<StateVersion> {
+        if !self.subscribers.contains(subscriber_id) {
+            return None;
+        };
+        self.versions.get(&subscriber_id.0).copied()
+    }
+
     /// Get the size of the map.
     pub fn len(&self) -> usize {
         self.subscribers.len()
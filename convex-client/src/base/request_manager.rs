@@ -35,6 +35,9 @@ pub struct Request {
     pub ts: Option<Timestamp>,
     pub value: Option<FunctionResult>,
     pub message: ClientMessage,
+    /// Whether the message was ever handed to the protocol. Once sent, the
+    /// server may run the request even if the connection drops.
+    pub sent: bool,
 }
 
 impl Request {
@@ -46,6 +49,7 @@ impl Request {
             ts: None,
             value: None,
             message,
+            sent: false,
         }
     }
 
@@ -75,7 +79,7 @@ impl RequestManager {
         request_type: RequestType,
         value: FunctionResult,
         ts: Option<Timestamp>,
-    ) -> Result<(), ReconnectProtocolReason> {
+    ) -> Result<Option<RequestId>, ReconnectProtocolReason> {
         let Some((request, _)) = self.ongoing_requests.get_mut(request_id) else {
             return Err("Invalid request id from server".to_string());
         };
@@ -90,8 +94,9 @@ impl RequestManager {
         // Actions and errored mutations are ok to complete immediately
         if request_type == RequestType::Action || errored {
             self._remove_and_notify_completed(request_id);
+            return Ok(Some(*request_id));
         }
-        Ok(())
+        Ok(None)
     }
 
     pub fn remove_and_notify_completed(&mut self, ts: Timestamp) -> BTreeSet<RequestId> {
@@ -145,16 +150,51 @@ impl RequestManager {
         rx
     }
 
-    pub fn restart(&self) -> VecDeque<ClientMessage> {
-        // Sort ongoing requests by timestamp
-        let mut ordered_requests = Vec::from_iter(self.ongoing_requests.values());
-        ordered_requests.sort_by(|(req_a, _), (req_b, _)| req_b.ts.cmp(&req_a.ts));
+    pub fn request(&self, request_id: &RequestId) -> Option<&Request> {
+        self.ongoing_requests
+            .get(request_id)
+            .map(|(request, _)| request)
+    }
+
+    /// Returns whether the request was sent for the first time.
+    pub fn mark_sent(&mut self, request_id: &RequestId) -> bool {
+        match self.ongoing_requests.get_mut(request_id) {
+            Some((request, _)) => !std::mem::replace(&mut request.sent, true),
+            None => false,
+        }
+    }
 
-        let mut messages = VecDeque::new();
-        for (request, _) in ordered_requests {
-            messages.push_back(request.message.clone());
+    /// Stop tracking `request_id` if it has not been sent yet. Its result
+    /// receiver is notified by being dropped.
+    ///
+    /// Returns whether the request was dropped.
+    pub fn cancel(&mut self, request_id: &RequestId) -> bool {
+        match self.ongoing_requests.get(request_id) {
+            Some((request, _)) if !request.sent => {
+                self.ongoing_requests.remove(request_id);
+                true
+            }
+            _ => false,
         }
-        messages
+    }
+
+    /// Number of tracked requests of `request_type` that the server has not
+    /// fully completed yet.
+    pub fn num_ongoing(&self, request_type: RequestType) -> usize {
+        self.ongoing_requests
+            .values()
+            .filter(|(request, _)| request.typ == request_type)
+            .count()
+    }
+
+    pub fn restart(&self) -> VecDeque<ClientMessage> {
+        // Resend in the order the requests were made, which is request id
+        // order, so that replayed mutations apply in the order the user made
+        // them.
+        self.ongoing_requests
+            .values()
+            .map(|(request, _)| request.message.clone())
+            .collect()
     }
 }
 
@@ -0,0 +1,229 @@
+use convex_sync_types::{
+    ClientMessage, ErrorPayload, LogLinesMessage, SessionRequestSeqNumber, StateModification,
+    StateVersion, Timestamp, UdfPath,
+};
+use serde_json::{Value, json};
+
+use super::optimistic_update::OptimisticUpdate;
+use super::query_result::FunctionResult;
+use super::{BaseConvexClient, SubscriberId};
+use crate::websocket::ServerMessage;
+
+fn counter() -> UdfPath {
+    "counter:get".parse().unwrap()
+}
+
+fn ts(ts: u64) -> Timestamp {
+    Timestamp::try_from(ts).unwrap()
+}
+
+/// A client subscribed to the counter, with its subscription sent.
+fn subscribed_client() -> (BaseConvexClient, SubscriberId) {
+    let mut client = BaseConvexClient::new();
+    let subscriber = client.subscribe(counter(), json!({}));
+    while client.pop_next_message().is_some() {}
+    (client, subscriber)
+}
+
+/// Send the counter's value as of `at`, continuing from the client's
+/// current server version.
+fn transition(client: &mut BaseConvexClient, at: u64, value: i64) {
+    let start_version = client.remote_query_set.version;
+    let end_version = StateVersion {
+        query_set: client.state.query_set_version,
+        identity: start_version.identity,
+        ts: ts(at),
+    };
+    let query_id = *client.state.query_id_to_token.keys().next().unwrap();
+    client
+        .receive_message(ServerMessage::Transition {
+            start_version,
+            end_version,
+            modifications: vec![StateModification::QueryUpdated {
+                query_id,
+                value: json!(value),
+                log_lines: LogLinesMessage(vec![]),
+                journal: None,
+            }],
+        })
+        .unwrap();
+}
+
+/// Send the queued messages, returning the request id of the mutation
+/// among them.
+fn send_mutation(client: &mut BaseConvexClient) -> SessionRequestSeqNumber {
+    let mut sent = None;
+    while let Some(message) = client.pop_next_message() {
+        if let ClientMessage::Mutation { request_id, .. } = message {
+            sent = Some(request_id);
+        }
+    }
+    sent.expect("a mutation was queued")
+}
+
+fn mutation_response(
+    request_id: SessionRequestSeqNumber,
+    result: Result<Value, ErrorPayload<Value>>,
+    at: Option<u64>,
+) -> ServerMessage {
+    ServerMessage::MutationResponse {
+        request_id,
+        result,
+        ts: at.map(ts),
+        log_lines: LogLinesMessage(vec![]),
+    }
+}
+
+/// Bump the counter by one, if it has a value.
+fn increment() -> OptimisticUpdate {
+    Box::new(|store| {
+        if let Some(Value::Number(count)) = store.get_query(counter(), &json!({})) {
+            let count = count.as_i64().unwrap();
+            store.set_query(counter(), &json!({}), json!(count + 1));
+        }
+    })
+}
+
+fn count(client: &BaseConvexClient, subscriber: &SubscriberId) -> Option<FunctionResult> {
+    client.latest_results().get(subscriber).cloned()
+}
+
+#[test]
+fn optimistic_update_applies_right_away() {
+    let (mut client, subscriber) = subscribed_client();
+    transition(&mut client, 1, 1);
+
+    let _ = client.mutation(
+        "counter:increment".parse().unwrap(),
+        json!({}),
+        Some(increment()),
+        None,
+    );
+
+    assert_eq!(
+        count(&client, &subscriber),
+        Some(FunctionResult::Value(json!(2)))
+    );
+}
+
+#[test]
+fn optimistic_update_is_replayed_over_new_transitions() {
+    let (mut client, subscriber) = subscribed_client();
+    transition(&mut client, 1, 1);
+    let _ = client.mutation(
+        "counter:increment".parse().unwrap(),
+        json!({}),
+        Some(increment()),
+        None,
+    );
+    while client.pop_next_message().is_some() {}
+
+    // Someone else bumped the counter before the mutation ran.
+    transition(&mut client, 2, 5);
+
+    assert_eq!(
+        count(&client, &subscriber),
+        Some(FunctionResult::Value(json!(6)))
+    );
+}
+
+#[test]
+fn optimistic_update_is_dropped_once_its_mutation_is_observed() {
+    let (mut client, subscriber) = subscribed_client();
+    transition(&mut client, 1, 1);
+    let (_, mut result) = client.mutation(
+        "counter:increment".parse().unwrap(),
+        json!({}),
+        Some(increment()),
+        None,
+    );
+    let request_id = send_mutation(&mut client);
+
+    client
+        .receive_message(mutation_response(request_id, Ok(Value::Null), Some(3)))
+        .unwrap();
+    // The server ran the mutation, but the client hasn't seen its effect yet.
+    assert_eq!(
+        count(&client, &subscriber),
+        Some(FunctionResult::Value(json!(2)))
+    );
+    assert_eq!(result.try_recv().unwrap(), None);
+
+    transition(&mut client, 3, 2);
+
+    assert_eq!(
+        count(&client, &subscriber),
+        Some(FunctionResult::Value(json!(2)))
+    );
+    assert_eq!(
+        result.try_recv().unwrap(),
+        Some(FunctionResult::Value(Value::Null))
+    );
+    // Later transitions don't bring the update back.
+    transition(&mut client, 4, 7);
+    assert_eq!(
+        count(&client, &subscriber),
+        Some(FunctionResult::Value(json!(7)))
+    );
+}
+
+#[test]
+fn failed_mutation_rolls_back_its_optimistic_update() {
+    let (mut client, subscriber) = subscribed_client();
+    transition(&mut client, 1, 1);
+    let (_, mut result) = client.mutation(
+        "counter:increment".parse().unwrap(),
+        json!({}),
+        Some(increment()),
+        None,
+    );
+    let request_id = send_mutation(&mut client);
+
+    let results = client
+        .receive_message(mutation_response(
+            request_id,
+            Err(ErrorPayload::Message("too big".into())),
+            None,
+        ))
+        .unwrap()
+        .expect("the rollback changes the results");
+
+    assert_eq!(
+        results.get(&subscriber),
+        Some(&FunctionResult::Value(json!(1)))
+    );
+    assert_eq!(
+        result.try_recv().unwrap(),
+        Some(FunctionResult::ErrorMessage("too big".into()))
+    );
+}
+
+#[test]
+fn cancelled_mutation_rolls_back_its_optimistic_update() {
+    let (mut client, subscriber) = subscribed_client();
+    transition(&mut client, 1, 1);
+    let (request_id, _result) = client.mutation(
+        "counter:increment".parse().unwrap(),
+        json!({}),
+        Some(increment()),
+        None,
+    );
+
+    let results = client
+        .cancel(request_id)
+        .unwrap()
+        .expect("the rollback changes the results");
+
+    assert_eq!(
+        results.get(&subscriber),
+        Some(&FunctionResult::Value(json!(1)))
+    );
+    // The mutation never goes out.
+    assert_eq!(client.pop_next_message(), None);
+    // Nor does its update come back on the next transition.
+    transition(&mut client, 2, 1);
+    assert_eq!(
+        count(&client, &subscriber),
+        Some(FunctionResult::Value(json!(1)))
+    );
+}
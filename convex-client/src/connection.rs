@@ -0,0 +1,133 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::channel::mpsc;
+use futures::stream::Fuse;
+use web_time::Instant;
+
+use crate::AuthError;
+use crate::base::BaseConvexClient;
+use crate::websocket::WebSocketState;
+
+/// The callback given to a client builder's `with_on_auth_error`.
+pub(crate) type OnAuthError = Arc<dyn Fn(AuthError) + Send + Sync>;
+
+/// Whether the client currently has a live connection to the deployment.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Connected,
+    #[default]
+    Connecting,
+    /// The connection failed and the client is backing off before retrying.
+    Disconnected,
+    /// The deployment reported a fatal error. The client stopped and won't
+    /// reconnect.
+    Failed,
+}
+
+/// A snapshot of the client's connection to the deployment, as returned by
+/// [`use_connection_state`](crate::leptos::use_connection_state).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConnectionState {
+    pub status: ConnectionStatus,
+    /// Whether the client connected at least once. Useful to tell the initial
+    /// connection apart from a reconnect.
+    pub has_ever_connected: bool,
+    /// Mutations sent to the server whose results are not reflected in the
+    /// query results yet.
+    pub inflight_mutations: usize,
+    pub last_server_message: Option<Instant>,
+    /// How long the client waits before reconnecting, while disconnected.
+    pub reconnect_backoff: Option<Duration>,
+    /// The message of the fatal error that stopped the client, once
+    /// [`Failed`](ConnectionStatus::Failed).
+    pub fatal_error: Option<String>,
+}
+
+impl ConnectionState {
+    pub fn time_since_last_server_message(&self) -> Option<Duration> {
+        self.last_server_message.map(|instant| instant.elapsed())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.status == ConnectionStatus::Connected
+    }
+}
+
+/// Keeps the [`ConnectionState`] of a client up to date from the websocket
+/// state changes and the worker's activity, broadcasting it on every change.
+pub struct ConnectionTracker {
+    state: ConnectionState,
+    pub(crate) state_change_receiver: Fuse<mpsc::Receiver<WebSocketState>>,
+    on_state_change: Option<mpsc::Sender<WebSocketState>>,
+    on_auth_error: Option<OnAuthError>,
+    sender: async_broadcast::Sender<ConnectionState>,
+}
+
+impl ConnectionTracker {
+    /// `on_state_change` and `on_auth_error` are the optional listeners
+    /// given to the client builder;
+    /// every websocket state change is forwarded to the former.
+    pub fn new(
+        state_change_receiver: mpsc::Receiver<WebSocketState>,
+        on_state_change: Option<mpsc::Sender<WebSocketState>>,
+        on_auth_error: Option<OnAuthError>,
+        sender: async_broadcast::Sender<ConnectionState>,
+    ) -> Self {
+        let _ = sender.try_broadcast(ConnectionState::default());
+        Self {
+            state: ConnectionState::default(),
+            state_change_receiver: state_change_receiver.fuse(),
+            on_state_change,
+            on_auth_error,
+            sender,
+        }
+    }
+
+    pub fn on_websocket_state(&mut self, websocket_state: WebSocketState) {
+        if let Some(on_state_change) = self.on_state_change.as_mut() {
+            let _ = on_state_change.try_send(websocket_state);
+        }
+        match websocket_state {
+            WebSocketState::Connected => {
+                self.state.status = ConnectionStatus::Connected;
+                self.state.has_ever_connected = true;
+                self.state.reconnect_backoff = None;
+            }
+            WebSocketState::Connecting => {
+                self.state.status = ConnectionStatus::Connecting;
+                self.state.reconnect_backoff = None;
+            }
+            WebSocketState::Disconnected { retry_in } => {
+                self.state.status = ConnectionStatus::Disconnected;
+                self.state.reconnect_backoff = Some(retry_in);
+            }
+        }
+    }
+
+    pub fn on_server_message(&mut self) {
+        self.state.last_server_message = Some(Instant::now());
+    }
+
+    pub fn on_auth_error(&self, error: AuthError) {
+        if let Some(on_auth_error) = &self.on_auth_error {
+            on_auth_error(error);
+        }
+    }
+
+    pub fn on_fatal_error(&mut self, message: String) {
+        self.state.status = ConnectionStatus::Failed;
+        self.state.reconnect_backoff = None;
+        self.state.fatal_error = Some(message);
+    }
+
+    /// Broadcast the current state if it changed since the last call.
+    pub fn publish(&mut self, base_client: &BaseConvexClient) {
+        let previous = self.state.clone();
+        self.state.inflight_mutations = base_client.num_inflight_mutations();
+        if previous != self.state {
+            let _ = self.sender.try_broadcast(self.state.clone());
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use leptos::prelude::*;
+
+use crate::connection::ConnectionState;
+
+#[derive(Clone, Copy)]
+struct ConnectionStateContext(Signal<ConnectionState>);
+
+/// Provide the connection state of `client` to [`use_connection_state`].
+#[cfg(feature = "hydrate")]
+pub(super) fn provide_connection_state(client: &super::ConvexClient) {
+    use futures::StreamExt;
+    use leptos::task::spawn_local_scoped_with_cancellation;
+
+    let state = RwSignal::new(ConnectionState::default());
+    let mut connection_state = client.watch_connection_state();
+    spawn_local_scoped_with_cancellation(async move {
+        while let Some(new_state) = connection_state.next().await {
+            state.set(new_state);
+        }
+    });
+    provide_context(ConnectionStateContext(state.into()));
+}
+
+/// The state of the connection of the [`ConvexClient`](super::ConvexClient)
+/// provided by the closest [`ConvexProvider`](super::ConvexProvider).
+///
+/// Outside of a provider, and while server rendering, this is always the
+/// default [`ConnectionStatus::Connecting`](super::ConnectionStatus::Connecting)
+/// state.
+pub fn use_connection_state() -> Signal<ConnectionState> {
+    use_context::<ConnectionStateContext>()
+        .map(|context| context.0)
+        .unwrap_or_else(|| Signal::stored(ConnectionState::default()))
+}
@@ -0,0 +1,70 @@
+use std::collections::VecDeque;
+
+use leptos::prelude::*;
+
+use crate::logs::{ConsoleLogSink, LogLine, LogSink};
+
+/// Keeps the latest log lines of a client for a [`ConvexLogPanel`], and
+/// prints them to the console.
+///
+/// The providers use one in debug builds.
+#[derive(Clone)]
+pub struct LogPanelSink {
+    lines: ArcRwSignal<VecDeque<LogLine>>,
+    capacity: usize,
+}
+
+impl LogPanelSink {
+    /// Keep the last `capacity` lines.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: ArcRwSignal::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+}
+
+impl LogSink for LogPanelSink {
+    fn log(&self, line: LogLine) {
+        ConsoleLogSink.log(line.clone());
+        self.lines.update(|lines| {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        });
+    }
+}
+
+/// The lines logged by the functions the provided client ran, oldest first.
+///
+/// Shows the lines of the [`LogPanelSink`] provided as context, which the
+/// providers only do in debug builds. Renders nothing otherwise.
+#[component]
+pub fn ConvexLogPanel() -> impl IntoView {
+    use_context::<LogPanelSink>().map(|sink| {
+        let lines = sink.lines;
+        let count = {
+            let lines = lines.clone();
+            move || lines.with(|lines| lines.len())
+        };
+        view! {
+            <details class="convex-log-panel">
+                <summary>"Convex logs (" {count} ")"</summary>
+                <ol>
+                    {move || {
+                        lines
+                            .get()
+                            .into_iter()
+                            .map(|line| {
+                                view! {
+                                    <li data-level=line.level.to_string()>{line.to_string()}</li>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </ol>
+            </details>
+        }
+    })
+}
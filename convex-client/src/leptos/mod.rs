@@ -1,38 +1,72 @@
 #![allow(clippy::new_ret_no_self)]
-mod json;
-mod subscription;
-mod worker;
+mod connection;
+mod log_panel;
+#[cfg(feature = "hydrate")]
+mod mutation_store;
+mod pagination;
+mod queries;
+mod snapshot;
 
-use crate::leptos::worker::worker;
+use anyhow::Context;
 use async_trait::async_trait;
 use leptos::logging::{error, warn};
 use leptos::prelude::*;
 use leptos::task::spawn_local_scoped_with_cancellation;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use convex_sync_types::{AuthenticationToken, UdfPath, UserIdentityAttributes};
 use futures::channel::{mpsc, oneshot};
-use futures::{SinkExt, StreamExt};
+use futures::{FutureExt, SinkExt, StreamExt};
+use send_wrapper::SendWrapper;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use url::Url;
-use wasm_bindgen_futures::spawn_local;
 
-use crate::base::BaseConvexClient;
-use crate::base::query_result::{FunctionResult, QueryResults};
-use crate::websocket::{ProtocolResponse, SyncProtocol, WebSocketState};
+pub use self::connection::use_connection_state;
+pub use self::log_panel::{ConvexLogPanel, LogPanelSink};
+#[cfg(feature = "hydrate")]
+pub use self::mutation_store::BrowserMutationStore;
+#[cfg(feature = "ssr")]
+pub(crate) use self::pagination::drive_paginated_query;
+pub use self::pagination::{
+    PageStatus, PaginatedQuery, PaginatedResults, PaginationOptions, PaginationResult,
+    PaginationStatus, UsePaginatedQuery,
+};
+pub use self::queries::{Queries, use_queries};
+pub use crate::base::mutation_store::{InMemoryMutationStore, MutationStore, PersistedMutation};
+pub use crate::base::optimistic_update::{OptimisticLocalStore, OptimisticUpdate};
+pub use crate::connection::{ConnectionState, ConnectionStatus};
+
+use crate::ConvexClientError;
+use crate::auth::TokenFetcher;
+pub use crate::base::query_result::FunctionResult;
+use crate::base::query_result::QueryResults;
+use crate::base::{BaseConvexClient, QueryKeepAlive};
+use crate::logs::LogSink;
+#[cfg(feature = "hydrate")]
+use crate::logs::default_log_sink;
+use crate::observer::ClientObserver;
+use crate::recording::ProtocolRecorder;
+pub use crate::websocket::WebSocketState;
 use serde_json::Value;
 
-use self::json::convex_json;
-use self::subscription::{QuerySetSubscription, QuerySubscription};
-use self::worker::{
+use self::snapshot::{query_snapshot, snapshot_for};
+use crate::runtime::BrowserRuntime;
+use crate::subscription::{ClientHandle, PendingRequest, QuerySetSubscription, QuerySubscription};
+use crate::value::{from_json, to_json};
+use crate::worker::{
     ActionRequest, AuthenticateRequest, ClientRequest, MutationRequest, SubscribeRequest,
+    WORKER_STOPPED,
 };
 
 pub struct ConvexClient {
-    shutdown_listen: Arc<oneshot::Sender<()>>,
+    shutdown_listen: ClientHandle,
     request_sender: mpsc::UnboundedSender<ClientRequest>,
     watch_receiver: async_broadcast::Receiver<QueryResults>,
+    connection_state_receiver: async_broadcast::Receiver<ConnectionState>,
+    request_timeout: Option<Duration>,
 }
 
 impl Clone for ConvexClient {
@@ -41,6 +75,8 @@ impl Clone for ConvexClient {
             shutdown_listen: self.shutdown_listen.clone(),
             request_sender: self.request_sender.clone(),
             watch_receiver: self.watch_receiver.new_receiver(),
+            connection_state_receiver: self.connection_state_receiver.clone(),
+            request_timeout: self.request_timeout,
         }
     }
 }
@@ -62,7 +98,12 @@ impl ConvexClient {
 
     #[doc(hidden)]
     pub fn new_from_builder(builder: ConvexClientBuilder) -> anyhow::Result<Self> {
+        use crate::connection::ConnectionTracker;
+        use crate::recording::RecordingProtocol;
+        use crate::runtime::Runtime;
+        use crate::websocket::ProtocolResponse;
         use crate::websocket::client::WebSocketManager;
+        use crate::worker::worker;
         let client_id = builder
             .client_id
             .unwrap_or_else(|| format!("rust-{}", "unknown"));
@@ -74,27 +115,55 @@ impl ConvexClient {
         let (mut watch_sender, watch_receiver) = async_broadcast::broadcast(1);
         watch_sender.set_overflow(true);
 
+        let (mut connection_state_sender, connection_state_receiver) =
+            async_broadcast::broadcast(1);
+        connection_state_sender.set_overflow(true);
+        let (state_change_sender, state_change_receiver) = mpsc::channel(8);
+        let connection = ConnectionTracker::new(
+            state_change_receiver,
+            builder.on_state_change,
+            builder.on_auth_error,
+            connection_state_sender,
+        );
+
         let (shutdown_sender, shutdown_listen) = oneshot::channel();
 
-        let base_client = BaseConvexClient::new();
+        let mut base_client = BaseConvexClient::new();
+        base_client.set_query_keep_alive(builder.query_keep_alive);
+        if let Some(observer) = builder.observer {
+            base_client.set_observer(observer);
+        }
+        base_client.set_log_sink(builder.log_sink);
+        if let Some(mutation_store) = builder.mutation_store {
+            base_client.set_mutation_store(mutation_store);
+        }
 
-        spawn_local(async move {
+        let on_response = match &builder.recorder {
+            Some(recorder) => recorder.tap::<BrowserRuntime>(response_sender),
+            None => response_sender,
+        };
+        let recorder = builder.recorder;
+        let inactivity_timeout = builder.inactivity_timeout;
+        BrowserRuntime::spawn(async move {
             let protocol = WebSocketManager::open(
                 ws_url,
-                response_sender,
-                builder.on_state_change,
+                on_response,
+                Some(state_change_sender),
                 client_id.as_str(),
+                inactivity_timeout,
             )
             .await
             .expect("should open the websocket");
+            let protocol = RecordingProtocol::new(protocol, recorder);
 
-            worker(
+            worker::<BrowserRuntime, _>(
                 response_receiver,
                 request_receiver,
                 watch_sender,
                 base_client,
                 shutdown_listen,
                 protocol,
+                connection,
             )
             .await;
         });
@@ -102,6 +171,8 @@ impl ConvexClient {
             shutdown_listen: Arc::new(shutdown_sender),
             request_sender,
             watch_receiver,
+            connection_state_receiver,
+            request_timeout: builder.request_timeout,
         };
         Ok(client)
     }
@@ -112,11 +183,27 @@ impl ConvexClient {
         &mut self,
         name: &str,
         args: Value,
+    ) -> anyhow::Result<QuerySubscription> {
+        self.subscribe_with_seed(name, args, None).await
+    }
+
+    /// Like [`subscribe`](Self::subscribe), starting from `seed`, a result
+    /// rendered on the server, until the deployment sends one. The seed is
+    /// ignored if the client already has a result for the query.
+    pub async fn subscribe_with_seed(
+        &mut self,
+        name: &str,
+        args: Value,
+        seed: Option<FunctionResult>,
     ) -> anyhow::Result<QuerySubscription> {
         let (tx, rx) = oneshot::channel();
 
         let udf_path = name.parse()?;
-        let request = SubscribeRequest { udf_path, args };
+        let request = SubscribeRequest {
+            udf_path,
+            args,
+            seed,
+        };
 
         self.request_sender
             .send(ClientRequest::Subscribe(
@@ -126,17 +213,17 @@ impl ConvexClient {
             ))
             .await?;
 
-        let res = rx.await?;
-        Ok(res)
+        let mut subscription = rx.await?;
+        subscription.client = Some(self.shutdown_listen.clone());
+        Ok(subscription)
     }
 
     pub async fn query(&mut self, name: &str, args: Value) -> anyhow::Result<FunctionResult> {
-        Ok(self
-            .subscribe(name, args)
+        self.subscribe(name, args)
             .await?
             .next()
             .await
-            .expect("INTERNAL BUG: Convex Client dropped prematurely."))
+            .context(WORKER_STOPPED)
     }
 
     /// Perform a mutation `name` with `args` and return a future
@@ -156,17 +243,85 @@ impl ConvexClient {
     /// # Ok(())
     /// # }
     pub async fn mutation(&mut self, name: &str, args: Value) -> anyhow::Result<FunctionResult> {
-        let (tx, rx) = oneshot::channel();
+        self.mutation_with_optimistic_update(name, args, None).await
+    }
 
-        let udf_path: UdfPath = name.parse()?;
-        let request = MutationRequest { udf_path, args };
+    /// Perform a mutation `name` with `args`, applying `optimistic_update` to
+    /// the local query results until the server has observed the mutation.
+    ///
+    /// Subscribers see the patched results right away. Once the mutation is
+    /// reflected in a server transition, or fails, the patch is dropped and
+    /// the server's results take over.
+    pub async fn mutation_with_optimistic_update(
+        &mut self,
+        name: &str,
+        args: Value,
+        optimistic_update: Option<OptimisticUpdate>,
+    ) -> anyhow::Result<FunctionResult> {
+        self.mutation_with_timeout(name, args, optimistic_update, None)
+            .await
+    }
+
+    /// Like [`mutation_with_optimistic_update`](Self::mutation_with_optimistic_update),
+    /// failing with a [`TimeoutError`](crate::TimeoutError) if the mutation
+    /// doesn't complete within `timeout`, or within the client's
+    /// [request timeout](ConvexClientBuilder::with_request_timeout) if `None`.
+    ///
+    /// Dropping the returned future before the mutation is sent to the
+    /// server cancels it and rolls back its optimistic update.
+    pub async fn mutation_with_timeout(
+        &mut self,
+        name: &str,
+        args: Value,
+        optimistic_update: Option<OptimisticUpdate>,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<FunctionResult> {
+        let request = MutationRequest {
+            udf_path: name.parse()?,
+            args,
+            optimistic_update,
+            idempotency_key: None,
+        };
+        self.send_mutation(request, timeout).await
+    }
+
+    /// Perform `mutation` with its optimistic update, timeout and
+    /// idempotency key. This is what [`Mutation::run`] does before decoding
+    /// the result.
+    pub async fn run_mutation<M: Mutation + ?Sized>(
+        &mut self,
+        mutation: &M,
+    ) -> anyhow::Result<FunctionResult> {
+        let request = MutationRequest {
+            udf_path: mutation.name().parse()?,
+            args: mutation.args()?,
+            optimistic_update: mutation.optimistic_update(),
+            idempotency_key: mutation.idempotency_key(),
+        };
+        self.send_mutation(request, mutation.timeout()).await
+    }
+
+    async fn send_mutation(
+        &mut self,
+        request: MutationRequest,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<FunctionResult> {
+        let (tx, rx) = oneshot::channel();
+        let name = request.udf_path.to_string();
 
         self.request_sender
             .send(ClientRequest::Mutation(request, tx))
             .await?;
 
-        let res = rx.await?;
-        Ok(res.await?)
+        let (request_id, result) = rx.await?;
+        PendingRequest {
+            request_id,
+            result,
+            request_sender: self.request_sender.clone(),
+            completed: false,
+        }
+        .wait::<BrowserRuntime>(&name, timeout.or(self.request_timeout))
+        .await
     }
 
     /// Perform an action `name` with `args` and return a future
@@ -186,6 +341,19 @@ impl ConvexClient {
     /// # Ok(())
     /// # }
     pub async fn action(&mut self, name: &str, args: Value) -> anyhow::Result<FunctionResult> {
+        self.action_with_timeout(name, args, None).await
+    }
+
+    /// Like [`action`](Self::action), failing with a
+    /// [`TimeoutError`](crate::TimeoutError) if the action doesn't complete
+    /// within `timeout`, or within the client's
+    /// [request timeout](ConvexClientBuilder::with_request_timeout) if `None`.
+    pub async fn action_with_timeout(
+        &mut self,
+        name: &str,
+        args: Value,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<FunctionResult> {
         let (tx, rx) = oneshot::channel();
 
         let udf_path: UdfPath = name.parse()?;
@@ -195,8 +363,15 @@ impl ConvexClient {
             .send(ClientRequest::Action(request, tx))
             .await?;
 
-        let res = rx.await?;
-        Ok(res.await?)
+        let (request_id, result) = rx.await?;
+        PendingRequest {
+            request_id,
+            result,
+            request_sender: self.request_sender.clone(),
+            completed: false,
+        }
+        .wait::<BrowserRuntime>(name, timeout.or(self.request_timeout))
+        .await
     }
 
     /// Get a consistent view of the results of multiple queries (query set).
@@ -230,7 +405,18 @@ impl ConvexClient {
     /// # Ok(())
     /// # }
     pub fn watch_all(&self) -> QuerySetSubscription {
-        QuerySetSubscription::new(self.watch_receiver.new_receiver())
+        QuerySetSubscription::new(
+            self.watch_receiver.new_receiver(),
+            self.shutdown_listen.clone(),
+        )
+    }
+
+    /// Get a stream of the [`ConnectionState`] of this client, starting with
+    /// the current one.
+    ///
+    /// Within a [`ConvexProvider`], prefer [`use_connection_state`].
+    pub fn watch_connection_state(&self) -> async_broadcast::Receiver<ConnectionState> {
+        self.connection_state_receiver.clone()
     }
 
     /// Set auth for use when calling Convex functions.
@@ -245,10 +431,34 @@ impl ConvexClient {
                 Some(token) => AuthenticationToken::User(token),
             },
         };
-        self.request_sender
-            .send(ClientRequest::Authenticate(Box::new(req)))
-            .await
-            .expect("INTERNAL BUG: Worker has gone away");
+        self.send_auth_request(ClientRequest::Authenticate(Box::new(req)))
+            .await;
+    }
+
+    /// Authenticate with the tokens `fetch_token` returns, refreshing them
+    /// before they expire.
+    ///
+    /// The client fetches a token right away, then a fresh one shortly
+    /// before each expires, going by the JWT's `exp` claim. When the
+    /// deployment rejects a token, it fetches one more before giving up and
+    /// calling the [`with_on_auth_error`](ConvexClientBuilder::with_on_auth_error)
+    /// callback. The fetches for fresh tokens pass `force_refresh`, asking
+    /// the auth provider not to return a cached one. Returning `None` signs
+    /// out.
+    ///
+    /// Calling [`set_auth`](Self::set_auth) stops the fetching.
+    ///
+    /// The futures `fetch_token` returns are only polled on the page's
+    /// thread, so they needn't be `Send`.
+    pub async fn set_auth_fetcher<F, Fut>(&mut self, fetch_token: F)
+    where
+        F: Fn(bool) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + 'static,
+    {
+        let fetch_token: TokenFetcher =
+            Arc::new(move |force_refresh| SendWrapper::new(fetch_token(force_refresh)).boxed());
+        self.send_auth_request(ClientRequest::FetchAuth(fetch_token))
+            .await;
     }
 
     /// Set admin auth for use when calling Convex functions as a deployment
@@ -266,10 +476,14 @@ impl ConvexClient {
         let req = AuthenticateRequest {
             token: AuthenticationToken::Admin(deploy_key, acting_as),
         };
-        self.request_sender
-            .send(ClientRequest::Authenticate(Box::new(req)))
-            .await
-            .expect("INTERNAL BUG: Worker has gone away");
+        self.send_auth_request(ClientRequest::Authenticate(Box::new(req)))
+            .await;
+    }
+
+    async fn send_auth_request(&mut self, request: ClientRequest) {
+        // Fails only once a fatal error stopped the worker, when there is
+        // nothing left to authenticate.
+        let _ = self.request_sender.send(request).await;
     }
 }
 
@@ -291,6 +505,14 @@ pub struct ConvexClientBuilder {
     deployment_url: String,
     client_id: Option<String>,
     on_state_change: Option<mpsc::Sender<WebSocketState>>,
+    request_timeout: Option<Duration>,
+    mutation_store: Option<Box<dyn MutationStore>>,
+    query_keep_alive: QueryKeepAlive,
+    recorder: Option<ProtocolRecorder>,
+    observer: Option<Arc<dyn ClientObserver>>,
+    log_sink: Arc<dyn LogSink>,
+    on_auth_error: Option<crate::connection::OnAuthError>,
+    inactivity_timeout: Duration,
 }
 
 #[cfg(feature = "hydrate")]
@@ -300,6 +522,14 @@ impl ConvexClientBuilder {
             deployment_url: deployment_url.to_string(),
             client_id: None,
             on_state_change: None,
+            request_timeout: None,
+            mutation_store: None,
+            query_keep_alive: QueryKeepAlive::default(),
+            recorder: None,
+            observer: None,
+            log_sink: default_log_sink(),
+            on_auth_error: None,
+            inactivity_timeout: crate::websocket::DEFAULT_INACTIVITY_TIMEOUT,
         }
     }
 
@@ -312,33 +542,148 @@ impl ConvexClientBuilder {
         self.on_state_change = Some(on_state_change);
         self
     }
+
+    /// Fail mutations and actions that get no response within `timeout`
+    /// with a [`TimeoutError`](crate::TimeoutError). By default they wait
+    /// indefinitely.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Keep unacknowledged mutations in `store`, such as a
+    /// [`BrowserMutationStore`], so they survive page reloads. See
+    /// [`Mutation::idempotency_key`].
+    pub fn with_mutation_store(mut self, store: impl MutationStore + 'static) -> Self {
+        self.mutation_store = Some(Box::new(store));
+        self
+    }
+
+    /// Keep queries subscribed for `duration` after their last subscriber
+    /// drops, so that subscribing to them again returns the cached result
+    /// right away. At most `max_idle_queries` are kept; past that, the least
+    /// recently used are dropped early. Off by default.
+    pub fn with_query_keep_alive(mut self, duration: Duration, max_idle_queries: usize) -> Self {
+        self.query_keep_alive = QueryKeepAlive {
+            duration,
+            max_idle_queries,
+        };
+        self
+    }
+
+    /// Record every message the client sends and receives to `recorder`, to
+    /// replay the session with `server::ConvexClientBuilder::replay`.
+    pub fn with_protocol_recorder(mut self, recorder: ProtocolRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Report the client's queries, mutations and reconnects to `observer`.
+    pub fn with_observer(mut self, observer: impl ClientObserver + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Send the lines the functions log to `log_sink`, such as a
+    /// [`LogPanelSink`]. Debug builds print them to the console and release
+    /// builds drop them by default.
+    pub fn with_log_sink(mut self, log_sink: impl LogSink + 'static) -> Self {
+        self.log_sink = Arc::new(log_sink);
+        self
+    }
+
+    /// Call `on_auth_error` when the deployment rejects the auth token. The
+    /// client drops the token and reconnects unauthenticated; call
+    /// [`ConvexClient::set_auth`] with a fresh one to recover. Clients
+    /// authenticated with [`ConvexClient::set_auth_fetcher`] call it only
+    /// once a freshly fetched token is rejected too.
+    pub fn with_on_auth_error(
+        mut self,
+        on_auth_error: impl Fn(crate::AuthError) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_auth_error = Some(Arc::new(on_auth_error));
+        self
+    }
+
+    /// Reconnect when the deployment sends nothing, not even a ping, for
+    /// `timeout`. Defaults to 30 seconds.
+    pub fn with_inactivity_timeout(mut self, timeout: Duration) -> Self {
+        self.inactivity_timeout = timeout;
+        self
+    }
+
     pub async fn build(self) -> anyhow::Result<ConvexClient> {
         ConvexClient::new_from_builder(self)
     }
 }
 
+/// The `sessionStorage` key the providers persist unacknowledged mutations
+/// under.
+#[cfg(feature = "hydrate")]
+const MUTATION_STORE_KEY: &str = "convex-pending-mutations";
+
+/// How long the providers keep queries live after the last component using
+/// them unmounts, so that navigating back shows them without a loading state.
+#[cfg(feature = "hydrate")]
+const QUERY_KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+#[cfg(feature = "hydrate")]
+const MAX_IDLE_QUERIES: usize = 64;
+
+/// How many log lines the providers keep for the [`ConvexLogPanel`].
+#[cfg(feature = "hydrate")]
+const LOG_PANEL_LINES: usize = 200;
+
+/// Create the providers' client and provide it, its connection state and,
+/// in debug builds, its logs as context.
+#[cfg(feature = "hydrate")]
+fn provide_client() -> ConvexClient {
+    let mut builder = ConvexClientBuilder::new("https://quick-cardinal-805.convex.cloud")
+        .with_mutation_store(BrowserMutationStore::new(MUTATION_STORE_KEY))
+        .with_query_keep_alive(QUERY_KEEP_ALIVE, MAX_IDLE_QUERIES);
+    if cfg!(debug_assertions) {
+        let log_panel = LogPanelSink::new(LOG_PANEL_LINES);
+        provide_context(log_panel.clone());
+        builder = builder.with_log_sink(log_panel);
+    }
+    let client = ConvexClient::new_from_builder(builder).expect("should provide the convex client");
+    self::connection::provide_connection_state(&client);
+    provide_context(client.clone());
+    client
+}
+
+/// Provides a [`ConvexClient`] authenticated with the tokens
+/// `fetch_access_token` returns while `is_authenticated`, refreshed before
+/// they expire. See [`ConvexClient::set_auth_fetcher`].
 #[component]
-pub fn ConvexProviderWithAuth(
+pub fn ConvexProviderWithAuth<F, Fut>(
     children: Children,
-    access_token: impl Fn() -> Option<String> + Send + Sync + 'static,
-) -> impl IntoView {
+    #[prop(into)] is_authenticated: Signal<bool>,
+    fetch_access_token: F,
+) -> impl IntoView
+where
+    F: Fn(bool) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Option<String>> + 'static,
+{
+    #[cfg(not(feature = "hydrate"))]
+    let _ = (is_authenticated, fetch_access_token);
     #[cfg(feature = "hydrate")]
     {
         use leptos::task::spawn_local;
 
-        let access_token = Memo::new(move |_| access_token());
-
-        let client = ConvexClient::new("https://quick-cardinal-805.convex.cloud")
-            .expect("should provide the convex client");
-        let client_clone = client.clone();
+        let client_clone = provide_client();
         Effect::new(move |_| {
             let mut client = client_clone.clone();
-            let access_token = access_token.get();
+            let is_authenticated = is_authenticated.get();
+            let fetch_access_token = fetch_access_token.clone();
             spawn_local(async move {
-                client.set_auth(access_token).await;
+                if is_authenticated {
+                    client.set_auth_fetcher(fetch_access_token).await;
+                } else {
+                    client.set_auth(None).await;
+                }
             });
         });
-        provide_context(client);
     }
 
     view! {
@@ -349,11 +694,7 @@ pub fn ConvexProviderWithAuth(
 #[component]
 pub fn ConvexProvider(children: Children) -> impl IntoView {
     #[cfg(feature = "hydrate")]
-    {
-        let client = ConvexClient::new("https://quick-cardinal-805.convex.cloud")
-            .expect("should provide the convex client");
-        provide_context(client);
-    }
+    provide_client();
     view! {
         {children()}
     }
@@ -365,23 +706,33 @@ pub trait Query<F: DeserializeOwned + Send + Sync + 'static> {
     where
         Self: serde::Serialize,
     {
-        let mut value = serde_json::to_value(self)?;
-        convex_json(&mut value);
-        Ok(value)
+        Ok(to_json(self)?)
     }
 }
 
 pub struct UseQuery;
 
 impl UseQuery {
+    /// Subscribe to the query returned by `query`, resubscribing whenever it
+    /// changes. `None` means no query, and reads as `None`.
+    ///
+    /// While server rendering, the query is run once on the server
+    /// [`ConvexClient`](crate::server::ConvexClient) provided as context and
+    /// its result is sent along with the HTML. The hydrating client starts
+    /// from that result until its own subscription catches up, so reading the
+    /// signal under a `<Suspense/>` renders the data on first paint.
     pub fn new<F, Q>(
         query: impl Fn() -> Option<Q> + Send + Sync + 'static,
-    ) -> ReadSignal<Option<Result<F, String>>>
+    ) -> Signal<Option<Result<F, ConvexClientError>>>
     where
         F: DeserializeOwned + PartialEq + Clone + Send + Sync + 'static + std::fmt::Debug,
         Q: Query<F> + Serialize + Send + Sync + 'static + PartialEq + Clone + std::fmt::Debug,
     {
         let source = Memo::new(move |_| query());
+        let snapshot = query_snapshot(move || {
+            let query = source.get()?;
+            Some((query.name(), query.args().ok()?))
+        });
 
         let (query_signal, set_query_signal) = signal(None);
 
@@ -399,48 +750,27 @@ impl UseQuery {
                     let args_value = match query.args() {
                         Ok(val) => val,
                         Err(e) => {
-                            set_query_signal(Some(Err(format!(
-                                "Failed to serialize query arguments: {e}"
+                            set_query_signal(Some(Err(ConvexClientError::Serialization(
+                                e.to_string(),
                             ))));
                             return;
                         }
                     };
+                    let seed = snapshot
+                        .get_untracked()
+                        .and_then(|snapshot| snapshot_for(snapshot, &args_value));
 
                     spawn_local_scoped_with_cancellation(async move {
-                        match client.subscribe(&name_clone, args_value).await {
+                        match client
+                            .subscribe_with_seed(&name_clone, args_value, seed)
+                            .await
+                        {
                             Ok(sub) => {
                                 if let Some(initial) = sub.initial.clone() {
-                                    let initial = match initial {
-                                        FunctionResult::Value(value) => {
-                                            match serde_json::from_value::<F>(value) {
-                                                Err(err) => Err(format!(
-                                                    "Deserialization error for {query:?} query: {err}"
-                                                )),
-                                                Ok(value) => Ok(value),
-                                            }
-                                        }
-                                        FunctionResult::ErrorMessage(err) => Err(err),
-                                        FunctionResult::ConvexError(convex_error) => {
-                                            Err(format!("{convex_error:?}"))
-                                        }
-                                    };
-
-                                    set_query_signal(Some(initial));
+                                    set_query_signal(Some(decode_result(initial, &query)));
                                 }
-                                let mut sub_stream = sub.map(|result| match result {
-                                    FunctionResult::Value(value) => {
-                                        match serde_json::from_value::<F>(value) {
-                                            Err(err) => Err(format!(
-                                                "Deserialization error for {query:?} query: {err}"
-                                            )),
-                                            Ok(value) => Ok(value),
-                                        }
-                                    }
-                                    FunctionResult::ErrorMessage(err) => Err(err),
-                                    FunctionResult::ConvexError(convex_error) => {
-                                        Err(format!("{convex_error:?}"))
-                                    }
-                                });
+                                let mut sub_stream =
+                                    sub.map(|result| decode_result(result, &query));
                                 while let Some(result) = sub_stream.next().await {
                                     let prev_value = query_signal.get_untracked();
                                     if prev_value.is_none()
@@ -451,7 +781,9 @@ impl UseQuery {
                                 }
                             }
                             Err(e) => {
-                                set_query_signal(Some(Err(format!("Failed to subscribe: {e}"))));
+                                set_query_signal(Some(Err(ConvexClientError::Transport(format!(
+                                    "Failed to subscribe: {e}"
+                                )))));
                             }
                         }
                     });
@@ -462,13 +794,22 @@ impl UseQuery {
                 set_query_signal(None);
             }
         });
-        query_signal
+
+        Signal::derive(move || {
+            if let Some(result) = query_signal.get() {
+                return Some(result);
+            }
+            let query = source.get()?;
+            let args = query.args().ok()?;
+            let seed = snapshot_for(snapshot.get()?, &args)?;
+            Some(decode_result(seed, &query))
+        })
     }
 
     pub fn with_preloaded<F, Q>(
         query: impl Fn() -> Option<Q> + Send + Sync + 'static,
         preloaded: F,
-    ) -> Signal<Option<Result<F, String>>>
+    ) -> Signal<Option<Result<F, ConvexClientError>>>
     where
         F: DeserializeOwned + PartialEq + Clone + Send + Sync + 'static + std::fmt::Debug,
         Q: Query<F> + Serialize + Send + Sync + 'static + PartialEq + Clone + std::fmt::Debug,
@@ -479,16 +820,24 @@ impl UseQuery {
             if query_signal.get().is_none() {
                 Some(Ok(preloaded.clone()))
             } else {
-                query_signal()
+                query_signal.get()
             }
         })
     }
 }
 
+fn decode_result<F: DeserializeOwned>(
+    result: FunctionResult,
+    query: &impl std::fmt::Debug,
+) -> Result<F, ConvexClientError> {
+    from_json::<F>(result.into_result()?)
+        .map_err(|err| ConvexClientError::Deserialization(format!("{query:?} query: {err}")))
+}
+
 pub struct UseMutation;
 
 impl UseMutation {
-    pub fn new<M>() -> Action<M, Result<M::Output, String>>
+    pub fn new<M>() -> Action<M, Result<M::Output, ConvexClientError>>
     where
         M: Mutation + Clone,
     {
@@ -542,25 +891,31 @@ pub trait Mutation: Serialize + Send + Sync + 'static {
     where
         Self: serde::Serialize,
     {
-        let mut value = serde_json::to_value(self)?;
-        convex_json(&mut value);
-        Ok(value)
+        Ok(to_json(self)?)
     }
-    async fn run(&self, client: &mut ConvexClient) -> Result<Self::Output, String> {
-        match client
-            .mutation(&self.name(), self.args().unwrap_or_default())
-            .await
-        {
-            Ok(FunctionResult::Value(value)) => {
-                match serde_json::from_value::<Self::Output>(value) {
-                    Err(err) => Err(format!("{err}")),
-                    Ok(value) => Ok(value),
-                }
-            }
-            Ok(FunctionResult::ErrorMessage(err)) => Err(err),
-            Ok(FunctionResult::ConvexError(convex_error)) => Err(format!("{convex_error:?}")),
-            Err(e) => Err(format!("Mutation failed: {e}")),
-        }
+    /// Patch the local query results while this mutation is in flight. See
+    /// [`OptimisticUpdate`].
+    fn optimistic_update(&self) -> Option<OptimisticUpdate> {
+        None
+    }
+    /// How long to wait for this mutation before giving up, overriding the
+    /// client's [request timeout](ConvexClientBuilder::with_request_timeout).
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+    /// A key identifying this mutation across retries and page reloads.
+    ///
+    /// Mutations with a key are kept in the client's [`MutationStore`] until
+    /// the server acknowledges them, and replayed by the next client built
+    /// on the same store otherwise. The server function must dedupe them by
+    /// this key, since a replayed mutation may have run already.
+    fn idempotency_key(&self) -> Option<String> {
+        None
+    }
+    async fn run(&self, client: &mut ConvexClient) -> Result<Self::Output, ConvexClientError> {
+        let value = client.run_mutation(self).await?.into_result()?;
+        from_json::<Self::Output>(value)
+            .map_err(|err| ConvexClientError::Deserialization(err.to_string()))
     }
 }
 
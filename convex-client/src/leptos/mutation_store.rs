@@ -0,0 +1,74 @@
+use leptos::logging::error;
+
+use crate::base::mutation_store::{InMemoryMutationStore, MutationStore, PersistedMutation};
+
+/// A [`MutationStore`] backed by the tab's `sessionStorage`.
+///
+/// Session storage survives page reloads but is private to the tab, so two
+/// tabs never replay each other's mutations.
+pub struct BrowserMutationStore {
+    storage_key: String,
+    mutations: InMemoryMutationStore,
+}
+
+impl BrowserMutationStore {
+    /// Store mutations as JSON under `storage_key`, picking up those left
+    /// there by a previous page load.
+    pub fn new(storage_key: &str) -> Self {
+        let mutations = session_storage()
+            .and_then(|storage| storage.get_item(storage_key).ok().flatten())
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(mutations) => Some(mutations),
+                Err(err) => {
+                    error!("Ignoring unreadable persisted mutations: {err}");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            storage_key: storage_key.to_string(),
+            mutations: InMemoryMutationStore::new(mutations),
+        }
+    }
+
+    fn save(&self) {
+        let Some(storage) = session_storage() else {
+            return;
+        };
+        let mutations = self.mutations.load();
+        let result = if mutations.is_empty() {
+            storage.remove_item(&self.storage_key)
+        } else {
+            match serde_json::to_string(&mutations) {
+                Ok(json) => storage.set_item(&self.storage_key, &json),
+                Err(err) => {
+                    error!("Failed to serialize persisted mutations: {err}");
+                    return;
+                }
+            }
+        };
+        if let Err(err) = result {
+            error!("Failed to persist mutations: {err:?}");
+        }
+    }
+}
+
+impl MutationStore for BrowserMutationStore {
+    fn load(&self) -> Vec<PersistedMutation> {
+        self.mutations.load()
+    }
+
+    fn insert(&mut self, mutation: PersistedMutation) {
+        self.mutations.insert(mutation);
+        self.save();
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.mutations.remove(key);
+        self.save();
+    }
+}
+
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok().flatten()
+}
@@ -0,0 +1,652 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use futures::channel::mpsc;
+use futures::stream::{AbortHandle, SelectAll, abortable};
+use futures::{Future, Stream, StreamExt, select};
+use leptos::prelude::*;
+use leptos::task::spawn_local_scoped_with_cancellation;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ConvexClientError;
+use crate::base::optimistic_update::OptimisticLocalStore;
+use crate::base::query_result::FunctionResult;
+use crate::value::{from_json, to_json};
+
+use super::ConvexClient;
+
+/// The `paginationOpts` argument of a paginated Convex query.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginationOptions {
+    /// Sent as a float, since Convex validates it with `v.number()`.
+    pub num_items: f64,
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_cursor: Option<String>,
+    /// Keeps two pages starting at the same cursor as separate subscriptions.
+    pub id: f64,
+}
+
+/// The value returned by a paginated Convex query.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginationResult<T> {
+    pub page: Vec<T>,
+    pub is_done: bool,
+    pub continue_cursor: String,
+    #[serde(default)]
+    pub split_cursor: Option<String>,
+    #[serde(default)]
+    pub page_status: Option<PageStatus>,
+}
+
+/// Set by the server when a page has grown too large to be served in one
+/// query and should be split at `split_cursor`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PageStatus {
+    SplitRecommended,
+    SplitRequired,
+}
+
+/// The loading state of a paginated query.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PaginationStatus {
+    #[default]
+    LoadingFirstPage,
+    CanLoadMore,
+    LoadingMore,
+    Exhausted,
+}
+
+/// The merged results of all the loaded pages of a paginated query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaginatedResults<T> {
+    pub results: Vec<T>,
+    pub status: PaginationStatus,
+}
+
+/// A Convex query that takes a `paginationOpts` argument and returns a
+/// [`PaginationResult`] of `T`.
+///
+/// The implementing struct holds every other argument; the pagination options
+/// are added by [`UsePaginatedQuery`] for each page.
+pub trait PaginatedQuery<T: DeserializeOwned + Send + Sync + 'static> {
+    fn name(&self) -> String;
+    fn args(&self, options: &PaginationOptions) -> anyhow::Result<Value>
+    where
+        Self: serde::Serialize,
+    {
+        let mut value = to_json(self)?;
+        let Value::Object(args) = &mut value else {
+            anyhow::bail!("Paginated query arguments must serialize to an object");
+        };
+        args.insert("paginationOpts".into(), to_json(options)?);
+        Ok(value)
+    }
+}
+
+impl OptimisticLocalStore<'_> {
+    /// Patch the first page of every loaded instance of the paginated `query`,
+    /// whatever its page size.
+    pub fn update_first_page<T, Q>(&mut self, query: &Q, update: impl Fn(&mut Vec<T>))
+    where
+        T: DeserializeOwned + Serialize + Send + Sync + 'static,
+        Q: PaginatedQuery<T> + Serialize,
+    {
+        let Ok(udf_path) = query.name().parse() else {
+            return;
+        };
+        let Ok(query_args) = to_json(query) else {
+            return;
+        };
+        for (args, value) in self.get_all_queries(udf_path) {
+            let Some(value) = value else {
+                continue;
+            };
+            let Value::Object(mut other_args) = args.clone() else {
+                continue;
+            };
+            let is_first_page = other_args
+                .remove("paginationOpts")
+                .and_then(|options| from_json::<PaginationOptions>(options).ok())
+                .is_some_and(|options| options.cursor.is_none());
+            if !is_first_page || Value::Object(other_args) != query_args {
+                continue;
+            }
+            let Ok(mut result) = from_json::<PaginationResult<T>>(value) else {
+                continue;
+            };
+            update(&mut result.page);
+            if let (Ok(udf_path), Ok(value)) = (query.name().parse(), to_json(&result)) {
+                self.set_query(udf_path, &args, value);
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum PageRequest {
+    Subscribe(u32, PaginationOptions),
+    Unsubscribe(u32),
+}
+
+struct Page {
+    id: u32,
+    options: PaginationOptions,
+    result: Option<Result<PaginationResult<Value>, ConvexClientError>>,
+}
+
+impl Page {
+    fn new(id: u32, num_items: usize, cursor: Option<String>, end_cursor: Option<String>) -> Self {
+        Self {
+            id,
+            options: PaginationOptions {
+                num_items: num_items as f64,
+                cursor,
+                end_cursor,
+                id: id as f64,
+            },
+            result: None,
+        }
+    }
+
+    fn subscribe(&self) -> PageRequest {
+        PageRequest::Subscribe(self.id, self.options.clone())
+    }
+}
+
+/// The list of pages backing a paginated query.
+///
+/// This only keeps track of cursors and results; subscribing and
+/// unsubscribing is left to the caller through the returned
+/// [`PageRequest`]s.
+pub(crate) struct Pages {
+    initial_num_items: usize,
+    next_page_id: u32,
+    pages: Vec<Page>,
+    /// Halves of a page being split, not shown until both have loaded.
+    split_pages: BTreeMap<u32, Page>,
+    /// Page being split, mapped to the two pages that will replace it.
+    ongoing_splits: BTreeMap<u32, (u32, u32)>,
+}
+
+impl Pages {
+    pub(crate) fn new(initial_num_items: usize) -> (Self, Vec<PageRequest>) {
+        let first_page = Page::new(0, initial_num_items, None, None);
+        let requests = vec![first_page.subscribe()];
+        let pages = Self {
+            initial_num_items,
+            next_page_id: 1,
+            pages: vec![first_page],
+            split_pages: BTreeMap::new(),
+            ongoing_splits: BTreeMap::new(),
+        };
+        (pages, requests)
+    }
+
+    fn next_page_id(&mut self) -> u32 {
+        let id = self.next_page_id;
+        self.next_page_id += 1;
+        id
+    }
+
+    /// Add a page of `num_items` after the last one, if it has loaded and
+    /// there is more to load.
+    pub(crate) fn load_more(&mut self, num_items: usize) -> Vec<PageRequest> {
+        let continue_cursor = match self.pages.last().and_then(|page| page.result.as_ref()) {
+            Some(Ok(result)) if !result.is_done => result.continue_cursor.clone(),
+            _ => return vec![],
+        };
+        let page_id = self.next_page_id();
+        let page = Page::new(page_id, num_items, Some(continue_cursor), None);
+        let request = page.subscribe();
+        self.pages.push(page);
+        vec![request]
+    }
+
+    pub(crate) fn receive(
+        &mut self,
+        page_id: u32,
+        result: Result<PaginationResult<Value>, ConvexClientError>,
+    ) -> Vec<PageRequest> {
+        if let Some(page) = self.split_pages.get_mut(&page_id) {
+            page.result = Some(result);
+            return self.complete_splits();
+        }
+        let Some(index) = self.pages.iter().position(|page| page.id == page_id) else {
+            return vec![];
+        };
+        let page = &mut self.pages[index];
+        let split_cursor = match &result {
+            Ok(result)
+                if result.page_status.is_some()
+                    || result.page.len() > self.initial_num_items * 2 =>
+            {
+                result.split_cursor.clone()
+            }
+            _ => None,
+        };
+        let end_cursor = match &result {
+            Ok(result) => Some(result.continue_cursor.clone()),
+            Err(_) => None,
+        };
+        page.result = Some(result);
+
+        let (Some(split_cursor), Some(end_cursor)) = (split_cursor, end_cursor) else {
+            return vec![];
+        };
+        if self.ongoing_splits.contains_key(&page_id) {
+            return vec![];
+        }
+        let num_items = self.pages[index].options.num_items as usize;
+        let cursor = self.pages[index].options.cursor.clone();
+        let first_half_id = self.next_page_id();
+        let second_half_id = self.next_page_id();
+        let first_half = Page::new(first_half_id, num_items, cursor, Some(split_cursor.clone()));
+        let second_half = Page::new(
+            second_half_id,
+            num_items,
+            Some(split_cursor),
+            Some(end_cursor),
+        );
+        let requests = vec![first_half.subscribe(), second_half.subscribe()];
+        self.split_pages.insert(first_half_id, first_half);
+        self.split_pages.insert(second_half_id, second_half);
+        self.ongoing_splits
+            .insert(page_id, (first_half_id, second_half_id));
+        requests
+    }
+
+    /// Swap in the halves of every split page whose halves have both loaded.
+    fn complete_splits(&mut self) -> Vec<PageRequest> {
+        let completed: Vec<_> = self
+            .ongoing_splits
+            .iter()
+            .filter(|(_, (first, second))| {
+                self.split_pages[first].result.is_some()
+                    && self.split_pages[second].result.is_some()
+            })
+            .map(|(page_id, halves)| (*page_id, *halves))
+            .collect();
+
+        let mut requests = vec![];
+        for (page_id, (first, second)) in completed {
+            self.ongoing_splits.remove(&page_id);
+            let first = self.split_pages.remove(&first).expect("split page exists");
+            let second = self.split_pages.remove(&second).expect("split page exists");
+            if let Some(index) = self.pages.iter().position(|page| page.id == page_id) {
+                self.pages.splice(index..=index, [first, second]);
+            }
+            requests.push(PageRequest::Unsubscribe(page_id));
+        }
+        requests
+    }
+
+    /// Merge the loaded pages, in order, up to the first one still loading.
+    pub(crate) fn results(&self) -> Result<PaginatedResults<Value>, ConvexClientError> {
+        let mut results = vec![];
+        for (index, page) in self.pages.iter().enumerate() {
+            match &page.result {
+                None => {
+                    let status = if index == 0 {
+                        PaginationStatus::LoadingFirstPage
+                    } else {
+                        PaginationStatus::LoadingMore
+                    };
+                    return Ok(PaginatedResults { results, status });
+                }
+                Some(Err(err)) => return Err(err.clone()),
+                Some(Ok(result)) => results.extend(result.page.iter().cloned()),
+            }
+        }
+        let is_done = matches!(
+            self.pages.last().and_then(|page| page.result.as_ref()),
+            Some(Ok(result)) if result.is_done
+        );
+        let status = if is_done {
+            PaginationStatus::Exhausted
+        } else {
+            PaginationStatus::CanLoadMore
+        };
+        Ok(PaginatedResults { results, status })
+    }
+}
+
+/// The items are left in their wire encoding, to be decoded as the query's
+/// item type once the pages are merged.
+fn page_result(result: FunctionResult) -> Result<PaginationResult<Value>, ConvexClientError> {
+    serde_json::from_value(result.into_result()?).map_err(|err| {
+        ConvexClientError::Deserialization(format!("Paginated query did not return a page: {err}"))
+    })
+}
+
+/// Keep the page subscriptions of a paginated query in sync with [`Pages`],
+/// reporting the merged results after every change, until `load_more` is
+/// closed.
+pub(crate) async fn drive_paginated_query<S, Sub, Fut>(
+    args: impl Fn(&PaginationOptions) -> anyhow::Result<Value>,
+    initial_num_items: usize,
+    mut subscribe: Sub,
+    load_more: mpsc::UnboundedReceiver<usize>,
+    mut on_results: impl FnMut(Result<PaginatedResults<Value>, ConvexClientError>),
+) where
+    Sub: FnMut(Value) -> Fut,
+    Fut: Future<Output = anyhow::Result<S>>,
+    S: Stream<Item = FunctionResult> + Unpin,
+{
+    let (mut pages, mut requests) = Pages::new(initial_num_items);
+    let mut page_streams = SelectAll::new();
+    let mut abort_handles: BTreeMap<u32, AbortHandle> = BTreeMap::new();
+    let mut load_more = load_more.fuse();
+
+    loop {
+        for request in requests.drain(..) {
+            match request {
+                PageRequest::Subscribe(page_id, options) => {
+                    let subscription = match args(&options) {
+                        Ok(args) => subscribe(args).await,
+                        Err(err) => Err(err),
+                    };
+                    match subscription {
+                        Ok(subscription) => {
+                            let (stream, abort_handle) =
+                                abortable(subscription.map(move |result| (page_id, result)));
+                            abort_handles.insert(page_id, abort_handle);
+                            page_streams.push(stream);
+                        }
+                        Err(err) => {
+                            on_results(Err(ConvexClientError::Transport(format!(
+                                "Failed to subscribe: {err}"
+                            ))));
+                            return;
+                        }
+                    }
+                }
+                PageRequest::Unsubscribe(page_id) => {
+                    // Aborting ends the stream, which drops the subscription.
+                    if let Some(abort_handle) = abort_handles.remove(&page_id) {
+                        abort_handle.abort();
+                    }
+                }
+            }
+        }
+        on_results(pages.results());
+
+        select! {
+            num_items = load_more.next() => match num_items {
+                Some(num_items) => requests = pages.load_more(num_items),
+                None => break,
+            },
+            (page_id, result) = page_streams.select_next_some() => {
+                requests = pages.receive(page_id, page_result(result));
+            },
+            complete => break,
+        }
+    }
+}
+
+/// A reactive paginated query.
+///
+/// Subscribes to the first page of `initial_num_items` and to one more page
+/// per call to [`load_more`](Self::load_more), merging all of them into
+/// [`results`](Self::results). Pages that grow too large are split
+/// transparently.
+pub struct UsePaginatedQuery<T: Send + Sync + 'static> {
+    results: ReadSignal<Option<Result<Vec<T>, ConvexClientError>>>,
+    status: ReadSignal<PaginationStatus>,
+    load_more: StoredValue<Option<mpsc::UnboundedSender<usize>>>,
+}
+
+impl<T: Send + Sync + 'static> Clone for UsePaginatedQuery<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Send + Sync + 'static> Copy for UsePaginatedQuery<T> {}
+
+impl<T> UsePaginatedQuery<T>
+where
+    T: DeserializeOwned + PartialEq + Clone + Send + Sync + 'static + Debug,
+{
+    pub fn new<Q>(
+        query: impl Fn() -> Option<Q> + Send + Sync + 'static,
+        initial_num_items: usize,
+    ) -> Self
+    where
+        Q: PaginatedQuery<T> + Serialize + Send + Sync + 'static + PartialEq + Clone + Debug,
+    {
+        let source = Memo::new(move |_| query());
+
+        let (results, set_results) = signal(None);
+        let (status, set_status) = signal(PaginationStatus::LoadingFirstPage);
+        let load_more = StoredValue::new(None);
+
+        Effect::new(move |_| {
+            set_results.set(None);
+            set_status.set(PaginationStatus::LoadingFirstPage);
+            load_more.set_value(None);
+            let (Some(client), Some(query)) = (use_context::<ConvexClient>(), source.get()) else {
+                return;
+            };
+            let (load_more_sender, load_more_receiver) = mpsc::unbounded();
+            load_more.set_value(Some(load_more_sender));
+
+            spawn_local_scoped_with_cancellation(async move {
+                let name = query.name();
+                let subscribe = {
+                    let name = name.clone();
+                    move |args| {
+                        let mut client = client.clone();
+                        let name = name.clone();
+                        async move { client.subscribe(&name, args).await }
+                    }
+                };
+                let on_results =
+                    move |update: Result<PaginatedResults<Value>, ConvexClientError>| {
+                        let update = update.and_then(|update| {
+                            let items = update
+                                .results
+                                .into_iter()
+                                .map(from_json::<T>)
+                                .collect::<Result<Vec<_>, _>>()
+                                .map_err(|err| {
+                                    ConvexClientError::Deserialization(format!(
+                                        "{name} paginated query: {err}"
+                                    ))
+                                })?;
+                            Ok((items, update.status))
+                        });
+                        let result = match update {
+                            Ok((items, status)) => {
+                                set_status.set(status);
+                                Ok(items)
+                            }
+                            Err(err) => Err(err),
+                        };
+                        if results.get_untracked().as_ref() != Some(&result) {
+                            set_results.set(Some(result));
+                        }
+                    };
+                drive_paginated_query(
+                    move |options| query.args(options),
+                    initial_num_items,
+                    subscribe,
+                    load_more_receiver,
+                    on_results,
+                )
+                .await;
+            });
+        });
+
+        Self {
+            results,
+            status,
+            load_more,
+        }
+    }
+
+    /// The merged items of every loaded page, or `None` while there is no
+    /// query to run.
+    pub fn results(&self) -> ReadSignal<Option<Result<Vec<T>, ConvexClientError>>> {
+        self.results
+    }
+
+    pub fn status(&self) -> ReadSignal<PaginationStatus> {
+        self.status
+    }
+
+    /// Load `num_items` more items after the last loaded page. Does nothing
+    /// while a page is loading or once the query is exhausted.
+    pub fn load_more(&self, num_items: usize) {
+        self.load_more.with_value(|load_more| {
+            if let Some(load_more) = load_more {
+                let _ = load_more.unbounded_send(num_items);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn page(items: &[i64], continue_cursor: &str, is_done: bool) -> PaginationResult<Value> {
+        PaginationResult {
+            page: items.iter().map(|item| json!(item)).collect(),
+            is_done,
+            continue_cursor: continue_cursor.into(),
+            split_cursor: None,
+            page_status: None,
+        }
+    }
+
+    fn options(
+        num_items: usize,
+        cursor: Option<&str>,
+        end_cursor: Option<&str>,
+        id: u32,
+    ) -> PaginationOptions {
+        PaginationOptions {
+            num_items: num_items as f64,
+            cursor: cursor.map(Into::into),
+            end_cursor: end_cursor.map(Into::into),
+            id: id as f64,
+        }
+    }
+
+    fn items(pages: &Pages) -> Vec<Value> {
+        pages.results().unwrap().results
+    }
+
+    #[test]
+    fn page_is_split_when_the_server_asks() {
+        let (mut pages, _) = Pages::new(2);
+        let oversized = PaginationResult {
+            split_cursor: Some("b".into()),
+            page_status: Some(PageStatus::SplitRecommended),
+            ..page(&[1, 2, 3], "c", false)
+        };
+
+        let requests = pages.receive(0, Ok(oversized));
+
+        assert_eq!(
+            requests,
+            vec![
+                PageRequest::Subscribe(1, options(2, None, Some("b"), 1)),
+                PageRequest::Subscribe(2, options(2, Some("b"), Some("c"), 2)),
+            ]
+        );
+        // The oversized page is shown until both halves load.
+        assert_eq!(items(&pages), vec![json!(1), json!(2), json!(3)]);
+        // Another update of it doesn't split it again.
+        let oversized = PaginationResult {
+            split_cursor: Some("b".into()),
+            page_status: Some(PageStatus::SplitRequired),
+            ..page(&[1, 2, 3, 4], "c", false)
+        };
+        assert_eq!(pages.receive(0, Ok(oversized)), vec![]);
+    }
+
+    #[test]
+    fn page_grown_past_twice_its_size_is_split() {
+        let (mut pages, _) = Pages::new(1);
+        let grown = PaginationResult {
+            split_cursor: Some("b".into()),
+            ..page(&[1, 2, 3], "c", true)
+        };
+
+        assert_eq!(pages.receive(0, Ok(grown)).len(), 2);
+    }
+
+    #[test]
+    fn split_completes_once_both_halves_load() {
+        let (mut pages, _) = Pages::new(2);
+        let oversized = PaginationResult {
+            split_cursor: Some("b".into()),
+            page_status: Some(PageStatus::SplitRecommended),
+            ..page(&[1, 2, 3], "c", false)
+        };
+        pages.receive(0, Ok(oversized));
+
+        assert_eq!(pages.receive(2, Ok(page(&[3], "c", false))), vec![]);
+        assert_eq!(items(&pages), vec![json!(1), json!(2), json!(3)]);
+
+        assert_eq!(
+            pages.receive(1, Ok(page(&[1, 2], "b", false))),
+            vec![PageRequest::Unsubscribe(0)]
+        );
+        assert_eq!(items(&pages), vec![json!(1), json!(2), json!(3)]);
+        // The halves are regular pages now: loading more continues after the
+        // second one.
+        assert_eq!(
+            pages.load_more(2),
+            vec![PageRequest::Subscribe(3, options(2, Some("c"), None, 3))]
+        );
+    }
+
+    #[test]
+    fn load_more_continues_after_the_last_page() {
+        let (mut pages, requests) = Pages::new(2);
+        assert_eq!(
+            requests,
+            vec![PageRequest::Subscribe(0, options(2, None, None, 0))]
+        );
+        // Nothing to continue from while the last page is loading.
+        assert_eq!(pages.load_more(3), vec![]);
+        assert_eq!(
+            pages.results().unwrap().status,
+            PaginationStatus::LoadingFirstPage
+        );
+
+        pages.receive(0, Ok(page(&[1, 2], "a", false)));
+        assert_eq!(
+            pages.results().unwrap().status,
+            PaginationStatus::CanLoadMore
+        );
+        assert_eq!(
+            pages.load_more(3),
+            vec![PageRequest::Subscribe(1, options(3, Some("a"), None, 1))]
+        );
+        assert_eq!(
+            pages.results().unwrap().status,
+            PaginationStatus::LoadingMore
+        );
+
+        pages.receive(1, Ok(page(&[3], "b", true)));
+        assert_eq!(
+            pages.results().unwrap(),
+            PaginatedResults {
+                results: vec![json!(1), json!(2), json!(3)],
+                status: PaginationStatus::Exhausted,
+            }
+        );
+        // The last page is done, so there is nothing more to load.
+        assert_eq!(pages.load_more(3), vec![]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::Debug;
+
+use convex_sync_types::StateVersion;
+use futures::StreamExt;
+use futures::future::try_join_all;
+use leptos::prelude::*;
+use leptos::task::spawn_local_scoped_with_cancellation;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::ConvexClientError;
+use crate::base::query_result::FunctionResult;
+
+use super::{ConvexClient, Query, decode_result};
+
+/// A tuple of [`Query`]s read together by [`use_queries`], with `F` the
+/// tuple of their result types.
+pub trait Queries<F>: PartialEq + Clone + Send + Sync + 'static + Debug {
+    /// The decoded result of each query, in order.
+    type Output: PartialEq + Clone + Send + Sync + 'static;
+
+    /// The name and args of each query, in order.
+    fn requests(&self) -> anyhow::Result<Vec<(String, Value)>>;
+
+    /// Decode `results`, one per query, in order.
+    fn decode(&self, results: &[FunctionResult]) -> Self::Output;
+
+    /// `err` as the result of every query.
+    fn fail(err: ConvexClientError) -> Self::Output;
+}
+
+macro_rules! impl_queries {
+    ($(($query:ident, $output:ident, $index:tt)),+) => {
+        impl<$($query, $output),+> Queries<($($output,)+)> for ($($query,)+)
+        where
+            $(
+                $output: DeserializeOwned + PartialEq + Clone + Send + Sync + 'static,
+                $query: Query<$output>
+                    + Serialize
+                    + PartialEq
+                    + Clone
+                    + Send
+                    + Sync
+                    + 'static
+                    + Debug,
+            )+
+        {
+            type Output = ($(Result<$output, ConvexClientError>,)+);
+
+            fn requests(&self) -> anyhow::Result<Vec<(String, Value)>> {
+                Ok(vec![$((self.$index.name(), self.$index.args()?)),+])
+            }
+
+            fn decode(&self, results: &[FunctionResult]) -> Self::Output {
+                ($(decode_result(results[$index].clone(), &self.$index),)+)
+            }
+
+            fn fail(err: ConvexClientError) -> Self::Output {
+                ($(Err::<$output, _>(err.clone()),)+)
+            }
+        }
+    };
+}
+
+impl_queries!((Q0, F0, 0), (Q1, F1, 1));
+impl_queries!((Q0, F0, 0), (Q1, F1, 1), (Q2, F2, 2));
+impl_queries!((Q0, F0, 0), (Q1, F1, 1), (Q2, F2, 2), (Q3, F3, 3));
+impl_queries!(
+    (Q0, F0, 0),
+    (Q1, F1, 1),
+    (Q2, F2, 2),
+    (Q3, F3, 3),
+    (Q4, F4, 4)
+);
+impl_queries!(
+    (Q0, F0, 0),
+    (Q1, F1, 1),
+    (Q2, F2, 2),
+    (Q3, F3, 3),
+    (Q4, F4, 4),
+    (Q5, F5, 5)
+);
+
+/// Subscribe to the tuple of queries returned by `queries`, resubscribing
+/// whenever it changes, and read their results as a tuple that only updates
+/// once all of them come from the same server transition.
+///
+/// Unlike separate [`UseQuery`](super::UseQuery)s, which update one at a
+/// time, the results never mix two versions of the server state, e.g. a
+/// message list and the reaction counts of its messages. Until all the
+/// queries have a result, and while their results catch up with each other
+/// after a reconnect, the signal keeps its previous value. `None` means no
+/// queries, and reads as `None`.
+///
+/// Results rendered on the server aren't consistent with each other, so the
+/// signal stays `None` until the client has its own.
+///
+/// ```ignore
+/// let results = use_queries(move || Some((ListMessages { channel }, ListReactions { channel })));
+/// let reaction_counts = move || {
+///     let (messages, reactions) = results.get()?;
+///     Some(count_reactions(messages.ok()?, reactions.ok()?))
+/// };
+/// ```
+pub fn use_queries<F, T>(
+    queries: impl Fn() -> Option<T> + Send + Sync + 'static,
+) -> Signal<Option<T::Output>>
+where
+    T: Queries<F>,
+{
+    let source = Memo::new(move |_| queries());
+    let (results, set_results) = signal(None);
+
+    Effect::new(move |_| {
+        set_results.set(None);
+        let (Some(client), Some(queries)) = (use_context::<ConvexClient>(), source.get()) else {
+            return;
+        };
+        let requests = match queries.requests() {
+            Ok(requests) => requests,
+            Err(err) => {
+                set_results.set(Some(T::fail(ConvexClientError::Serialization(
+                    err.to_string(),
+                ))));
+                return;
+            }
+        };
+
+        spawn_local_scoped_with_cancellation(async move {
+            // Watch before subscribing, so that no transition is missed.
+            let mut watch = client.watch_all();
+            let subscribe = requests.into_iter().map(|(name, args)| {
+                let mut client = client.clone();
+                async move { client.subscribe(&name, args).await }
+            });
+            let subscriptions = match try_join_all(subscribe).await {
+                Ok(subscriptions) => subscriptions,
+                Err(err) => {
+                    set_results.set(Some(T::fail(ConvexClientError::Transport(format!(
+                        "Failed to subscribe: {err}"
+                    )))));
+                    return;
+                }
+            };
+
+            let mut latest: Vec<_> = subscriptions
+                .iter()
+                .map(|subscription| (subscription.initial.clone(), subscription.initial_version))
+                .collect();
+            loop {
+                if let Some(consistent) = consistent_results(&latest) {
+                    let output = queries.decode(&consistent);
+                    if results.get_untracked().as_ref() != Some(&output) {
+                        set_results.set(Some(output));
+                    }
+                }
+                let Some(snapshot) = watch.next().await else {
+                    break;
+                };
+                latest = subscriptions
+                    .iter()
+                    .map(|subscription| {
+                        (
+                            snapshot.get(subscription.id()).cloned(),
+                            snapshot.version(subscription.id()),
+                        )
+                    })
+                    .collect();
+            }
+        });
+    });
+
+    results.into()
+}
+
+/// The results in `latest`, if every query has one and they all share a
+/// version.
+fn consistent_results(
+    latest: &[(Option<FunctionResult>, Option<StateVersion>)],
+) -> Option<Vec<FunctionResult>> {
+    let (_, Some(version)) = latest.first()? else {
+        return None;
+    };
+    latest
+        .iter()
+        .map(|(result, result_version)| match result_version {
+            Some(result_version) if result_version == version => result.clone(),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,94 @@
+use leptos::prelude::*;
+use serde_json::Value;
+
+use crate::base::query_result::FunctionResult;
+
+/// A query result rendered on the server, with the args it was run with.
+pub(super) type Snapshot = Option<(Value, FunctionResult)>;
+
+/// How long server rendering waits on a query before leaving it to the
+/// hydrated client.
+#[cfg(feature = "ssr")]
+const SNAPSHOT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Run the query named and called with the args from `source` while server
+/// rendering, and send its result to the hydrating client with the HTML.
+///
+/// Renders for a [`RenderingUser`](crate::server::RenderingUser) run the
+/// query with that user's client from the
+/// [`ConvexClientPool`](crate::server::ConvexClientPool) provided as context,
+/// and others with the server [`ConvexClient`](crate::server::ConvexClient)
+/// provided as context. Without the client to use, nothing is rendered
+/// ahead of the live subscription. In the browser the snapshot is only ever
+/// read back from the HTML.
+pub(super) fn query_snapshot(
+    source: impl Fn() -> Option<(String, Value)> + Send + Sync + 'static,
+) -> Resource<Snapshot> {
+    #[cfg(feature = "ssr")]
+    let client = SnapshotClient::from_context();
+
+    Resource::new(source, move |query| {
+        #[cfg(feature = "ssr")]
+        let client = client.clone();
+        async move {
+            #[cfg(feature = "ssr")]
+            {
+                let (name, args) = query?;
+                let result = tokio::time::timeout(SNAPSHOT_TIMEOUT, async {
+                    client?
+                        .client()
+                        .await?
+                        .query_result(&name, args.clone())
+                        .await
+                        .ok()
+                })
+                .await
+                .ok()??;
+                Some((args, result))
+            }
+            #[cfg(not(feature = "ssr"))]
+            {
+                let _ = query;
+                None
+            }
+        }
+    })
+}
+
+/// The client a server render runs its queries with.
+#[cfg(feature = "ssr")]
+#[derive(Clone)]
+enum SnapshotClient {
+    /// Authenticated as the user the page is rendered for.
+    Pooled(crate::server::ConvexClientPool, String),
+    /// Signed out, for renders for nobody in particular.
+    Shared(crate::server::ConvexClient),
+}
+
+#[cfg(feature = "ssr")]
+impl SnapshotClient {
+    fn from_context() -> Option<Self> {
+        use crate::server::{ConvexClientPool, RenderingUser};
+
+        match use_context::<RenderingUser>() {
+            Some(RenderingUser(user)) => {
+                use_context::<ConvexClientPool>().map(|pool| Self::Pooled(pool, user))
+            }
+            None => use_context().map(Self::Shared),
+        }
+    }
+
+    async fn client(self) -> Option<crate::server::ConvexClient> {
+        match self {
+            Self::Pooled(pool, user) => pool.client(&user).await.ok(),
+            Self::Shared(client) => Some(client),
+        }
+    }
+}
+
+/// The result in `snapshot`, if it was rendered for `args`.
+pub(super) fn snapshot_for(snapshot: Snapshot, args: &Value) -> Option<FunctionResult> {
+    snapshot
+        .filter(|(snapshot_args, _)| snapshot_args == args)
+        .map(|(_, result)| result)
+}
@@ -1,12 +1,26 @@
+mod auth;
 mod base;
+mod connection;
 pub mod leptos;
+pub mod logs;
+pub mod observer;
+pub mod recording;
+mod runtime;
 #[cfg(feature = "ssr")]
 pub mod server;
+mod subscription;
+pub mod value;
 mod websocket;
+mod worker;
 
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct ConvexError {
     /// From any error, redacted from prod deployments.
     pub message: String,
@@ -14,3 +28,119 @@ pub struct ConvexError {
     /// function to a client.
     pub data: Value,
 }
+
+impl ConvexError {
+    /// Decode the application data payload as `T`.
+    pub fn data_as<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        T::deserialize(&self.data)
+    }
+}
+
+/// Returned, wrapped in an [`anyhow::Error`], when a mutation or action gets
+/// no response within its timeout.
+///
+/// A request that timed out before being sent is dropped. One that was
+/// already sent may still run on the server.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TimeoutError {
+    pub udf_path: String,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} timed out after {:?}", self.udf_path, self.timeout)
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// Passed to the `with_on_auth_error` callback of the client builders when
+/// the deployment rejects the client's auth token.
+///
+/// The client stops sending the rejected token and carries on
+/// unauthenticated until a new one is set.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AuthError {
+    pub message: String,
+    /// Whether the token was rejected as it was set, rather than later on,
+    /// e.g. once it expired. `None` from older deployments.
+    pub auth_update_attempted: Option<bool>,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Authentication failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Why a query or mutation run through [`leptos::UseQuery`],
+/// [`leptos::UseMutation`] or [`leptos::Mutation::run`] failed.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConvexClientError {
+    /// The function arguments could not be serialized.
+    Serialization(String),
+    /// The function result did not have the expected type.
+    Deserialization(String),
+    /// The function failed with an error message. Messages are redacted in
+    /// production deployments.
+    Server(String),
+    /// The function threw a `ConvexError` with application data.
+    Convex(ConvexError),
+    /// The request could not reach the deployment or the client went away.
+    Transport(String),
+    Timeout(TimeoutError),
+}
+
+impl ConvexClientError {
+    /// The application data of a [`Convex`](Self::Convex) error decoded as
+    /// `T`, or `None` for other errors and data of another shape.
+    pub fn data_as<T: DeserializeOwned>(&self) -> Option<T> {
+        match self {
+            ConvexClientError::Convex(error) => error.data_as().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConvexClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvexClientError::Serialization(err) => {
+                write!(f, "Failed to serialize arguments: {err}")
+            }
+            ConvexClientError::Deserialization(err) => write!(f, "Deserialization error: {err}"),
+            ConvexClientError::Server(message) => write!(f, "{message}"),
+            ConvexClientError::Convex(error) => write!(f, "{}", error.message),
+            ConvexClientError::Transport(err) => write!(f, "{err}"),
+            ConvexClientError::Timeout(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConvexClientError {}
+
+/// Sort the errors returned by the client methods, which are
+/// [`anyhow::Error`]s, into kinds.
+impl From<anyhow::Error> for ConvexClientError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<TimeoutError>() {
+            Ok(timeout) => return ConvexClientError::Timeout(timeout),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<ConvexClientError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<value::Error>() {
+            Ok(err) => return ConvexClientError::Serialization(err.to_string()),
+            Err(err) => err,
+        };
+        match err.downcast::<serde_json::Error>() {
+            Ok(err) => ConvexClientError::Serialization(err.to_string()),
+            Err(err) => ConvexClientError::Transport(format!("{err:#}")),
+        }
+    }
+}
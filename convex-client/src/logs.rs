@@ -0,0 +1,188 @@
+//! The console output of Convex functions.
+//!
+//! Queries, mutations and actions send back the lines they log. The client
+//! tags each with the function that logged it and hands it to the
+//! [`LogSink`] given to the client builder's `with_log_sink`. By default,
+//! debug builds print them to the console and release builds drop them.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use convex_sync_types::CanonicalizedUdfPath;
+use leptos::logging::{error, log, warn};
+use serde_json::Value;
+
+/// The kind of function that logged a [`LogLine`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionKind {
+    Query,
+    Mutation,
+    Action,
+}
+
+impl fmt::Display for FunctionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FunctionKind::Query => "query",
+            FunctionKind::Mutation => "mutation",
+            FunctionKind::Action => "action",
+        })
+    }
+}
+
+/// The `console` method a [`LogLine`] was logged with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Log,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Log => "LOG",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        })
+    }
+}
+
+/// A line a Convex function logged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogLine {
+    pub kind: FunctionKind,
+    pub udf_path: CanonicalizedUdfPath,
+    /// A hash of the function's arguments, to tell apart the runs of a
+    /// function with different arguments. Only stable within a build.
+    pub args_hash: u64,
+    pub level: LogLevel,
+    /// The line without its `[LEVEL]` prefix.
+    pub message: String,
+}
+
+impl LogLine {
+    /// Tag `line`, as sent by the deployment, with the function run that
+    /// logged it.
+    pub(crate) fn new(
+        kind: FunctionKind,
+        udf_path: CanonicalizedUdfPath,
+        args: &Value,
+        line: String,
+    ) -> Self {
+        let (level, message) = parse_level(line);
+        let mut hasher = DefaultHasher::new();
+        args.to_string().hash(&mut hasher);
+        Self {
+            kind,
+            udf_path,
+            args_hash: hasher.finish(),
+            level,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} {} ({:016x}): {}",
+            self.level, self.kind, self.udf_path, self.args_hash, self.message
+        )
+    }
+}
+
+/// Split the `[LEVEL] ` prefix off a line. Lines without one were logged
+/// with `console.log`.
+fn parse_level(line: String) -> (LogLevel, String) {
+    let levels = [
+        ("[DEBUG] ", LogLevel::Debug),
+        ("[INFO] ", LogLevel::Info),
+        ("[LOG] ", LogLevel::Log),
+        ("[WARN] ", LogLevel::Warn),
+        ("[ERROR] ", LogLevel::Error),
+    ];
+    for (prefix, level) in levels {
+        if let Some(message) = line.strip_prefix(prefix) {
+            return (level, message.to_string());
+        }
+    }
+    (LogLevel::Log, line)
+}
+
+/// Where the client sends the [`LogLine`]s of the functions it runs.
+///
+/// Called from the client's worker, so it should return quickly.
+pub trait LogSink: Send + Sync {
+    fn log(&self, line: LogLine);
+}
+
+/// Prints log lines to stdout on the server and to the browser console on
+/// the client. The default in debug builds.
+pub struct ConsoleLogSink;
+
+impl LogSink for ConsoleLogSink {
+    fn log(&self, line: LogLine) {
+        match line.level {
+            LogLevel::Error => error!("{line}"),
+            LogLevel::Warn => warn!("{line}"),
+            _ => log!("{line}"),
+        }
+    }
+}
+
+/// Drops log lines. The default in release builds.
+pub struct DiscardLogSink;
+
+impl LogSink for DiscardLogSink {
+    fn log(&self, _line: LogLine) {}
+}
+
+/// Emits log lines as `tracing` events on the `convex` target, at the level
+/// they were logged with.
+#[cfg(feature = "ssr")]
+pub struct TracingLogSink;
+
+#[cfg(feature = "ssr")]
+impl LogSink for TracingLogSink {
+    fn log(&self, line: LogLine) {
+        let LogLine {
+            kind,
+            udf_path,
+            args_hash,
+            level,
+            message,
+        } = line;
+        let args_hash = format!("{args_hash:016x}");
+        match level {
+            LogLevel::Debug => {
+                tracing::debug!(target: "convex", %kind, %udf_path, %args_hash, "{message}")
+            }
+            LogLevel::Info | LogLevel::Log => {
+                tracing::info!(target: "convex", %kind, %udf_path, %args_hash, "{message}")
+            }
+            LogLevel::Warn => {
+                tracing::warn!(target: "convex", %kind, %udf_path, %args_hash, "{message}")
+            }
+            LogLevel::Error => {
+                tracing::error!(target: "convex", %kind, %udf_path, %args_hash, "{message}")
+            }
+        }
+    }
+}
+
+/// The sink of client builders that weren't given one.
+pub(crate) fn default_log_sink() -> Arc<dyn LogSink> {
+    if cfg!(debug_assertions) {
+        Arc::new(ConsoleLogSink)
+    } else {
+        Arc::new(DiscardLogSink)
+    }
+}
@@ -0,0 +1,126 @@
+//! Hooks into what a client does, for metrics and tracing.
+//!
+//! Give a [`ClientObserver`] to the client builder's `with_observer`. On the
+//! server, [`TracingObserver`] exports the client's activity as `tracing`
+//! spans and counters.
+
+pub use convex_sync_types::{CanonicalizedUdfPath, QueryId, Timestamp};
+
+pub use crate::base::request_manager::RequestId;
+use crate::logs::LogLine;
+
+/// Callbacks on a client's queries, mutations and connection. Every method
+/// does nothing by default.
+///
+/// They are called from the client's worker, so they should return quickly.
+pub trait ClientObserver: Send + Sync {
+    /// The client subscribed to a query it wasn't subscribed to yet.
+    fn on_subscribe(&self, _query_id: QueryId, _udf_path: &CanonicalizedUdfPath) {}
+
+    /// The deployment sent the first result of a query subscribed to since
+    /// [`on_subscribe`](Self::on_subscribe).
+    fn on_first_result(&self, _query_id: QueryId) {}
+
+    /// The client unsubscribed from a query.
+    fn on_unsubscribe(&self, _query_id: QueryId) {}
+
+    /// The client applied a transition to `ts` that updated
+    /// `updated_queries` query results.
+    fn on_transition(&self, _ts: Timestamp, _updated_queries: usize) {}
+
+    /// A mutation was sent for the first time. Resends after a reconnect
+    /// aren't reported.
+    fn on_mutation_sent(&self, _request_id: RequestId, _udf_path: &CanonicalizedUdfPath) {}
+
+    /// The deployment ran a mutation.
+    fn on_mutation_acknowledged(&self, _request_id: RequestId) {}
+
+    /// A mutation completed: its writes are reflected in the query results,
+    /// or it failed.
+    fn on_mutation_observed(&self, _request_id: RequestId) {}
+
+    /// The client is reconnecting because of `reason`.
+    fn on_reconnect(&self, _reason: &str) {}
+
+    /// A function logged `line`. It is also sent to the client's
+    /// [`LogSink`](crate::logs::LogSink).
+    fn on_log_line(&self, _line: &LogLine) {}
+}
+
+#[cfg(feature = "ssr")]
+pub use self::tracing_observer::TracingObserver;
+
+#[cfg(feature = "ssr")]
+mod tracing_observer {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use tracing::Span;
+
+    use super::*;
+
+    /// Exports a client's activity to `tracing`.
+    ///
+    /// Queries get a `convex.query` span from subscription to first result
+    /// and mutations a `convex.mutation` span from being sent to completing.
+    /// Subscriptions, transitions, mutations and reconnects are counted with
+    /// `monotonic_counter.*` fields. Pair it with a
+    /// [`TracingLogSink`](crate::logs::TracingLogSink) for the functions'
+    /// logs.
+    #[derive(Default)]
+    pub struct TracingObserver {
+        queries: Mutex<BTreeMap<QueryId, Span>>,
+        mutations: Mutex<BTreeMap<RequestId, Span>>,
+    }
+
+    impl TracingObserver {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    impl ClientObserver for TracingObserver {
+        fn on_subscribe(&self, query_id: QueryId, udf_path: &CanonicalizedUdfPath) {
+            tracing::info!(monotonic_counter.convex_subscriptions = 1, %udf_path);
+            let span = tracing::info_span!("convex.query", %udf_path, %query_id);
+            self.queries.lock().unwrap().insert(query_id, span);
+        }
+
+        fn on_first_result(&self, query_id: QueryId) {
+            // Dropping the span closes it.
+            if let Some(span) = self.queries.lock().unwrap().remove(&query_id) {
+                span.in_scope(|| tracing::debug!("first result"));
+            }
+        }
+
+        fn on_unsubscribe(&self, query_id: QueryId) {
+            self.queries.lock().unwrap().remove(&query_id);
+        }
+
+        fn on_transition(&self, ts: Timestamp, updated_queries: usize) {
+            tracing::debug!(monotonic_counter.convex_transitions = 1, %ts, updated_queries);
+        }
+
+        fn on_mutation_sent(&self, request_id: RequestId, udf_path: &CanonicalizedUdfPath) {
+            tracing::info!(monotonic_counter.convex_mutations = 1, %udf_path);
+            let span = tracing::info_span!("convex.mutation", %udf_path, ?request_id);
+            self.mutations.lock().unwrap().insert(request_id, span);
+        }
+
+        fn on_mutation_acknowledged(&self, request_id: RequestId) {
+            if let Some(span) = self.mutations.lock().unwrap().get(&request_id) {
+                span.in_scope(|| tracing::debug!("acknowledged"));
+            }
+        }
+
+        fn on_mutation_observed(&self, request_id: RequestId) {
+            if let Some(span) = self.mutations.lock().unwrap().remove(&request_id) {
+                span.in_scope(|| tracing::debug!("observed"));
+            }
+        }
+
+        fn on_reconnect(&self, reason: &str) {
+            tracing::warn!(monotonic_counter.convex_reconnects = 1, reason);
+        }
+    }
+}
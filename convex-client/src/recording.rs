@@ -0,0 +1,306 @@
+//! Recording the traffic between the client and the deployment, and replaying
+//! it to reproduce a session.
+//!
+//! A recording is newline-delimited JSON, one [`RecordedFrame`] per line:
+//!
+//! ```text
+//! {"timestampMs":1718000000000,"type":"sent","message":{"type":"ModifyQuerySet",...}}
+//! {"timestampMs":1718000000042,"type":"received","message":{"type":"Transition",...}}
+//! ```
+//!
+//! Auth tokens and admin keys are replaced by [`REDACTED_TOKEN`], so a
+//! recording can be attached to a bug report.
+
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use convex_sync_types::{AuthenticationToken, ClientMessage, ServerMessage};
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use leptos::logging::{error, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::runtime::Runtime;
+use crate::websocket::{ProtocolResponse, ReconnectRequest, SyncProtocol};
+
+/// One line of a recording.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedFrame {
+    /// When the frame was sent or received, in milliseconds since the Unix
+    /// epoch.
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub event: ProtocolEvent,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ProtocolEvent {
+    /// A `ClientMessage` the client sent, as JSON.
+    Sent { message: Value },
+    /// A `ServerMessage` the client received, as JSON.
+    Received { message: Value },
+    /// The connection failed.
+    Failure,
+    /// The client reconnected after a failure.
+    Reconnect { reason: String },
+}
+
+/// Writes every frame of a client's sync protocol to `out` as it is sent or
+/// received.
+///
+/// Cloning it shares the output.
+#[derive(Clone)]
+pub struct ProtocolRecorder {
+    out: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl ProtocolRecorder {
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Self {
+            out: Arc::new(Mutex::new(Box::new(out))),
+        }
+    }
+
+    /// Record to the file at `path`, replacing it. Lines are flushed as they
+    /// are written, so the recording survives the process crashing.
+    #[cfg(feature = "ssr")]
+    pub fn create(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::new(std::io::LineWriter::new(file)))
+    }
+
+    fn record(&self, event: ProtocolEvent) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let frame = RecordedFrame {
+            timestamp_ms,
+            event,
+        };
+        let mut line = serde_json::to_string(&frame).expect("recorded frames are JSON");
+        line.push('\n');
+        let Ok(mut out) = self.out.lock() else {
+            return;
+        };
+        if let Err(err) = out.write_all(line.as_bytes()) {
+            warn!("Failed to record a protocol frame: {err}");
+        }
+    }
+
+    fn record_response(&self, response: &ProtocolResponse) {
+        self.record(match response {
+            ProtocolResponse::ServerMessage(message) => ProtocolEvent::Received {
+                message: message.clone().into(),
+            },
+            ProtocolResponse::Failure => ProtocolEvent::Failure,
+        });
+    }
+
+    /// A sender for a protocol to deliver its responses to, recording them
+    /// before passing them on to `on_response`.
+    pub(crate) fn tap<R: Runtime>(
+        &self,
+        mut on_response: mpsc::Sender<ProtocolResponse>,
+    ) -> mpsc::Sender<ProtocolResponse> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let recorder = self.clone();
+        R::spawn(async move {
+            while let Some(response) = receiver.next().await {
+                recorder.record_response(&response);
+                if on_response.send(response).await.is_err() {
+                    break;
+                }
+            }
+        });
+        sender
+    }
+}
+
+/// A [`SyncProtocol`] that records the messages sent through `P`.
+///
+/// Responses are recorded separately, by opening `P` with a
+/// [`ProtocolRecorder::tap`]ped sender.
+pub(crate) struct RecordingProtocol<P> {
+    inner: P,
+    recorder: Option<ProtocolRecorder>,
+}
+
+impl<P: SyncProtocol> RecordingProtocol<P> {
+    pub(crate) fn new(inner: P, recorder: Option<ProtocolRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl<P: SyncProtocol> SyncProtocol for RecordingProtocol<P> {
+    async fn send(&mut self, message: ClientMessage) -> anyhow::Result<()> {
+        if let Some(recorder) = &self.recorder {
+            match Value::try_from(redact(message.clone())) {
+                Ok(message) => recorder.record(ProtocolEvent::Sent { message }),
+                Err(err) => warn!("Failed to record a client message: {err}"),
+            }
+        }
+        self.inner.send(message).await
+    }
+
+    async fn reconnect(&mut self, request: ReconnectRequest) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(ProtocolEvent::Reconnect {
+                reason: request.reason.clone(),
+            });
+        }
+        self.inner.reconnect(request).await
+    }
+}
+
+/// What a recording keeps of the token in an `Authenticate` message, so that
+/// recordings can be shared without leaking credentials.
+pub const REDACTED_TOKEN: &str = "[redacted]";
+
+/// `message` with any token it carries replaced by [`REDACTED_TOKEN`].
+fn redact(message: ClientMessage) -> ClientMessage {
+    match message {
+        ClientMessage::Authenticate {
+            base_version,
+            token,
+        } => ClientMessage::Authenticate {
+            base_version,
+            token: match token {
+                AuthenticationToken::Admin(_, acting_as) => {
+                    AuthenticationToken::Admin(REDACTED_TOKEN.to_string(), acting_as)
+                }
+                AuthenticationToken::User(_) => {
+                    AuthenticationToken::User(REDACTED_TOKEN.to_string())
+                }
+                AuthenticationToken::None => AuthenticationToken::None,
+            },
+        },
+        message => message,
+    }
+}
+
+/// A session recorded by a [`ProtocolRecorder`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl FromStr for Recording {
+    type Err = anyhow::Error;
+
+    /// Parse a newline-delimited JSON recording. Blank lines are skipped.
+    fn from_str(ndjson: &str) -> anyhow::Result<Self> {
+        let frames = ndjson
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| format!("Bad frame on line {}", i + 1))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { frames })
+    }
+}
+
+enum ReplayRequest {
+    Send(Box<ClientMessage>),
+    Reconnect(ReconnectRequest),
+}
+
+/// A [`SyncProtocol`] that plays a [`Recording`] back instead of talking to a
+/// deployment.
+///
+/// Received frames are delivered in their recorded order relative to the sent
+/// ones: each waits for the client to send every message recorded before it.
+/// The client's messages are expected to match the recording; a mismatch is
+/// logged, as it means the replay diverged from the recorded session.
+pub(crate) struct ReplayProtocol {
+    requests: mpsc::UnboundedSender<ReplayRequest>,
+}
+
+impl ReplayProtocol {
+    pub(crate) fn start<R: Runtime>(
+        recording: Recording,
+        mut on_response: mpsc::Sender<ProtocolResponse>,
+    ) -> Self {
+        let (requests, mut request_receiver) = mpsc::unbounded();
+        R::spawn(async move {
+            for frame in recording.frames {
+                let response = match frame.event {
+                    ProtocolEvent::Sent { message: expected } => {
+                        match request_receiver.next().await {
+                            Some(ReplayRequest::Send(message)) => {
+                                let message = Value::try_from(redact(*message)).unwrap_or_default();
+                                if message != expected {
+                                    warn!(
+                                        "Replay diverged: the client sent {message} instead of \
+                                         {expected}"
+                                    );
+                                }
+                            }
+                            Some(ReplayRequest::Reconnect(request)) => warn!(
+                                "Replay diverged: the client reconnected ({}) instead of sending \
+                                 {expected}",
+                                request.reason
+                            ),
+                            None => return,
+                        }
+                        continue;
+                    }
+                    ProtocolEvent::Reconnect { .. } => {
+                        loop {
+                            match request_receiver.next().await {
+                                Some(ReplayRequest::Reconnect(_)) => break,
+                                Some(ReplayRequest::Send(message)) => warn!(
+                                    "Replay diverged: the client sent {:?} instead of \
+                                     reconnecting",
+                                    redact(*message)
+                                ),
+                                None => return,
+                            }
+                        }
+                        continue;
+                    }
+                    ProtocolEvent::Received { message } => match ServerMessage::try_from(message) {
+                        Ok(message) => ProtocolResponse::ServerMessage(message),
+                        Err(err) => {
+                            error!("Skipping an unreadable recorded server message: {err}");
+                            continue;
+                        }
+                    },
+                    ProtocolEvent::Failure => ProtocolResponse::Failure,
+                };
+                if on_response.send(response).await.is_err() {
+                    return;
+                }
+            }
+            // The recording is over. Keep the response channel open until
+            // the client goes away, so the worker doesn't see the protocol
+            // fail.
+            while request_receiver.next().await.is_some() {}
+        });
+        Self { requests }
+    }
+}
+
+#[async_trait]
+impl SyncProtocol for ReplayProtocol {
+    async fn send(&mut self, message: ClientMessage) -> anyhow::Result<()> {
+        self.requests
+            .unbounded_send(ReplayRequest::Send(Box::new(message)))
+            .map_err(|_| anyhow!("Replay stopped"))
+    }
+
+    async fn reconnect(&mut self, request: ReconnectRequest) {
+        let _ = self
+            .requests
+            .unbounded_send(ReplayRequest::Reconnect(request));
+    }
+}
@@ -0,0 +1,53 @@
+use std::future::Future;
+use std::time::Duration;
+
+/// The async runtime the client's background tasks run on.
+///
+/// The worker and the websocket manager are generic over it, so the same
+/// code drives the client on tokio and in the browser.
+pub trait Runtime: Send + Sync + 'static {
+    /// Run `future` in the background until it completes.
+    fn spawn(future: impl Future<Output = ()> + Send + 'static);
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+    /// A random number in `[0, 1)`, used to jitter backoffs.
+    fn random() -> f64;
+}
+
+/// Runs the client on the ambient tokio runtime.
+#[cfg(feature = "ssr")]
+pub struct TokioRuntime;
+
+#[cfg(feature = "ssr")]
+impl Runtime for TokioRuntime {
+    fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+        tokio::spawn(future);
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+
+    fn random() -> f64 {
+        use rand::Rng;
+        rand::rng().random()
+    }
+}
+
+/// Runs the client on the browser's event loop.
+pub struct BrowserRuntime;
+
+impl Runtime for BrowserRuntime {
+    fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+        wasm_bindgen_futures::spawn_local(future);
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        // Browser timers are tied to the page's only thread, which is also
+        // the only thread that ever polls the client's futures.
+        send_wrapper::SendWrapper::new(gloo_timers::future::sleep(duration))
+    }
+
+    fn random() -> f64 {
+        js_sys::Math::random()
+    }
+}
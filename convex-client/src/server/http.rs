@@ -0,0 +1,213 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use convex_sync_types::UdfPath;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use url::Url;
+
+use super::VERSION;
+use crate::base::query_result::FunctionResult;
+use crate::leptos::{Mutation, Query};
+use crate::logs::{FunctionKind, LogLine, LogSink, default_log_sink};
+use crate::value::from_json;
+use crate::{ConvexError, TimeoutError};
+
+/// A stateless client running queries, mutations and actions through the
+/// deployment's HTTP API, one request each.
+///
+/// Unlike [`ConvexClient`](super::ConvexClient), it keeps no connection or
+/// subscriptions, so concurrent server functions don't queue behind a shared
+/// worker, nor subscribe to a query only to read it once. Query results are
+/// read at the latest timestamp and aren't consistent with each other.
+///
+/// Cloning is cheap and shares the connection pool, so clone it to call a
+/// function with another user's auth:
+///
+/// ```no_run
+/// # use convex_client::server::ConvexHttpClient;
+/// # async fn run(client: &ConvexHttpClient, token: String) -> anyhow::Result<()> {
+/// let mut client = client.clone();
+/// client.set_auth(Some(token));
+/// let result = client.action("messages:sendGif", serde_json::json!({})).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ConvexHttpClient {
+    http: reqwest::Client,
+    deployment_url: Url,
+    client_id: String,
+    /// The `Authorization` header to send, if any.
+    authorization: Option<String>,
+    log_sink: Arc<dyn LogSink>,
+}
+
+/// The body the HTTP API answers function calls with.
+#[derive(Deserialize)]
+#[serde(
+    tag = "status",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+enum FunctionResponse {
+    Success {
+        value: Value,
+        #[serde(default)]
+        log_lines: Vec<String>,
+    },
+    Error {
+        error_message: String,
+        error_data: Option<Value>,
+        #[serde(default)]
+        log_lines: Vec<String>,
+    },
+}
+
+impl ConvexHttpClient {
+    /// Constructs a new client for calling the functions of
+    /// `deployment_url`.
+    pub fn new(deployment_url: &str) -> anyhow::Result<Self> {
+        let deployment_url = Url::parse(deployment_url)?;
+        if !matches!(deployment_url.scheme(), "http" | "https") {
+            anyhow::bail!(
+                "Unknown scheme {}. Expected http or https.",
+                deployment_url.scheme()
+            );
+        }
+        Ok(Self {
+            http: reqwest::Client::new(),
+            deployment_url,
+            client_id: format!("rust-{}", VERSION.unwrap_or("unknown")),
+            authorization: None,
+            log_sink: default_log_sink(),
+        })
+    }
+
+    /// Send the lines the functions log to `log_sink`. Debug builds print
+    /// them to stdout and release builds drop them by default.
+    pub fn with_log_sink(mut self, log_sink: impl LogSink + 'static) -> Self {
+        self.log_sink = Arc::new(log_sink);
+        self
+    }
+
+    /// Set auth for the following calls, with a token from your auth
+    /// provider. `None` unsets it.
+    pub fn set_auth(&mut self, token: Option<String>) {
+        self.authorization = token.map(|token| format!("Bearer {token}"));
+    }
+
+    /// Call functions as a deployment admin, with a deploy key from the
+    /// Convex dashboard. Not typically required.
+    #[doc(hidden)]
+    pub fn set_admin_auth(&mut self, deploy_key: String) {
+        self.authorization = Some(format!("Convex {deploy_key}"));
+    }
+
+    /// Run the query `query` once and decode its result.
+    pub async fn query<F, Q>(&self, query: Q) -> anyhow::Result<F>
+    where
+        F: DeserializeOwned + Send + Sync + 'static,
+        Q: Query<F> + Serialize,
+    {
+        let value = self
+            .query_result(&query.name(), query.args()?)
+            .await?
+            .into_result()?;
+        Ok(from_json(value)?)
+    }
+
+    /// Run the query `name` with `args` once, returning its raw
+    /// [`FunctionResult`].
+    pub async fn query_result(&self, name: &str, args: Value) -> anyhow::Result<FunctionResult> {
+        self.run(FunctionKind::Query, name, args, None).await
+    }
+
+    /// Run `mutation`, failing with a [`TimeoutError`] if it doesn't
+    /// complete within [`Mutation::timeout`].
+    ///
+    /// Its writes are visible to the queries run once it completes.
+    pub async fn mutation<M: Mutation>(&self, mutation: M) -> anyhow::Result<FunctionResult> {
+        self.run(
+            FunctionKind::Mutation,
+            &mutation.name(),
+            mutation.args()?,
+            mutation.timeout(),
+        )
+        .await
+    }
+
+    /// Run the action `name` with `args`.
+    pub async fn action(&self, name: &str, args: Value) -> anyhow::Result<FunctionResult> {
+        self.run(FunctionKind::Action, name, args, None).await
+    }
+
+    async fn run(
+        &self,
+        kind: FunctionKind,
+        name: &str,
+        args: Value,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<FunctionResult> {
+        let udf_path: UdfPath = name.parse()?;
+        let mut url = self.deployment_url.clone();
+        url.set_path(&format!("api/{kind}"));
+        let mut request = self
+            .http
+            .post(url)
+            .header("Convex-Client", &self.client_id)
+            .json(&json!({ "path": name, "args": args, "format": "json" }));
+        if let Some(authorization) = &self.authorization {
+            request = request.header(reqwest::header::AUTHORIZATION, authorization);
+        }
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let response = match request.send().await {
+            Err(err) if err.is_timeout() => {
+                return Err(TimeoutError {
+                    udf_path: name.to_string(),
+                    timeout: timeout.unwrap_or_default(),
+                }
+                .into());
+            }
+            response => response?,
+        };
+        let status = response.status();
+        let body = response.text().await?;
+        // Function errors come with a status of their own, other failures
+        // with a body that isn't a function response.
+        let Ok(response) = serde_json::from_str::<FunctionResponse>(&body) else {
+            anyhow::bail!("{name} failed with {status}: {body}");
+        };
+        let (result, log_lines) = match response {
+            FunctionResponse::Success { value, log_lines } => {
+                (FunctionResult::Value(value), log_lines)
+            }
+            FunctionResponse::Error {
+                error_message,
+                error_data: Some(data),
+                log_lines,
+            } => (
+                FunctionResult::ConvexError(ConvexError {
+                    message: error_message,
+                    data,
+                }),
+                log_lines,
+            ),
+            FunctionResponse::Error {
+                error_message,
+                error_data: None,
+                log_lines,
+            } => (FunctionResult::ErrorMessage(error_message), log_lines),
+        };
+        let udf_path = udf_path.canonicalize();
+        for line in log_lines {
+            self.log_sink
+                .log(LogLine::new(kind, udf_path.clone(), &args, line));
+        }
+        Ok(result)
+    }
+}
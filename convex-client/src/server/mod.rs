@@ -1,37 +1,58 @@
-use anyhow::anyhow;
-use futures::SinkExt;
+use anyhow::Context;
+use futures::{FutureExt, SinkExt};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+mod http;
+mod pool;
 mod subscription;
-mod worker;
 
-use std::{convert::Infallible, sync::Arc};
+use std::future::Future;
+use std::{sync::Arc, time::Duration};
 
 use convex_sync_types::{AuthenticationToken, UdfPath, UserIdentityAttributes};
 use futures::StreamExt;
 use futures::channel::mpsc;
 use futures::channel::oneshot;
 use serde_json::Value;
-use tokio::{sync::broadcast, task::JoinHandle};
-use tokio_stream::wrappers::BroadcastStream;
 use url::Url;
 
-use crate::base::BaseConvexClient;
+use crate::auth::TokenFetcher;
+use crate::base::mutation_store::MutationStore;
 use crate::base::query_result::FunctionResult;
 use crate::base::query_result::QueryResults;
+use crate::base::{BaseConvexClient, QueryKeepAlive};
+use crate::connection::{ConnectionTracker, OnAuthError};
 use crate::leptos::Mutation;
+use crate::leptos::PaginatedQuery;
+use crate::leptos::PaginatedResults;
 use crate::leptos::Query;
+use crate::leptos::drive_paginated_query;
+use crate::logs::{LogSink, default_log_sink};
+use crate::observer::ClientObserver;
+use crate::recording::{ProtocolRecorder, Recording, RecordingProtocol, ReplayProtocol};
+use crate::runtime::{Runtime, TokioRuntime};
+use crate::subscription::ClientHandle;
+use crate::subscription::PendingRequest;
+use crate::subscription::QuerySetSubscription;
+use crate::subscription::QuerySubscription;
+use crate::value::from_json;
+use crate::websocket::DEFAULT_INACTIVITY_TIMEOUT;
+use crate::websocket::ProtocolResponse;
 use crate::websocket::SyncProtocol;
 use crate::websocket::WebSocketState;
 use crate::websocket::server::WebSocketManager;
-
-use self::subscription::QuerySetSubscription;
-use self::subscription::QuerySubscription;
-use self::worker::ActionRequest;
-use self::worker::AuthenticateRequest;
-use self::worker::ClientRequest;
-use self::worker::MutationRequest;
-use self::worker::SubscribeRequest;
+use crate::worker::ActionRequest;
+use crate::worker::AuthenticateRequest;
+use crate::worker::ClientRequest;
+use crate::worker::MutationRequest;
+use crate::worker::SubscribeRequest;
+use crate::worker::WORKER_STOPPED;
+use crate::worker::worker;
+use crate::{AuthError, ConvexClientError};
+
+pub use self::http::ConvexHttpClient;
+pub use self::pool::{ConvexClientPool, RenderingUser};
+use self::subscription::PaginatedQuerySubscription;
 
 const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
 
@@ -64,9 +85,10 @@ const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
 /// ## Examples
 /// For example code, please refer to the examples directory.
 pub struct ConvexClient {
-    listen_handle: Option<Arc<JoinHandle<Infallible>>>,
+    shutdown_listen: ClientHandle,
     request_sender: mpsc::UnboundedSender<ClientRequest>,
-    watch_receiver: broadcast::Receiver<QueryResults>,
+    watch_receiver: async_broadcast::Receiver<QueryResults>,
+    request_timeout: Option<Duration>,
 }
 
 /// Clone the [`ConvexClient`], sharing the connection and outstanding
@@ -74,23 +96,10 @@ pub struct ConvexClient {
 impl Clone for ConvexClient {
     fn clone(&self) -> Self {
         Self {
-            listen_handle: self.listen_handle.clone(),
+            shutdown_listen: self.shutdown_listen.clone(),
             request_sender: self.request_sender.clone(),
-            watch_receiver: self.watch_receiver.resubscribe(),
-        }
-    }
-}
-
-/// Drop the [`ConvexClient`]. When the final reference to the [`ConvexClient`]
-/// is dropped, the connection is cleaned up.
-impl Drop for ConvexClient {
-    fn drop(&mut self) {
-        if let Ok(j_handle) = Arc::try_unwrap(
-            self.listen_handle
-                .take()
-                .expect("INTERNAL BUG: listen handle should never be none"),
-        ) {
-            j_handle.abort()
+            watch_receiver: self.watch_receiver.new_receiver(),
+            request_timeout: self.request_timeout,
         }
     }
 }
@@ -111,42 +120,90 @@ impl ConvexClient {
     }
 
     #[doc(hidden)]
-    pub async fn new_from_builder(builder: ConvexClientBuilder) -> anyhow::Result<Self> {
+    pub async fn new_from_builder(mut builder: ConvexClientBuilder) -> anyhow::Result<Self> {
         let client_id = builder
             .client_id
+            .take()
             .unwrap_or_else(|| format!("rust-{}", VERSION.unwrap_or("unknown")));
         let ws_url = deployment_to_ws_url(builder.deployment_url.as_str().try_into()?)?;
 
         // Channels for the `listen` background thread
         let (response_sender, response_receiver) = mpsc::channel(1);
-        let (request_sender, request_receiver) = mpsc::unbounded();
-
-        // Listener for when each transaction completes
-        let (watch_sender, watch_receiver) = broadcast::channel(1);
-
-        let base_client = BaseConvexClient::new();
-
+        let (state_change_sender, state_change_receiver) = mpsc::channel(8);
+        let on_response = match &builder.recorder {
+            Some(recorder) => recorder.tap::<TokioRuntime>(response_sender),
+            None => response_sender,
+        };
         let protocol = WebSocketManager::open(
             ws_url,
-            response_sender,
-            builder.on_state_change,
+            on_response,
+            Some(state_change_sender),
             client_id.as_str(),
+            builder.inactivity_timeout,
         )
         .await?;
+        let protocol = RecordingProtocol::new(protocol, builder.recorder.take());
+        Ok(Self::start(
+            builder,
+            protocol,
+            response_receiver,
+            state_change_receiver,
+        ))
+    }
+
+    /// Run the worker over `protocol`, which delivers its responses to
+    /// `response_receiver` and its state changes to `state_change_receiver`.
+    fn start<P: SyncProtocol + 'static>(
+        builder: ConvexClientBuilder,
+        protocol: P,
+        response_receiver: mpsc::Receiver<ProtocolResponse>,
+        state_change_receiver: mpsc::Receiver<WebSocketState>,
+    ) -> Self {
+        let (request_sender, request_receiver) = mpsc::unbounded();
 
-        let listen_handle = tokio::spawn(worker::worker(
+        // Listener for when each transaction completes
+        let (mut watch_sender, watch_receiver) = async_broadcast::broadcast(1);
+        watch_sender.set_overflow(true);
+
+        // Nothing reads the connection state on the server, but the worker
+        // still forwards websocket state changes to `on_state_change`.
+        let (connection_state_sender, _) = async_broadcast::broadcast(1);
+        let connection = ConnectionTracker::new(
+            state_change_receiver,
+            builder.on_state_change,
+            builder.on_auth_error,
+            connection_state_sender,
+        );
+
+        // Dropping the last clone of the client stops the worker, which in
+        // turn closes the connection.
+        let (shutdown_sender, shutdown_listen) = oneshot::channel();
+
+        let mut base_client = BaseConvexClient::new();
+        base_client.set_query_keep_alive(builder.query_keep_alive);
+        if let Some(observer) = builder.observer {
+            base_client.set_observer(observer);
+        }
+        base_client.set_log_sink(builder.log_sink);
+        if let Some(mutation_store) = builder.mutation_store {
+            base_client.set_mutation_store(mutation_store);
+        }
+
+        TokioRuntime::spawn(worker::<TokioRuntime, _>(
             response_receiver,
             request_receiver,
             watch_sender,
             base_client,
+            shutdown_listen,
             protocol,
+            connection,
         ));
-        let client = ConvexClient {
-            listen_handle: Some(Arc::new(listen_handle)),
+        ConvexClient {
+            shutdown_listen: Arc::new(shutdown_sender),
             request_sender,
             watch_receiver,
-        };
-        Ok(client)
+            request_timeout: builder.request_timeout,
+        }
     }
 
     /// Subscribe to the results of query `name` called with `args`.
@@ -169,11 +226,19 @@ impl ConvexClient {
     /// }
     /// # Ok(())
     /// # }
-    async fn subscribe(&mut self, name: &str, args: Value) -> anyhow::Result<QuerySubscription> {
+    pub async fn subscribe(
+        &mut self,
+        name: &str,
+        args: Value,
+    ) -> anyhow::Result<QuerySubscription> {
         let (tx, rx) = oneshot::channel();
 
         let udf_path = name.parse()?;
-        let request = SubscribeRequest { udf_path, args };
+        let request = SubscribeRequest {
+            udf_path,
+            args,
+            seed: None,
+        };
 
         self.request_sender
             .send(ClientRequest::Subscribe(
@@ -183,18 +248,16 @@ impl ConvexClient {
             ))
             .await?;
 
-        let res = rx.await?;
-        Ok(res)
+        let mut subscription = rx.await?;
+        subscription.client = Some(self.shutdown_listen.clone());
+        Ok(subscription)
     }
 
-    /// Make a oneshot request to a query `name` with `args`.
-    ///
-    /// Returns a [`FunctionResult`] representing the result of the query.
+    /// Make a oneshot request to the query `query` and decode its result.
     ///
     /// This method is syntactic sugar for waiting for a single result on
-    /// a subscription.
-    /// It is equivalent to `client.subscribe(name,
-    /// args).await?.next().unwrap()`
+    /// a subscription. A function error keeps its kind, so a `ConvexError`
+    /// thrown by the query can be read with [`ConvexClientError::data_as`].
     ///
     /// ```no_run
     /// # use convex::ConvexClient;
@@ -206,25 +269,108 @@ impl ConvexClient {
     /// println!("{result:?}");
     /// # Ok(())
     /// # }
-    pub async fn query<F, Q>(&mut self, query: Q) -> anyhow::Result<F>
+    pub async fn query<F, Q>(&mut self, query: Q) -> Result<F, ConvexClientError>
     where
         F: DeserializeOwned + Send + Sync + 'static,
         Q: Query<F> + Serialize + Send + Sync + 'static + PartialEq + Clone,
     {
-        let result = self
-            .subscribe(&query.name(), query.args()?)
+        let name = query.name();
+        let result = self.query_result(&name, query.args()?).await?;
+        from_json::<F>(result.into_result()?)
+            .map_err(|err| ConvexClientError::Deserialization(format!("{name} query: {err}")))
+    }
+
+    /// Make a oneshot request to a query `name` with `args`, returning its
+    /// raw [`FunctionResult`] rather than decoding it like
+    /// [`query`](Self::query).
+    pub async fn query_result(
+        &mut self,
+        name: &str,
+        args: Value,
+    ) -> anyhow::Result<FunctionResult> {
+        self.subscribe(name, args)
             .await?
             .next()
             .await
-            .expect("INTERNAL BUG: Convex Client dropped prematurely.");
-        match result {
-            FunctionResult::Value(value) => match serde_json::from_value::<F>(value) {
-                Err(err) => Err(anyhow!("{err}")),
-                Ok(value) => Ok(value),
-            },
-            FunctionResult::ErrorMessage(err) => Err(anyhow!("{err}")),
-            FunctionResult::ConvexError(convex_error) => Err(anyhow!("{convex_error:?}")),
-        }
+            .context(WORKER_STOPPED)
+    }
+
+    /// Subscribe to the paginated query `query`, starting with a first page of
+    /// `initial_num_items`.
+    ///
+    /// Returns a [`PaginatedQuerySubscription`] yielding the items of every
+    /// loaded page each time one of them changes. Call
+    /// [`PaginatedQuerySubscription::load_more`] to subscribe to the next page.
+    ///
+    /// ```no_run
+    /// # use convex_client::leptos::PaginatedQuery;
+    /// # use convex_client::server::ConvexClient;
+    /// # use futures::StreamExt;
+    /// # use serde::Serialize;
+    /// # use serde_json::Value;
+    /// #[derive(Serialize)]
+    /// struct ListMessages {}
+    ///
+    /// impl PaginatedQuery<Value> for ListMessages {
+    ///     fn name(&self) -> String {
+    ///         "messages:list".into()
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let client = ConvexClient::new("https://cool-music-123.convex.cloud").await?;
+    /// let mut sub = client.paginated_query(ListMessages {}, 20);
+    /// while let Some(result) = sub.next().await {
+    ///     println!("{:?}", result?.results);
+    ///     sub.load_more(20);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn paginated_query<T, Q>(
+        &self,
+        query: Q,
+        initial_num_items: usize,
+    ) -> PaginatedQuerySubscription<T>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+        Q: PaginatedQuery<T> + Serialize + Send + Sync + 'static,
+    {
+        let (results_sender, results_receiver) = mpsc::unbounded();
+        let (load_more_sender, load_more_receiver) = mpsc::unbounded();
+        let client = self.clone();
+        let name = query.name();
+        tokio::spawn(async move {
+            let subscribe = move |args| {
+                let mut client = client.clone();
+                let name = name.clone();
+                async move { client.subscribe(&name, args).await }
+            };
+            let on_results = move |update: Result<PaginatedResults<Value>, ConvexClientError>| {
+                let update = update.map_err(anyhow::Error::from).and_then(|update| {
+                    let results = update
+                        .results
+                        .into_iter()
+                        .map(from_json::<T>)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(PaginatedResults {
+                        results,
+                        status: update.status,
+                    })
+                });
+                let _ = results_sender.unbounded_send(update);
+            };
+            drive_paginated_query(
+                move |options| query.args(options),
+                initial_num_items,
+                subscribe,
+                load_more_receiver,
+                on_results,
+            )
+            .await;
+        });
+        PaginatedQuerySubscription::new(results_receiver, load_more_sender)
     }
 
     /// Perform a mutation `name` with `args` and return a future
@@ -243,6 +389,13 @@ impl ConvexClient {
     /// println!("{result:?}");
     /// # Ok(())
     /// # }
+    /// ```
+    ///
+    /// Fails with a [`TimeoutError`](crate::TimeoutError) if the mutation
+    /// doesn't complete within [`Mutation::timeout`], or the client's
+    /// [request timeout](ConvexClientBuilder::with_request_timeout).
+    /// Dropping the returned future before the mutation is sent to the
+    /// server cancels it.
     pub async fn mutation<M>(&mut self, mutation: M) -> anyhow::Result<FunctionResult>
     where
         M: Mutation,
@@ -253,14 +406,26 @@ impl ConvexClient {
         let request = MutationRequest {
             udf_path,
             args: mutation.args()?,
+            optimistic_update: mutation.optimistic_update(),
+            idempotency_key: mutation.idempotency_key(),
         };
 
         self.request_sender
             .send(ClientRequest::Mutation(request, tx))
             .await?;
 
-        let res = rx.await?;
-        Ok(res.await?)
+        let (request_id, result) = rx.await?;
+        PendingRequest {
+            request_id,
+            result,
+            request_sender: self.request_sender.clone(),
+            completed: false,
+        }
+        .wait::<TokioRuntime>(
+            &mutation.name(),
+            mutation.timeout().or(self.request_timeout),
+        )
+        .await
     }
 
     /// Perform an action `name` with `args` and return a future
@@ -280,6 +445,19 @@ impl ConvexClient {
     /// # Ok(())
     /// # }
     pub async fn action(&mut self, name: &str, args: Value) -> anyhow::Result<FunctionResult> {
+        self.action_with_timeout(name, args, None).await
+    }
+
+    /// Like [`action`](Self::action), failing with a
+    /// [`TimeoutError`](crate::TimeoutError) if the action doesn't complete
+    /// within `timeout`, or within the client's
+    /// [request timeout](ConvexClientBuilder::with_request_timeout) if `None`.
+    pub async fn action_with_timeout(
+        &mut self,
+        name: &str,
+        args: Value,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<FunctionResult> {
         let (tx, rx) = oneshot::channel();
 
         let udf_path: UdfPath = name.parse()?;
@@ -289,8 +467,15 @@ impl ConvexClient {
             .send(ClientRequest::Action(request, tx))
             .await?;
 
-        let res = rx.await?;
-        Ok(res.await?)
+        let (request_id, result) = rx.await?;
+        PendingRequest {
+            request_id,
+            result,
+            request_sender: self.request_sender.clone(),
+            completed: false,
+        }
+        .wait::<TokioRuntime>(name, timeout.or(self.request_timeout))
+        .await
     }
 
     /// Get a consistent view of the results of multiple queries (query set).
@@ -324,7 +509,10 @@ impl ConvexClient {
     /// # Ok(())
     /// # }
     pub fn watch_all(&self) -> QuerySetSubscription {
-        QuerySetSubscription::new(BroadcastStream::new(self.watch_receiver.resubscribe()))
+        QuerySetSubscription::new(
+            self.watch_receiver.new_receiver(),
+            self.shutdown_listen.clone(),
+        )
     }
 
     /// Set auth for use when calling Convex functions.
@@ -339,10 +527,31 @@ impl ConvexClient {
                 Some(token) => AuthenticationToken::User(token),
             },
         };
-        self.request_sender
-            .send(ClientRequest::Authenticate(req))
-            .await
-            .expect("INTERNAL BUG: Worker has gone away");
+        self.send_auth_request(ClientRequest::Authenticate(Box::new(req)))
+            .await;
+    }
+
+    /// Authenticate with the tokens `fetch_token` returns, refreshing them
+    /// before they expire.
+    ///
+    /// The client fetches a token right away, then a fresh one shortly
+    /// before each expires, going by the JWT's `exp` claim. When the
+    /// deployment rejects a token, it fetches one more before giving up and
+    /// calling the [`with_on_auth_error`](ConvexClientBuilder::with_on_auth_error)
+    /// callback. The fetches for fresh tokens pass `force_refresh`, asking
+    /// the auth provider not to return a cached one. Returning `None` signs
+    /// out.
+    ///
+    /// Calling [`set_auth`](Self::set_auth) stops the fetching.
+    pub async fn set_auth_fetcher<F, Fut>(&mut self, fetch_token: F)
+    where
+        F: Fn(bool) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        let fetch_token: TokenFetcher =
+            Arc::new(move |force_refresh| fetch_token(force_refresh).boxed());
+        self.send_auth_request(ClientRequest::FetchAuth(fetch_token))
+            .await;
     }
 
     /// Set admin auth for use when calling Convex functions as a deployment
@@ -360,10 +569,14 @@ impl ConvexClient {
         let req = AuthenticateRequest {
             token: AuthenticationToken::Admin(deploy_key, acting_as),
         };
-        self.request_sender
-            .send(ClientRequest::Authenticate(req))
-            .await
-            .expect("INTERNAL BUG: Worker has gone away");
+        self.send_auth_request(ClientRequest::Authenticate(Box::new(req)))
+            .await;
+    }
+
+    async fn send_auth_request(&mut self, request: ClientRequest) {
+        // Fails only once a fatal error stopped the worker, when there is
+        // nothing left to authenticate.
+        let _ = self.request_sender.send(request).await;
     }
 }
 
@@ -385,6 +598,14 @@ pub struct ConvexClientBuilder {
     deployment_url: String,
     client_id: Option<String>,
     on_state_change: Option<mpsc::Sender<WebSocketState>>,
+    request_timeout: Option<Duration>,
+    mutation_store: Option<Box<dyn MutationStore>>,
+    query_keep_alive: QueryKeepAlive,
+    recorder: Option<ProtocolRecorder>,
+    observer: Option<Arc<dyn ClientObserver>>,
+    log_sink: Arc<dyn LogSink>,
+    on_auth_error: Option<OnAuthError>,
+    inactivity_timeout: Duration,
 }
 
 impl ConvexClientBuilder {
@@ -394,6 +615,14 @@ impl ConvexClientBuilder {
             deployment_url: deployment_url.to_string(),
             client_id: None,
             on_state_change: None,
+            request_timeout: None,
+            mutation_store: None,
+            query_keep_alive: QueryKeepAlive::default(),
+            recorder: None,
+            observer: None,
+            log_sink: default_log_sink(),
+            on_auth_error: None,
+            inactivity_timeout: DEFAULT_INACTIVITY_TIMEOUT,
         }
     }
 
@@ -410,6 +639,90 @@ impl ConvexClientBuilder {
         self
     }
 
+    /// Fail mutations and actions that get no response within `timeout`
+    /// with a [`TimeoutError`](crate::TimeoutError). By default they wait
+    /// indefinitely.
+    ///
+    /// A [`Mutation`] can override this with [`Mutation::timeout`].
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Keep mutations the server has not acknowledged yet in `store`, and
+    /// replay the ones a previous client left there. See
+    /// [`Mutation::idempotency_key`].
+    pub fn with_mutation_store(mut self, store: impl MutationStore + 'static) -> Self {
+        self.mutation_store = Some(Box::new(store));
+        self
+    }
+
+    /// Keep queries subscribed for `duration` after their last subscriber
+    /// drops, so that subscribing to them again returns the cached result
+    /// right away. At most `max_idle_queries` are kept; past that, the least
+    /// recently used are dropped early. Off by default.
+    pub fn with_query_keep_alive(mut self, duration: Duration, max_idle_queries: usize) -> Self {
+        self.query_keep_alive = QueryKeepAlive {
+            duration,
+            max_idle_queries,
+        };
+        self
+    }
+
+    /// Record every message the client sends and receives to `recorder`, to
+    /// [`replay`](Self::replay) the session later.
+    pub fn with_protocol_recorder(mut self, recorder: ProtocolRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Report the client's queries, mutations and reconnects to `observer`,
+    /// such as a [`TracingObserver`](crate::observer::TracingObserver).
+    pub fn with_observer(mut self, observer: impl ClientObserver + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Send the lines the functions log to `log_sink`, such as a
+    /// [`TracingLogSink`](crate::logs::TracingLogSink). Debug builds print
+    /// them to stdout and release builds drop them by default.
+    pub fn with_log_sink(mut self, log_sink: impl LogSink + 'static) -> Self {
+        self.log_sink = Arc::new(log_sink);
+        self
+    }
+
+    /// Call `on_auth_error` when the deployment rejects the auth token. The
+    /// client drops the token and reconnects unauthenticated; call
+    /// [`ConvexClient::set_auth`] with a fresh one to recover. Clients
+    /// authenticated with [`ConvexClient::set_auth_fetcher`] call it only
+    /// once a freshly fetched token is rejected too.
+    pub fn with_on_auth_error(
+        mut self,
+        on_auth_error: impl Fn(AuthError) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_auth_error = Some(Arc::new(on_auth_error));
+        self
+    }
+
+    /// Reconnect when the deployment sends nothing, not even a ping, for
+    /// `timeout`. Defaults to 30 seconds.
+    pub fn with_inactivity_timeout(mut self, timeout: Duration) -> Self {
+        self.inactivity_timeout = timeout;
+        self
+    }
+
+    /// Build a [`ConvexClient`] that plays `recording` back instead of
+    /// connecting to the deployment, to reproduce a recorded session.
+    ///
+    /// The client has to make the same requests as the recorded one for the
+    /// responses to line up.
+    pub async fn replay(self, recording: Recording) -> ConvexClient {
+        let (response_sender, response_receiver) = mpsc::channel(1);
+        let (_, state_change_receiver) = mpsc::channel(8);
+        let protocol = ReplayProtocol::start::<TokioRuntime>(recording, response_sender);
+        ConvexClient::start(self, protocol, response_receiver, state_change_receiver)
+    }
+
     /// Build the [`ConvexClient`] with the configured options.
     ///
     /// ```no_run
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use tokio::sync::{Mutex, OnceCell};
+use tokio::time::Instant;
+
+use super::{ConvexClient, ConvexClientBuilder};
+
+/// The default cap on the number of clients in a [`ConvexClientPool`].
+const DEFAULT_MAX_CLIENTS: usize = 100;
+/// How long a [`ConvexClientPool`] keeps a client nobody uses by default.
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+type UserTokenFetcher = Arc<dyn Fn(&str, bool) -> BoxFuture<'static, Option<String>> + Send + Sync>;
+type ConfigureClient = Arc<dyn Fn(ConvexClientBuilder) -> ConvexClientBuilder + Send + Sync>;
+
+/// A pool of [`ConvexClient`]s, each authenticated as one user, for server
+/// code that runs functions on behalf of users outside of their requests,
+/// like bots or push notifications.
+///
+/// [`client`](Self::client) hands out the user's client, connecting it on
+/// first use. Use its [`subscribe`](ConvexClient::subscribe) and
+/// [`watch_all`](ConvexClient::watch_all) for streams of results as that
+/// user. A client stays connected for as long as a clone of it or one of
+/// its subscriptions is alive; once only the pool holds it, it is closed
+/// within about twice the [idle timeout](Self::with_idle_timeout).
+///
+/// Every pooled client has a websocket of its own, so the pool holds at most
+/// [`with_max_clients`](Self::with_max_clients) of them, closing the least
+/// recently used idle client to make room for a new one.
+///
+/// Cloning is cheap and shares the pool.
+#[derive(Clone)]
+pub struct ConvexClientPool {
+    deployment_url: String,
+    fetch_token: UserTokenFetcher,
+    configure: Option<ConfigureClient>,
+    max_clients: usize,
+    idle_timeout: Duration,
+    clients: Arc<Mutex<PooledClients>>,
+}
+
+#[derive(Default)]
+struct PooledClients {
+    by_user: HashMap<String, PooledClient>,
+    /// Whether a [`reap_idle_clients`] task is looking after the pool.
+    reaping: bool,
+}
+
+struct PooledClient {
+    /// Set once the client has connected. Callers asking for the user's
+    /// client meanwhile wait on the cell rather than on the pool's lock.
+    client: Arc<OnceCell<ConvexClient>>,
+    last_used: Instant,
+    /// When the pool first found the client idle, if it still is.
+    idle_since: Option<Instant>,
+}
+
+impl PooledClient {
+    /// Whether only the pool holds the client, and nobody is connecting it.
+    /// Subscriptions and [`watch_all`](ConvexClient::watch_all) streams hold
+    /// the client's shutdown handle, so they count as uses.
+    fn is_idle(&self) -> bool {
+        Arc::strong_count(&self.client) == 1
+            && self
+                .client
+                .get()
+                .is_none_or(|client| Arc::strong_count(&client.shutdown_listen) == 1)
+    }
+}
+
+/// The user a server render is for, as context of the render next to a
+/// [`ConvexClientPool`]. Query results rendered on the server are then run
+/// with the pool's client for that user, so that queries reading the
+/// caller's identity see the same user the hydrated client will.
+///
+/// Without a pool, a render for a user leaves its queries to the hydrated
+/// client rather than run them signed out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderingUser(pub String);
+
+impl ConvexClientPool {
+    /// Constructs a pool of clients for `deployment_url`, authenticated with
+    /// the tokens `fetch_token` returns for a user id.
+    ///
+    /// `fetch_token` is called like the fetcher of
+    /// [`ConvexClient::set_auth_fetcher`], along with the id of the user.
+    pub fn new<F, Fut>(deployment_url: &str, fetch_token: F) -> Self
+    where
+        F: Fn(&str, bool) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        Self {
+            deployment_url: deployment_url.to_string(),
+            fetch_token: Arc::new(move |user_id, force_refresh| {
+                fetch_token(user_id, force_refresh).boxed()
+            }),
+            configure: None,
+            max_clients: DEFAULT_MAX_CLIENTS,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            clients: Arc::default(),
+        }
+    }
+
+    /// Hold at most `max_clients` clients, and so websockets, at once.
+    /// Defaults to 100.
+    pub fn with_max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = max_clients;
+        self
+    }
+
+    /// Close the clients nobody but the pool has held for `idle_timeout`.
+    /// Defaults to 5 minutes.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Build each client from the builder `configure` returns, e.g. to set a
+    /// log sink or an observer.
+    pub fn with_builder(
+        mut self,
+        configure: impl Fn(ConvexClientBuilder) -> ConvexClientBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.configure = Some(Arc::new(configure));
+        self
+    }
+
+    /// The client authenticated as `user_id`, connecting a new one if the
+    /// pool has none.
+    ///
+    /// Fails if the pool is full of clients in use.
+    pub async fn client(&self, user_id: &str) -> anyhow::Result<ConvexClient> {
+        let slot = self.slot(user_id).await?;
+        match slot.get_or_try_init(|| self.connect(user_id)).await {
+            Ok(client) => Ok(client.clone()),
+            Err(err) => {
+                // Give up the slot unless someone else is about to retry.
+                let mut clients = self.clients.lock().await;
+                if Arc::strong_count(&slot) == 2
+                    && clients
+                        .by_user
+                        .get(user_id)
+                        .is_some_and(|pooled| Arc::ptr_eq(&pooled.client, &slot))
+                {
+                    clients.by_user.remove(user_id);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// The cell holding the client of `user_id`, making room for an empty
+    /// one if the pool has none.
+    async fn slot(&self, user_id: &str) -> anyhow::Result<Arc<OnceCell<ConvexClient>>> {
+        let mut clients = self.clients.lock().await;
+        if let Some(pooled) = clients.by_user.get_mut(user_id) {
+            pooled.last_used = Instant::now();
+            pooled.idle_since = None;
+            return Ok(pooled.client.clone());
+        }
+        evict_idle(&mut clients.by_user, self.idle_timeout);
+        if clients.by_user.len() >= self.max_clients {
+            let Some(least_recently_used) = clients
+                .by_user
+                .iter()
+                .filter(|(_, pooled)| pooled.is_idle())
+                .min_by_key(|(_, pooled)| pooled.last_used)
+                .map(|(user_id, _)| user_id.clone())
+            else {
+                anyhow::bail!("All {} pooled clients are in use", self.max_clients);
+            };
+            clients.by_user.remove(&least_recently_used);
+        }
+
+        if !clients.reaping {
+            clients.reaping = true;
+            tokio::spawn(reap_idle_clients(
+                Arc::downgrade(&self.clients),
+                self.idle_timeout,
+            ));
+        }
+        let slot = Arc::new(OnceCell::new());
+        clients.by_user.insert(
+            user_id.to_string(),
+            PooledClient {
+                client: slot.clone(),
+                last_used: Instant::now(),
+                idle_since: None,
+            },
+        );
+        Ok(slot)
+    }
+
+    /// Connect a new client authenticated as `user_id`.
+    async fn connect(&self, user_id: &str) -> anyhow::Result<ConvexClient> {
+        let mut builder = ConvexClientBuilder::new(&self.deployment_url);
+        if let Some(configure) = &self.configure {
+            builder = configure(builder);
+        }
+        let mut client = builder.build().await?;
+        let fetch_token = self.fetch_token.clone();
+        let owner = user_id.to_string();
+        client
+            .set_auth_fetcher(move |force_refresh| fetch_token(&owner, force_refresh))
+            .await;
+        Ok(client)
+    }
+
+    /// Close the client of `user_id`, e.g. once they sign out. Clones of it
+    /// still in use keep it connected until they are dropped.
+    pub async fn remove(&self, user_id: &str) {
+        self.clients.lock().await.by_user.remove(user_id);
+    }
+
+    /// The number of clients in the pool.
+    pub async fn num_clients(&self) -> usize {
+        self.clients.lock().await.by_user.len()
+    }
+}
+
+/// Drop the clients found idle `idle_timeout` ago or earlier, and note when
+/// the others became idle.
+fn evict_idle(clients: &mut HashMap<String, PooledClient>, idle_timeout: Duration) {
+    let now = Instant::now();
+    clients.retain(|_, pooled| {
+        if !pooled.is_idle() {
+            pooled.idle_since = None;
+            return true;
+        }
+        let idle_since = *pooled.idle_since.get_or_insert(now);
+        now - idle_since < idle_timeout
+    });
+}
+
+/// Check for idle clients of a pool every `idle_timeout`, until the pool is
+/// dropped or empty.
+async fn reap_idle_clients(clients: Weak<Mutex<PooledClients>>, idle_timeout: Duration) {
+    loop {
+        tokio::time::sleep(idle_timeout).await;
+        let Some(clients) = clients.upgrade() else {
+            return;
+        };
+        let mut clients = clients.lock().await;
+        evict_idle(&mut clients.by_user, idle_timeout);
+        if clients.by_user.is_empty() {
+            clients.reaping = false;
+            return;
+        }
+    }
+}
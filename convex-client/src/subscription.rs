@@ -0,0 +1,192 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::{ops::Deref, pin::Pin};
+
+use convex_sync_types::StateVersion;
+use futures::channel::{mpsc, oneshot};
+use futures::future::{Either, select};
+use futures::{Stream, StreamExt, pin_mut, task};
+
+use crate::TimeoutError;
+use crate::base::SubscriberId;
+use crate::base::query_result::{FunctionResult, QueryResults};
+use crate::base::request_manager::RequestId;
+use crate::runtime::Runtime;
+use crate::worker::{CancelRequest, ClientRequest, UnsubscribeRequest};
+
+/// This structure represents a single subscription to a query with args.
+/// For convenience, [`QuerySubscription`] also implements
+/// [`Stream`]<[`FunctionResult`]>, giving a stream of results to the query.
+///
+/// It is returned by `ConvexClient::subscribe`. The subscription lives
+/// in the active query set for as long as this token stays in scope, and
+/// keeps the client connected meanwhile, like a clone of the client would.
+///
+/// For a consistent [`QueryResults`] of all your queries, use
+/// `ConvexClient::watch_all()` instead.
+pub struct QuerySubscription {
+    pub(crate) subscriber_id: SubscriberId,
+    pub(crate) request_sender: mpsc::UnboundedSender<ClientRequest>,
+    pub(crate) watch: async_broadcast::Receiver<QueryResults>,
+    pub(crate) initial: Option<FunctionResult>,
+    /// The [version](QueryResults::version) of `initial`.
+    pub(crate) initial_version: Option<StateVersion>,
+    /// The client's shutdown handle, set once the subscription is handed
+    /// out.
+    pub(crate) client: Option<ClientHandle>,
+}
+
+/// Held by every clone of a client and by its subscriptions. The worker stops
+/// once all are dropped.
+pub(crate) type ClientHandle = Arc<oneshot::Sender<()>>;
+impl QuerySubscription {
+    /// Returns an identifier for this subscription based on its query and args.
+    /// This identifier can be used to find the result within a
+    /// [`QuerySetSubscription`] as returned by `ConvexClient::watch_all()`
+    pub fn id(&self) -> &SubscriberId {
+        &self.subscriber_id
+    }
+}
+impl std::fmt::Debug for QuerySubscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuerySubscription")
+            .field("subscriber_id", &self.subscriber_id)
+            .finish()
+    }
+}
+impl Deref for QuerySubscription {
+    type Target = SubscriberId;
+
+    fn deref(&self) -> &SubscriberId {
+        &self.subscriber_id
+    }
+}
+impl Drop for QuerySubscription {
+    fn drop(&mut self) {
+        let _ = self
+            .request_sender
+            .unbounded_send(ClientRequest::Unsubscribe(UnsubscribeRequest {
+                subscriber_id: self.subscriber_id,
+            }));
+    }
+}
+/// A mutation or action handed to the worker, waiting for its result.
+///
+/// Dropping it before the result arrives cancels the request. A request
+/// still queued in the worker is discarded along with its optimistic update;
+/// one already sent to the server is left to run.
+pub(crate) struct PendingRequest {
+    pub(crate) request_id: RequestId,
+    pub(crate) result: oneshot::Receiver<FunctionResult>,
+    pub(crate) request_sender: mpsc::UnboundedSender<ClientRequest>,
+    pub(crate) completed: bool,
+}
+impl PendingRequest {
+    /// Wait for the result, failing with a [`TimeoutError`] after `timeout`.
+    pub(crate) async fn wait<R: Runtime>(
+        mut self,
+        udf_path: &str,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<FunctionResult> {
+        let result = match timeout {
+            None => (&mut self.result).await,
+            Some(timeout) => {
+                let timer = R::sleep(timeout);
+                pin_mut!(timer);
+                match select(&mut self.result, timer).await {
+                    Either::Left((result, _)) => result,
+                    Either::Right(_) => {
+                        return Err(TimeoutError {
+                            udf_path: udf_path.to_string(),
+                            timeout,
+                        }
+                        .into());
+                    }
+                }
+            }
+        };
+        self.completed = true;
+        Ok(result?)
+    }
+}
+impl Drop for PendingRequest {
+    fn drop(&mut self) {
+        if !self.completed {
+            let _ = self
+                .request_sender
+                .unbounded_send(ClientRequest::Cancel(CancelRequest {
+                    request_id: self.request_id,
+                }));
+        }
+    }
+}
+impl Stream for QuerySubscription {
+    type Item = FunctionResult;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        if let Some(initial) = self.initial.take() {
+            return task::Poll::Ready(Some(initial));
+        }
+        loop {
+            return match self.watch.poll_next_unpin(cx) {
+                task::Poll::Ready(Some(map)) => {
+                    let Some(value) = map.get(self.id()) else {
+                        continue;
+                    };
+                    task::Poll::Ready(Some(value.clone()))
+                }
+                task::Poll::Ready(None) => task::Poll::Ready(None),
+                task::Poll::Pending => task::Poll::Pending,
+            };
+        }
+    }
+}
+
+/// A subscription to a consistent view of multiple queries.
+///
+/// [`QuerySetSubscription`]
+/// implements [`Stream`]<[`QueryResults`]>.
+/// Each item in the stream contains a consistent view
+/// of the results of all the queries in the query set.
+///
+/// Queries can be added to the query set via `ConvexClient::subscribe`.
+/// Queries can be removed from the query set via dropping the
+/// [`QuerySubscription`] token returned by `ConvexClient::subscribe`.
+///
+///
+/// [`QueryResults`] is a copy-on-write mapping from [`SubscriberId`] to
+/// its latest result [`Value`].
+pub struct QuerySetSubscription {
+    watch: async_broadcast::Receiver<QueryResults>,
+    _client: ClientHandle,
+}
+impl QuerySetSubscription {
+    pub(crate) fn new(
+        watch: async_broadcast::Receiver<QueryResults>,
+        client: ClientHandle,
+    ) -> Self {
+        Self {
+            watch,
+            _client: client,
+        }
+    }
+}
+impl Stream for QuerySetSubscription {
+    type Item = QueryResults;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        match self.watch.poll_next_unpin(cx) {
+            // Ok to be lagged (skip intermediate values) - since Convex
+            // only guarantees a newer value than the previous value.
+            task::Poll::Ready(Some(map)) => task::Poll::Ready(Some(map)),
+            task::Poll::Ready(None) => task::Poll::Ready(None),
+            task::Poll::Pending => task::Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A byte string, stored as Convex `Bytes` rather than as an array of
+/// numbers like a `Vec<u8>`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(bytes: Bytes) -> Self {
+        bytes.0
+    }
+}
+
+impl Deref for Bytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for Bytes {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes(bytes.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Bytes, E> {
+        Ok(Bytes(bytes))
+    }
+
+    /// Other formats may write bytes as an array of numbers.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bytes, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(Bytes(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Bytes;
+    use crate::value::{ConvexValue, from_json, from_value, to_json, to_value};
+
+    #[test]
+    fn bytes_round_trip() {
+        let bytes = Bytes(vec![0, 1, 128, 255]);
+        assert_eq!(
+            to_value(&bytes).unwrap(),
+            ConvexValue::Bytes(vec![0, 1, 128, 255])
+        );
+        assert_eq!(from_json::<Bytes>(to_json(&bytes).unwrap()).unwrap(), bytes);
+        assert_eq!(
+            from_json::<Bytes>(to_json(&Bytes::default()).unwrap()).unwrap(),
+            Bytes::default()
+        );
+    }
+
+    #[test]
+    fn vec_of_bytes_stays_an_array() {
+        assert_eq!(to_json(&vec![1u8, 2]).unwrap()["$bytes"], json!(null));
+        assert_eq!(
+            from_value::<Vec<u8>>(ConvexValue::Bytes(vec![1, 2])).unwrap(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn bytes_are_read_from_an_array() {
+        assert_eq!(
+            from_value::<Bytes>(ConvexValue::Array(vec![
+                ConvexValue::Int64(1),
+                ConvexValue::Int64(2),
+            ]))
+            .unwrap(),
+            Bytes(vec![1, 2])
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, Error as _, IntoDeserializer, Unexpected,
+    VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use super::{ConvexValue, Error};
+
+impl ConvexValue {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            ConvexValue::Null => Unexpected::Unit,
+            ConvexValue::Int64(n) => Unexpected::Signed(*n),
+            ConvexValue::Float64(n) => Unexpected::Float(*n),
+            ConvexValue::Boolean(b) => Unexpected::Bool(*b),
+            ConvexValue::String(s) => Unexpected::Str(s),
+            ConvexValue::Bytes(bytes) => Unexpected::Bytes(bytes),
+            ConvexValue::Array(_) => Unexpected::Seq,
+            ConvexValue::Object(_) => Unexpected::Map,
+        }
+    }
+
+    /// Read an integer out of a `Float64` holding one.
+    fn deserialize_integer<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            ConvexValue::Float64(n)
+                if n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 =>
+            {
+                visitor.visit_i64(n as i64)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+}
+
+fn visit_array<'de, V: Visitor<'de>>(
+    items: impl Iterator<Item = impl IntoDeserializer<'de, Error>>,
+    visitor: V,
+) -> Result<V::Value, Error> {
+    let mut seq = SeqDeserializer::<_, Error>::new(items);
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+impl<'de> Deserializer<'de> for ConvexValue {
+    type Error = Error;
+
+    /// Bytes are visited as a sequence, so that they land in a `Vec<u8>` or
+    /// a `serde_json::Value` alike; `serde_bytes` types ask for bytes.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            ConvexValue::Null => visitor.visit_unit(),
+            ConvexValue::Int64(n) => visitor.visit_i64(n),
+            ConvexValue::Float64(n) => visitor.visit_f64(n),
+            ConvexValue::Boolean(b) => visitor.visit_bool(b),
+            ConvexValue::String(s) => visitor.visit_string(s),
+            ConvexValue::Bytes(bytes) => visit_array(bytes.into_iter(), visitor),
+            ConvexValue::Array(items) => visit_array(items.into_iter(), visitor),
+            ConvexValue::Object(fields) => {
+                let mut map = MapDeserializer::<_, Error>::new(fields.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            ConvexValue::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            ConvexValue::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Enums are encoded as `serde_json` does: unit variants as their name,
+    /// the others as an object with the variant name as its only field.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            ConvexValue::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: None,
+            }),
+            ConvexValue::Object(fields) if fields.len() == 1 => {
+                let (variant, value) = fields.into_iter().next().expect("one field");
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            value => Err(Error::invalid_type(
+                value.unexpected(),
+                &"a string or an object with one field",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ConvexValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<ConvexValue>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer), Error> {
+        let variant: StringDeserializer<Error> = self.variant.into_deserializer();
+        Ok((seed.deserialize(variant)?, VariantDeserializer(self.value)))
+    }
+}
+
+struct VariantDeserializer(Option<ConvexValue>);
+
+impl VariantDeserializer {
+    fn value(self) -> Result<ConvexValue, Error> {
+        self.0
+            .ok_or_else(|| Error::invalid_type(Unexpected::UnitVariant, &"a variant with data"))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.0 {
+            None | Some(ConvexValue::Null) => Ok(()),
+            Some(value) => Err(Error::invalid_type(value.unexpected(), &"a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.value()?.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value()?.deserialize_map(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::{ConvexValue, from_value};
+
+    #[test]
+    fn int64_extremes_are_read_exactly() {
+        assert_eq!(
+            from_value::<i64>(ConvexValue::Int64(i64::MIN)).unwrap(),
+            i64::MIN
+        );
+        assert_eq!(
+            from_value::<i64>(ConvexValue::Int64(i64::MAX)).unwrap(),
+            i64::MAX
+        );
+        assert_eq!(
+            from_value::<u64>(ConvexValue::Int64(i64::MAX)).unwrap(),
+            i64::MAX as u64
+        );
+    }
+
+    #[test]
+    fn integers_that_dont_fit_are_rejected() {
+        assert!(from_value::<u64>(ConvexValue::Int64(-1)).is_err());
+        assert!(from_value::<i32>(ConvexValue::Int64(i64::MAX)).is_err());
+        assert!(from_value::<u8>(ConvexValue::Float64(256.0)).is_err());
+    }
+
+    #[test]
+    fn integers_are_read_from_integral_floats() {
+        assert_eq!(from_value::<i64>(ConvexValue::Float64(-3.0)).unwrap(), -3);
+        assert!(from_value::<i64>(ConvexValue::Float64(1.5)).is_err());
+        assert!(from_value::<i64>(ConvexValue::Float64(f64::NAN)).is_err());
+        assert!(from_value::<i64>(ConvexValue::Float64(f64::INFINITY)).is_err());
+    }
+
+    #[test]
+    fn special_floats_are_read() {
+        assert!(
+            from_value::<f64>(ConvexValue::Float64(f64::NAN))
+                .unwrap()
+                .is_nan()
+        );
+        assert_eq!(
+            from_value::<f64>(ConvexValue::Float64(f64::NEG_INFINITY)).unwrap(),
+            f64::NEG_INFINITY
+        );
+        let n = from_value::<f64>(ConvexValue::Float64(-0.0)).unwrap();
+        assert!(n == 0.0 && n.is_sign_negative());
+    }
+}
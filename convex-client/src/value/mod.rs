@@ -0,0 +1,266 @@
+//! The Convex value model, and serde adapters between it and Rust types.
+//!
+//! Convex has types JSON lacks: 64-bit integers, byte strings, and floats
+//! such as `NaN` that JSON numbers can't hold. On the wire they are objects
+//! with a single reserved key:
+//!
+//! ```text
+//! {"$integer": "<base64 little-endian i64>"}
+//! {"$float": "<base64 little-endian f64>"}
+//! {"$bytes": "<base64>"}
+//! ```
+//!
+//! [`to_value`] and [`from_value`] map Rust types to and from a
+//! [`ConvexValue`]: Rust integers become [`ConvexValue::Int64`], floats
+//! [`ConvexValue::Float64`] and [`Bytes`] [`ConvexValue::Bytes`].
+//! [`to_json`] and [`from_json`] go through the wire encoding in one step.
+
+mod bytes;
+mod de;
+mod ser;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+pub use self::bytes::Bytes;
+pub use self::ser::Serializer;
+
+/// A value stored in or returned by Convex.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConvexValue {
+    Null,
+    Int64(i64),
+    Float64(f64),
+    Boolean(bool),
+    String(String),
+    Bytes(Vec<u8>),
+    Array(Vec<ConvexValue>),
+    Object(BTreeMap<String, ConvexValue>),
+}
+
+/// Why a value could not be converted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+fn reserved_field(name: &str) -> Error {
+    Error(format!(
+        "Field name {name:?} starts with '$', which is reserved"
+    ))
+}
+
+/// Field names starting with `$` are reserved for the wire encoding.
+fn check_field_name(name: &str) -> Result<(), Error> {
+    if name.starts_with('$') {
+        return Err(reserved_field(name));
+    }
+    Ok(())
+}
+
+/// Floats that a JSON number can't represent exactly.
+fn is_special_float(n: f64) -> bool {
+    !n.is_finite() || (n == 0.0 && n.is_sign_negative())
+}
+
+fn decode_base64(encoded: Value, kind: &str) -> Result<Vec<u8>, Error> {
+    let Value::String(encoded) = encoded else {
+        return Err(Error(format!("{kind} must be a base64 string")));
+    };
+    base64::decode(encoded.as_bytes()).map_err(|err| Error(format!("Invalid {kind}: {err}")))
+}
+
+fn decode_eight_bytes(encoded: Value, kind: &str) -> Result<[u8; 8], Error> {
+    decode_base64(encoded, kind)?
+        .try_into()
+        .map_err(|_| Error(format!("{kind} must be exactly eight bytes")))
+}
+
+impl From<ConvexValue> for Value {
+    /// Encode a value for the wire.
+    fn from(value: ConvexValue) -> Self {
+        match value {
+            ConvexValue::Null => Value::Null,
+            ConvexValue::Int64(n) => json!({ "$integer": base64::encode(n.to_le_bytes()) }),
+            ConvexValue::Float64(n) if is_special_float(n) => {
+                json!({ "$float": base64::encode(n.to_le_bytes()) })
+            }
+            ConvexValue::Float64(n) => json!(n),
+            ConvexValue::Boolean(b) => Value::Bool(b),
+            ConvexValue::String(s) => Value::String(s),
+            ConvexValue::Bytes(bytes) => json!({ "$bytes": base64::encode(bytes) }),
+            ConvexValue::Array(items) => Value::Array(items.into_iter().map(Value::from).collect()),
+            ConvexValue::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name, value.into()))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+}
+
+impl TryFrom<Value> for ConvexValue {
+    type Error = Error;
+
+    /// Decode a value from the wire. Plain JSON numbers are `Float64`s.
+    fn try_from(value: Value) -> Result<Self, Error> {
+        Ok(match value {
+            Value::Null => ConvexValue::Null,
+            Value::Bool(b) => ConvexValue::Boolean(b),
+            Value::Number(n) => ConvexValue::Float64(
+                n.as_f64()
+                    .ok_or_else(|| Error(format!("{n} is not a float")))?,
+            ),
+            Value::String(s) => ConvexValue::String(s),
+            Value::Array(items) => ConvexValue::Array(
+                items
+                    .into_iter()
+                    .map(ConvexValue::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(fields)
+                if fields.len() == 1 && fields.keys().all(|key| key.starts_with('$')) =>
+            {
+                let (key, encoded) = fields.into_iter().next().expect("one field");
+                match key.as_str() {
+                    "$integer" => ConvexValue::Int64(i64::from_le_bytes(decode_eight_bytes(
+                        encoded, "Int64",
+                    )?)),
+                    "$float" => ConvexValue::Float64(f64::from_le_bytes(decode_eight_bytes(
+                        encoded, "Float64",
+                    )?)),
+                    "$bytes" => ConvexValue::Bytes(decode_base64(encoded, "Bytes")?),
+                    _ => return Err(reserved_field(&key)),
+                }
+            }
+            Value::Object(fields) => ConvexValue::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| {
+                        check_field_name(&name)?;
+                        Ok((name, ConvexValue::try_from(value)?))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+        })
+    }
+}
+
+/// Convert `value` to a [`ConvexValue`].
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<ConvexValue, Error> {
+    value.serialize(Serializer)
+}
+
+/// Convert a [`ConvexValue`] to a `T`.
+///
+/// Integers may also be read from integral `Float64`s, since JavaScript
+/// functions return plain numbers.
+pub fn from_value<T: DeserializeOwned>(value: ConvexValue) -> Result<T, Error> {
+    T::deserialize(value)
+}
+
+/// Encode `value` for the wire, as function arguments are sent.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+    Ok(to_value(value)?.into())
+}
+
+/// Decode a `T` from the wire, as function results are received.
+pub fn from_json<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    from_value(ConvexValue::try_from(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{ConvexValue, from_json, to_json};
+
+    fn round_trip(value: ConvexValue) -> ConvexValue {
+        ConvexValue::try_from(Value::from(value)).unwrap()
+    }
+
+    #[test]
+    fn special_floats_are_sent_as_bytes() {
+        for n in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0] {
+            let wire = Value::from(ConvexValue::Float64(n));
+            assert_eq!(wire, json!({ "$float": base64::encode(n.to_le_bytes()) }));
+        }
+        assert_eq!(Value::from(ConvexValue::Float64(0.0)), json!(0.0));
+    }
+
+    #[test]
+    fn special_floats_round_trip() {
+        let ConvexValue::Float64(n) = round_trip(ConvexValue::Float64(f64::NAN)) else {
+            panic!("expected a Float64");
+        };
+        assert!(n.is_nan());
+        for n in [f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(round_trip(ConvexValue::Float64(n)), ConvexValue::Float64(n));
+        }
+        let ConvexValue::Float64(n) = round_trip(ConvexValue::Float64(-0.0)) else {
+            panic!("expected a Float64");
+        };
+        assert_eq!(n, 0.0);
+        assert!(n.is_sign_negative());
+    }
+
+    #[test]
+    fn int64_extremes_round_trip() {
+        for n in [i64::MIN, -1, 0, i64::MAX] {
+            assert_eq!(round_trip(ConvexValue::Int64(n)), ConvexValue::Int64(n));
+            assert_eq!(from_json::<i64>(to_json(&n).unwrap()).unwrap(), n);
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let bytes = ConvexValue::Bytes(vec![0, 1, 254, 255]);
+        assert_eq!(
+            Value::from(bytes.clone()),
+            json!({ "$bytes": base64::encode([0, 1, 254, 255]) })
+        );
+        assert_eq!(round_trip(bytes.clone()), bytes);
+        assert_eq!(
+            round_trip(ConvexValue::Bytes(vec![])),
+            ConvexValue::Bytes(vec![])
+        );
+    }
+
+    #[test]
+    fn reserved_field_names_are_rejected() {
+        assert!(ConvexValue::try_from(json!({ "$id": "abc" })).is_err());
+        assert!(ConvexValue::try_from(json!({ "name": "a", "$id": "abc" })).is_err());
+        assert!(ConvexValue::try_from(json!({ "nested": { "$id": "abc" } })).is_err());
+        assert!(ConvexValue::try_from(json!({ "id$": "abc" })).is_ok());
+    }
+
+    #[test]
+    fn malformed_encodings_are_rejected() {
+        assert!(ConvexValue::try_from(json!({ "$integer": 1 })).is_err());
+        assert!(ConvexValue::try_from(json!({ "$integer": base64::encode([1, 2, 3]) })).is_err());
+        assert!(ConvexValue::try_from(json!({ "$float": "not base64!" })).is_err());
+    }
+}
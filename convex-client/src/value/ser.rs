@@ -0,0 +1,430 @@
+use std::collections::BTreeMap;
+
+use serde::ser::{self, Error as _, Serialize};
+
+use super::{ConvexValue, Error, check_field_name};
+
+/// Serializes Rust values into [`ConvexValue`]s.
+///
+/// Integers become `Int64`s, failing if they don't fit, and unit enum
+/// variants their name, as in `serde_json`.
+pub struct Serializer;
+
+fn int64(n: impl TryInto<i64> + std::fmt::Display + Copy) -> Result<ConvexValue, Error> {
+    n.try_into()
+        .map(ConvexValue::Int64)
+        .map_err(|_| Error::custom(format!("{n} does not fit in an Int64")))
+}
+
+fn object(fields: impl IntoIterator<Item = (String, ConvexValue)>) -> ConvexValue {
+    ConvexValue::Object(fields.into_iter().collect())
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, v: bool) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<ConvexValue, Error> {
+        int64(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Float64(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Float64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::String(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<ConvexValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::String(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<ConvexValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<ConvexValue, Error> {
+        check_field_name(variant)?;
+        Ok(object([(variant.to_owned(), value.serialize(self)?)]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeTupleVariant, Error> {
+        check_field_name(variant)?;
+        Ok(SerializeTupleVariant {
+            variant,
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject {
+            fields: BTreeMap::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeStructVariant, Error> {
+        check_field_name(variant)?;
+        Ok(SerializeStructVariant {
+            variant,
+            fields: BTreeMap::new(),
+        })
+    }
+}
+
+pub struct SerializeArray {
+    items: Vec<ConvexValue>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Array(self.items))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<ConvexValue, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<ConvexValue, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+pub struct SerializeTupleVariant {
+    variant: &'static str,
+    items: Vec<ConvexValue>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<ConvexValue, Error> {
+        Ok(object([(
+            self.variant.to_owned(),
+            ConvexValue::Array(self.items),
+        )]))
+    }
+}
+
+pub struct SerializeObject {
+    fields: BTreeMap<String, ConvexValue>,
+    next_key: Option<String>,
+}
+
+impl SerializeObject {
+    fn insert(&mut self, name: String, value: ConvexValue) -> Result<(), Error> {
+        check_field_name(&name)?;
+        self.fields.insert(name, value);
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        // Numeric keys are stringified, as in `serde_json`.
+        self.next_key = Some(match key.serialize(Serializer)? {
+            ConvexValue::String(name) => name,
+            ConvexValue::Int64(n) => n.to_string(),
+            _ => return Err(Error::custom("Object keys must be strings")),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let name = self
+            .next_key
+            .take()
+            .ok_or_else(|| Error::custom("serialize_value called before serialize_key"))?;
+        let value = value.serialize(Serializer)?;
+        self.insert(name, value)
+    }
+
+    fn end(self) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Object(self.fields))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let value = value.serialize(Serializer)?;
+        self.insert(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<ConvexValue, Error> {
+        Ok(ConvexValue::Object(self.fields))
+    }
+}
+
+pub struct SerializeStructVariant {
+    variant: &'static str,
+    fields: BTreeMap<String, ConvexValue>,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = ConvexValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        check_field_name(key)?;
+        self.fields
+            .insert(key.to_owned(), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<ConvexValue, Error> {
+        Ok(object([(
+            self.variant.to_owned(),
+            ConvexValue::Object(self.fields),
+        )]))
+    }
+}
+
+impl Serialize for ConvexValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ConvexValue::Null => serializer.serialize_unit(),
+            ConvexValue::Int64(n) => serializer.serialize_i64(*n),
+            ConvexValue::Float64(n) => serializer.serialize_f64(*n),
+            ConvexValue::Boolean(b) => serializer.serialize_bool(*b),
+            ConvexValue::String(s) => serializer.serialize_str(s),
+            ConvexValue::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            ConvexValue::Array(items) => items.serialize(serializer),
+            ConvexValue::Object(fields) => fields.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use crate::value::{ConvexValue, to_value};
+
+    #[test]
+    fn integers_become_int64s() {
+        assert_eq!(to_value(&i64::MIN).unwrap(), ConvexValue::Int64(i64::MIN));
+        assert_eq!(to_value(&i64::MAX).unwrap(), ConvexValue::Int64(i64::MAX));
+        assert_eq!(to_value(&7u8).unwrap(), ConvexValue::Int64(7));
+        assert_eq!(
+            to_value(&(i64::MAX as u64)).unwrap(),
+            ConvexValue::Int64(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn integers_outside_int64_are_rejected() {
+        assert!(to_value(&(i64::MAX as u64 + 1)).is_err());
+        assert!(to_value(&u64::MAX).is_err());
+        assert!(to_value(&(i64::MIN as i128 - 1)).is_err());
+        assert!(to_value(&u128::MAX).is_err());
+    }
+
+    #[test]
+    fn special_floats_are_kept() {
+        let ConvexValue::Float64(n) = to_value(&f64::NAN).unwrap() else {
+            panic!("expected a Float64");
+        };
+        assert!(n.is_nan());
+        assert_eq!(
+            to_value(&f64::INFINITY).unwrap(),
+            ConvexValue::Float64(f64::INFINITY)
+        );
+        assert_eq!(
+            to_value(&f32::NEG_INFINITY).unwrap(),
+            ConvexValue::Float64(f64::NEG_INFINITY)
+        );
+        let ConvexValue::Float64(n) = to_value(&-0.0f64).unwrap() else {
+            panic!("expected a Float64");
+        };
+        assert!(n == 0.0 && n.is_sign_negative());
+    }
+
+    #[test]
+    fn reserved_field_names_are_rejected() {
+        #[derive(Serialize)]
+        struct Document {
+            #[serde(rename = "$id")]
+            id: String,
+        }
+
+        assert!(to_value(&Document { id: "abc".into() }).is_err());
+        let fields = BTreeMap::from([("$id", 1)]);
+        assert!(to_value(&fields).is_err());
+        let fields = BTreeMap::from([("id", 1)]);
+        assert!(to_value(&fields).is_ok());
+    }
+}
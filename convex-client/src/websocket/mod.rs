@@ -3,10 +3,33 @@ pub mod client;
 #[cfg(feature = "ssr")]
 pub mod server;
 
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use anyhow::Context;
 use async_trait::async_trait;
-use convex_sync_types::{ClientMessage, Timestamp};
-use futures::channel::mpsc;
+use convex_sync_types::backoff::Backoff;
+use convex_sync_types::{ClientMessage, SessionId, Timestamp};
+use futures::channel::mpsc::{self, UnboundedReceiver};
+use futures::channel::oneshot;
+use futures::future;
+use futures::stream::Fuse;
+use futures::{FutureExt, SinkExt, Stream, StreamExt, pin_mut, select_biased};
+use leptos::logging::{error, log};
 use url::Url;
+use uuid::Uuid;
+use web_time::Instant;
+
+use crate::runtime::Runtime;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(15);
+
+/// How long the server may stay silent before the connection is considered
+/// dead, unless the client builder sets another limit. The deployment pings
+/// idle connections well within it.
+pub const DEFAULT_INACTIVITY_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Upon a protocol failure, an explanation of the failure to pass in on
 /// reconnect
@@ -26,23 +49,249 @@ pub enum ProtocolResponse {
     Failure,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// The state of the Convex WebSocket connection
 pub enum WebSocketState {
     /// The WebSocket is open and connected
     Connected,
     /// The WebSocket is closed and connecting/reconnecting
     Connecting,
+    /// The WebSocket failed and is waiting `retry_in` before reconnecting
+    Disconnected { retry_in: Duration },
 }
 
+/// How the worker talks to the deployment. Responses are delivered on the
+/// channel the protocol was opened with.
 #[async_trait]
 pub trait SyncProtocol: Send + Sized {
-    async fn open(
+    async fn send(&mut self, message: ClientMessage) -> anyhow::Result<()>;
+    async fn reconnect(&mut self, request: ReconnectRequest);
+}
+
+/// A websocket transport that a [`WebSocketManager`] can drive.
+pub trait WebSocketConnection: Send + Sized + 'static {
+    /// The frames received from the server: the text of text frames, `None`
+    /// for any other frame, or the error that closed the connection.
+    type Frames: Stream<Item = anyhow::Result<Option<String>>> + Send + Unpin + 'static;
+
+    fn connect(
+        ws_url: &Url,
+        client_id: &str,
+    ) -> impl Future<Output = anyhow::Result<(Self, Self::Frames)>> + Send;
+    fn send(&mut self, text: String) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+#[derive(Debug)]
+enum WebSocketRequest {
+    SendMessage(Box<ClientMessage>, oneshot::Sender<()>),
+    Reconnect(ReconnectRequest),
+}
+
+/// The [`SyncProtocol`] over a [`WebSocketConnection`], reconnecting with
+/// backoff whenever the connection fails.
+pub struct WebSocketManager<R, C> {
+    internal_sender: mpsc::UnboundedSender<WebSocketRequest>,
+    /// Dropped along with the manager to stop its worker.
+    _shutdown_sender: oneshot::Sender<()>,
+    _runtime: PhantomData<fn() -> (R, C)>,
+}
+
+impl<R: Runtime, C: WebSocketConnection> WebSocketManager<R, C> {
+    /// Connect to `ws_url`, reconnecting whenever no frame, pings included,
+    /// arrives for `inactivity_timeout`.
+    pub async fn open(
         ws_url: Url,
         on_response: mpsc::Sender<ProtocolResponse>,
         on_state_change: Option<mpsc::Sender<WebSocketState>>,
         client_id: &str,
-    ) -> anyhow::Result<Self>;
-    async fn send(&mut self, message: ClientMessage) -> anyhow::Result<()>;
-    async fn reconnect(&mut self, request: ReconnectRequest);
+        inactivity_timeout: Duration,
+    ) -> anyhow::Result<Self> {
+        let (internal_sender, internal_receiver) = mpsc::unbounded();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let worker = WebSocketWorker::<R, C> {
+            ws_url,
+            on_response,
+            on_state_change,
+            internal_receiver: internal_receiver.fuse(),
+            connection_count: 0,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+            inactivity_timeout,
+            _runtime: PhantomData,
+        };
+        let client_id = client_id.to_string();
+        R::spawn(async move {
+            let run = worker.run(client_id);
+            pin_mut!(run);
+            future::select(run, shutdown_receiver).await;
+        });
+
+        Ok(WebSocketManager {
+            internal_sender,
+            _shutdown_sender: shutdown_sender,
+            _runtime: PhantomData,
+        })
+    }
+}
+
+#[async_trait]
+impl<R: Runtime, C: WebSocketConnection> SyncProtocol for WebSocketManager<R, C> {
+    async fn send(&mut self, message: ClientMessage) -> anyhow::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.internal_sender
+            .send(WebSocketRequest::SendMessage(Box::new(message), tx))
+            .await?;
+        rx.await?;
+        Ok(())
+    }
+
+    async fn reconnect(&mut self, request: ReconnectRequest) {
+        let _ = self
+            .internal_sender
+            .send(WebSocketRequest::Reconnect(request))
+            .await;
+    }
+}
+
+struct WebSocketWorker<R, C> {
+    ws_url: Url,
+    on_response: mpsc::Sender<ProtocolResponse>,
+    on_state_change: Option<mpsc::Sender<WebSocketState>>,
+    internal_receiver: Fuse<UnboundedReceiver<WebSocketRequest>>,
+    connection_count: u32,
+    backoff: Backoff,
+    inactivity_timeout: Duration,
+    _runtime: PhantomData<fn() -> (R, C)>,
+}
+
+impl<R: Runtime, C: WebSocketConnection> WebSocketWorker<R, C> {
+    async fn run(mut self, client_id: String) {
+        let mut last_close_reason = "InitialConnect".to_string();
+        let mut max_observed_timestamp = None;
+        self.set_state(WebSocketState::Connecting);
+        loop {
+            let e = match self
+                .work(last_close_reason, max_observed_timestamp, &client_id)
+                .await
+            {
+                Ok(reconnect) => {
+                    // WS worker exited cleanly because it got a request to reconnect
+                    self.set_state(WebSocketState::Connecting);
+                    log!("Reconnecting websocket due to {}", reconnect.reason);
+                    last_close_reason = reconnect.reason;
+                    max_observed_timestamp = reconnect.max_observed_timestamp;
+                    continue;
+                }
+                Err(e) => e,
+            };
+            self.connection_count += 1;
+            last_close_reason = e.to_string();
+            let delay = self.backoff.fail(R::random());
+            error!("Convex WebSocketWorker failed: {e:?}. Backing off for {delay:?} and retrying.");
+            self.set_state(WebSocketState::Disconnected { retry_in: delay });
+
+            // Tell the worker that we've failed so it can coordinate the reconnect.
+            // The worker will send a Reconnect message and the new query set all together.
+            // Drain the input request queue until we get that reconnect message - which
+            // will be followed by the refreshed query set.
+            let _ = self.on_response.send(ProtocolResponse::Failure).await;
+            loop {
+                // TODO: There is a potential issue where we have multiple queued reconnect
+                // requests in which case max_observed_timestamp might be lower than actually
+                // observed. This is fine since it will never cause errors. Will can fix this
+                // when we restructure the wider protocol to be a single routine.
+                match self.internal_receiver.next().await {
+                    Some(WebSocketRequest::Reconnect(reconnect)) => {
+                        max_observed_timestamp = reconnect.max_observed_timestamp;
+                        break;
+                    }
+                    Some(WebSocketRequest::SendMessage(..)) => continue,
+                    // The manager is gone.
+                    None => return,
+                }
+            }
+            R::sleep(delay).await;
+            self.set_state(WebSocketState::Connecting);
+        }
+    }
+
+    async fn work(
+        &mut self,
+        last_close_reason: String,
+        max_observed_timestamp: Option<Timestamp>,
+        client_id: &str,
+    ) -> anyhow::Result<ReconnectRequest> {
+        let verb = if self.connection_count == 0 {
+            "connect"
+        } else {
+            "reconnect"
+        };
+        log!("trying to {verb} to {}", self.ws_url);
+        let (mut connection, frames) = C::connect(&self.ws_url, client_id).await?;
+
+        // Send an initial connect message on the new websocket
+        let message = ClientMessage::Connect {
+            session_id: SessionId::new(Uuid::new_v4()),
+            connection_count: self.connection_count,
+            last_close_reason,
+            max_observed_timestamp,
+        };
+        connection
+            .send(
+                serde_json::Value::try_from(message)
+                    .context("JSONSerializationErrorOnConnect")?
+                    .to_string(),
+            )
+            .await?;
+        log!("completed websocket {verb} to {}", self.ws_url);
+        self.set_state(WebSocketState::Connected);
+
+        let mut frames = frames.fuse();
+        let mut last_server_response = Instant::now();
+        loop {
+            // The liveness watchdog, reset by every frame.
+            let inactivity_deadline = last_server_response + self.inactivity_timeout;
+            let inactive =
+                R::sleep(inactivity_deadline.saturating_duration_since(Instant::now())).fuse();
+            pin_mut!(inactive);
+            select_biased! {
+                _ = inactive => {
+                    anyhow::bail!("InactiveServer");
+                },
+                frame = frames.next() => {
+                    let Some(frame) = frame else {
+                        anyhow::bail!("WebsocketClosed");
+                    };
+                    last_server_response = Instant::now();
+                    let Some(text) = frame? else {
+                        continue;
+                    };
+                    let json: serde_json::Value = serde_json::from_str(&text).context("JsonDeserializeError")?;
+                    let server_message = json.try_into()?;
+                    let _ = self.on_response.send(ProtocolResponse::ServerMessage(server_message)).await;
+
+                    // TODO: Similar to JS, we should ideally only reset backoff if we get
+                    // the client gets into a correct state, where we have Connected and
+                    // received a response to our pending Queries and Mutations.
+                    self.backoff.reset();
+                },
+                request = self.internal_receiver.next() => {
+                    match request {
+                        Some(WebSocketRequest::SendMessage(message, sender)) => {
+                            let text = serde_json::Value::try_from(*message).context("JsonSerializeError")?.to_string();
+                            connection.send(text).await?;
+                            let _ = sender.send(());
+                        },
+                        Some(WebSocketRequest::Reconnect(reason)) => return Ok(reason),
+                        None => anyhow::bail!("WebSocketManagerDropped"),
+                    }
+                },
+            }
+        }
+    }
+
+    fn set_state(&self, state: WebSocketState) {
+        if let Some(mut state_change_sender) = self.on_state_change.clone() {
+            let _ = state_change_sender.try_send(state);
+        }
+    }
 }
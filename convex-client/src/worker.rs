@@ -0,0 +1,620 @@
+use std::time::Duration;
+
+use convex_sync_types::backoff::Backoff;
+use convex_sync_types::{AuthenticationToken, UdfPath};
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::channel::{mpsc, oneshot};
+use futures::stream::Fuse;
+use futures::{FutureExt, StreamExt, pin_mut, select};
+use leptos::logging::{error, log};
+use serde_json::Value;
+use web_time::Instant;
+
+use crate::auth::{self, AuthRefresher, TokenFetcher};
+use crate::base::optimistic_update::OptimisticUpdate;
+use crate::base::query_result::{FunctionResult, QueryResults};
+use crate::base::request_manager::RequestId;
+use crate::base::{BaseConvexClient, ServerError, SubscriberId};
+use crate::connection::ConnectionTracker;
+use crate::runtime::Runtime;
+use crate::subscription::QuerySubscription;
+use crate::websocket::{ProtocolResponse, ReconnectProtocolReason, ReconnectRequest, SyncProtocol};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(15);
+
+/// The error of requests the worker can no longer serve.
+pub const WORKER_STOPPED: &str = "The Convex client stopped after a fatal error";
+
+pub enum ClientRequest {
+    Mutation(
+        MutationRequest,
+        oneshot::Sender<(RequestId, oneshot::Receiver<FunctionResult>)>,
+    ),
+    Action(
+        ActionRequest,
+        oneshot::Sender<(RequestId, oneshot::Receiver<FunctionResult>)>,
+    ),
+    Subscribe(
+        SubscribeRequest,
+        oneshot::Sender<QuerySubscription>,
+        mpsc::UnboundedSender<ClientRequest>,
+    ),
+    Unsubscribe(UnsubscribeRequest),
+    Cancel(CancelRequest),
+    Authenticate(Box<AuthenticateRequest>),
+    FetchAuth(TokenFetcher),
+}
+
+pub struct MutationRequest {
+    pub udf_path: UdfPath,
+    pub args: Value,
+    pub optimistic_update: Option<OptimisticUpdate>,
+    pub idempotency_key: Option<String>,
+}
+
+pub struct ActionRequest {
+    pub udf_path: UdfPath,
+    pub args: Value,
+}
+
+pub struct SubscribeRequest {
+    pub udf_path: UdfPath,
+    pub args: Value,
+    /// A result rendered on the server to show until the first transition.
+    pub seed: Option<FunctionResult>,
+}
+
+pub struct AuthenticateRequest {
+    pub token: AuthenticationToken,
+}
+
+#[derive(Debug)]
+pub struct UnsubscribeRequest {
+    pub subscriber_id: SubscriberId,
+}
+
+#[derive(Debug)]
+pub struct CancelRequest {
+    pub request_id: RequestId,
+}
+
+/// Drive `base_client` from client requests and protocol responses until
+/// `shutdown_listen` fires, reconnecting with backoff whenever the protocol
+/// fails.
+///
+/// A fatal error from the deployment stops the worker instead, which fails
+/// the client's pending and future requests.
+pub async fn worker<R: Runtime, T: SyncProtocol>(
+    protocol_response_receiver: mpsc::Receiver<ProtocolResponse>,
+    client_request_receiver: mpsc::UnboundedReceiver<ClientRequest>,
+    mut watch_sender: async_broadcast::Sender<QueryResults>,
+    mut base_client: BaseConvexClient,
+    shutdown_listen: oneshot::Receiver<()>,
+    mut protocol_manager: T,
+    mut connection: ConnectionTracker,
+) {
+    log!("Convex worker started.");
+    let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+    let mut protocol_response_stream = protocol_response_receiver.fuse();
+    let mut client_request_stream = client_request_receiver.fuse();
+    let mut shutdown_listen = shutdown_listen.fuse();
+    let mut auth = None;
+    // Send the mutations replayed from the client's mutation store.
+    flush_messages(&mut base_client, &mut protocol_manager).await;
+
+    loop {
+        let worker_future = _worker_once::<R, T>(
+            &mut protocol_response_stream,
+            &mut client_request_stream,
+            &mut watch_sender,
+            &mut base_client,
+            &mut protocol_manager,
+            &mut connection,
+            &mut auth,
+        );
+
+        select! {
+            _ = shutdown_listen => break,
+            worker_result = worker_future.fuse() => {
+                match worker_result {
+                    Ok(()) => backoff.reset(),
+                    Err(e) => {
+                        match base_client.take_server_error() {
+                            Some(ServerError::Fatal(message)) => {
+                                error!("Convex deployment failed: {message}. Not reconnecting.");
+                                connection.on_fatal_error(message);
+                                connection.publish(&base_client);
+                                break;
+                            }
+                            Some(ServerError::Auth { error, current }) => {
+                                // With a token fetcher, give up only once a
+                                // fresh token is rejected too.
+                                let retrying = auth
+                                    .as_mut()
+                                    .is_some_and(|auth| !current || auth.retry());
+                                if !retrying {
+                                    auth = None;
+                                    connection.on_auth_error(error);
+                                }
+                            }
+                            None => {}
+                        }
+                        let delay = backoff.fail(R::random());
+                        log!("Convex worker failed: {e}. Retrying in {delay:?}.");
+                        if let Some(observer) = base_client.observer() {
+                            observer.on_reconnect(&e);
+                        }
+                        // Tell the sync protocol to reconnect followed by an
+                        // immediate resend of ongoing queries/mutations. It's
+                        // important these happen together to ensure mutation
+                        // ordering.
+                        protocol_manager
+                            .reconnect(ReconnectRequest {
+                                reason: e,
+                                max_observed_timestamp: base_client.max_observed_timestamp(),
+                            })
+                            .await;
+                        base_client.resend_ongoing_queries_mutations();
+                        flush_messages(&mut base_client, &mut protocol_manager).await;
+                        R::sleep(delay).await;
+                    }
+                }
+                connection.publish(&base_client);
+            }
+        }
+    }
+    log!("Convex worker stopped.");
+}
+
+async fn _worker_once<R: Runtime, T: SyncProtocol>(
+    protocol_response_stream: &mut Fuse<mpsc::Receiver<ProtocolResponse>>,
+    client_request_stream: &mut Fuse<UnboundedReceiver<ClientRequest>>,
+    watch_sender: &mut async_broadcast::Sender<QueryResults>,
+    base_client: &mut BaseConvexClient,
+    protocol_manager: &mut T,
+    connection: &mut ConnectionTracker,
+    auth: &mut Option<AuthRefresher>,
+) -> Result<(), ReconnectProtocolReason> {
+    pin_mut!(protocol_response_stream);
+    pin_mut!(client_request_stream);
+    let idle_expiry = sleep_until::<R>(base_client.next_idle_expiry()).fuse();
+    pin_mut!(idle_expiry);
+    select! {
+        protocol_response_opt = protocol_response_stream.next() => {
+            match protocol_response_opt {
+                Some(protocol_response) => {
+                    match protocol_response {
+                        ProtocolResponse::ServerMessage(msg) => {
+                            connection.on_server_message();
+                            if let Some(subscriber_id_to_latest_value) = base_client.receive_message(msg)? {
+                                if let Some(auth) = auth
+                                    && base_client.auth_confirmed()
+                                {
+                                    auth.confirm();
+                                }
+                                // Notify watchers of the new consistent query results at new timestamp
+                                let _ = watch_sender.broadcast(subscriber_id_to_latest_value).await;
+                            }
+                        },
+                        ProtocolResponse::Failure => {
+                            return Err("ProtocolFailure".into());
+                        },
+                    }
+                    Ok(())
+                },
+                None => {
+                    log!("Protocol stream terminated unexpectedly.");
+                    Err("ProtocolStreamTerminated".into())
+                },
+            }
+        },
+        client_request_opt = client_request_stream.next() => {
+            match client_request_opt {
+                Some(client_request) => {
+                    handle_client_request(client_request, watch_sender, base_client, auth).await?;
+                    // Take the requests already queued too before flushing, so
+                    // that the queries subscribed to in one render go out in one
+                    // query set modification.
+                    while let Some(Some(client_request)) = client_request_stream.next().now_or_never() {
+                        handle_client_request(client_request, watch_sender, base_client, auth).await?;
+                    }
+                    flush_messages(base_client, protocol_manager).await;
+                    Ok(())
+                },
+                None => {
+                    log!("Client request stream terminated unexpectedly.");
+                    Err("ClientStreamTerminated".into())
+                },
+            }
+        },
+        token = auth::next_token::<R>(auth).fuse() => {
+            match token {
+                Some(token) => base_client.set_auth(AuthenticationToken::User(token)),
+                None => {
+                    // Signed out.
+                    *auth = None;
+                    base_client.set_auth(AuthenticationToken::None);
+                },
+            }
+            flush_messages(base_client, protocol_manager).await;
+            Ok(())
+        },
+        _ = idle_expiry => {
+            base_client.expire_idle_queries();
+            flush_messages(base_client, protocol_manager).await;
+            Ok(())
+        },
+        websocket_state = connection.state_change_receiver.select_next_some() => {
+            connection.on_websocket_state(websocket_state);
+            Ok(())
+        },
+        complete => {
+            Err("AllStreamsTerminated".into())
+        }
+    }
+}
+
+/// Apply `client_request` to `base_client`, leaving the messages it queues
+/// for the caller to flush.
+async fn handle_client_request(
+    client_request: ClientRequest,
+    watch_sender: &mut async_broadcast::Sender<QueryResults>,
+    base_client: &mut BaseConvexClient,
+    auth: &mut Option<AuthRefresher>,
+) -> Result<(), ReconnectProtocolReason> {
+    match client_request {
+        ClientRequest::Subscribe(query, tx, request_sender) => {
+            let SubscribeRequest {
+                udf_path,
+                args,
+                seed,
+            } = query;
+            let watch = watch_sender.new_receiver();
+            let subscriber_id = base_client.subscribe(udf_path, args);
+            if let Some(seed) = seed {
+                base_client.seed_query(subscriber_id, seed);
+            }
+
+            let subscription = QuerySubscription {
+                subscriber_id,
+                request_sender,
+                watch,
+                initial: base_client.latest_results().get(&subscriber_id).cloned(),
+                initial_version: base_client.latest_results().version(&subscriber_id),
+                client: None,
+            };
+            let _ = tx.send(subscription);
+        }
+        ClientRequest::Mutation(mutation, tx) => {
+            let MutationRequest {
+                udf_path,
+                args,
+                optimistic_update,
+                idempotency_key,
+            } = mutation;
+            let has_optimistic_update = optimistic_update.is_some();
+            let (request_id, result_receiver) =
+                base_client.mutation(udf_path, args, optimistic_update, idempotency_key);
+            if has_optimistic_update {
+                // Notify watchers of the optimistically patched query results
+                let _ = watch_sender
+                    .broadcast(base_client.latest_results().clone())
+                    .await;
+            }
+            let _ = tx.send((request_id, result_receiver));
+        }
+        ClientRequest::Action(action, tx) => {
+            let ActionRequest { udf_path, args } = action;
+            let (request_id, result_receiver) = base_client.action(udf_path, args);
+            let _ = tx.send((request_id, result_receiver));
+        }
+        ClientRequest::Unsubscribe(unsubscribe) => {
+            let UnsubscribeRequest { subscriber_id } = unsubscribe;
+            base_client.unsubscribe(subscriber_id);
+        }
+        ClientRequest::Cancel(cancel) => {
+            let CancelRequest { request_id } = cancel;
+            if let Some(results) = base_client.cancel(request_id)? {
+                // Notify watchers that the optimistic update was rolled back
+                let _ = watch_sender.broadcast(results).await;
+            }
+        }
+        ClientRequest::Authenticate(authenticate) => {
+            *auth = None;
+            base_client.set_auth(authenticate.token);
+        }
+        ClientRequest::FetchAuth(fetch_token) => {
+            *auth = Some(AuthRefresher::new(fetch_token));
+        }
+    }
+    Ok(())
+}
+
+/// Flush all messages to the protocol
+async fn flush_messages<P: SyncProtocol>(base_client: &mut BaseConvexClient, protocol: &mut P) {
+    while let Some(modification) = base_client.pop_next_message() {
+        let _ = protocol.send(modification).await;
+    }
+}
+
+/// Resolves once `deadline` has passed, or never without one.
+async fn sleep_until<R: Runtime>(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => R::sleep(deadline.saturating_duration_since(Instant::now())).await,
+        None => futures::future::pending().await,
+    }
+}
+
+/// The worker's scenarios, written once against [`Runtime`] and run on each
+/// runtime the client supports.
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use convex_sync_types::{
+        ClientMessage, LogLinesMessage, QuerySetModification, StateModification, StateVersion,
+        Timestamp,
+    };
+    use futures::channel::{mpsc, oneshot};
+    use futures::{SinkExt, StreamExt};
+    use serde_json::json;
+
+    use super::*;
+    use crate::websocket::ServerMessage;
+
+    /// A protocol handing the messages the worker sends to the test.
+    struct ChannelProtocol {
+        sent: mpsc::UnboundedSender<ClientMessage>,
+        reconnects: mpsc::UnboundedSender<ReconnectRequest>,
+    }
+
+    #[async_trait]
+    impl SyncProtocol for ChannelProtocol {
+        async fn send(&mut self, message: ClientMessage) -> anyhow::Result<()> {
+            self.sent.unbounded_send(message)?;
+            Ok(())
+        }
+
+        async fn reconnect(&mut self, request: ReconnectRequest) {
+            let _ = self.reconnects.unbounded_send(request);
+        }
+    }
+
+    /// A worker running on `R`, playing the deployment's side through
+    /// channels. Dropping it stops the worker.
+    struct Harness {
+        requests: mpsc::UnboundedSender<ClientRequest>,
+        responses: mpsc::Sender<ProtocolResponse>,
+        sent: mpsc::UnboundedReceiver<ClientMessage>,
+        reconnects: mpsc::UnboundedReceiver<ReconnectRequest>,
+        version: StateVersion,
+        _watch: async_broadcast::Receiver<QueryResults>,
+        _state_changes: mpsc::Sender<crate::websocket::WebSocketState>,
+        _shutdown: oneshot::Sender<()>,
+    }
+
+    impl Harness {
+        fn start<R: Runtime>() -> Self {
+            let (response_sender, response_receiver) = mpsc::channel(1);
+            let (request_sender, request_receiver) = mpsc::unbounded();
+            let (mut watch_sender, watch_receiver) = async_broadcast::broadcast(1);
+            watch_sender.set_overflow(true);
+            let (mut connection_state_sender, _) = async_broadcast::broadcast(1);
+            connection_state_sender.set_overflow(true);
+            let (state_change_sender, state_change_receiver) = mpsc::channel(1);
+            let (shutdown_sender, shutdown_listen) = oneshot::channel();
+            let (sent_sender, sent_receiver) = mpsc::unbounded();
+            let (reconnect_sender, reconnect_receiver) = mpsc::unbounded();
+
+            R::spawn(worker::<R, _>(
+                response_receiver,
+                request_receiver,
+                watch_sender,
+                BaseConvexClient::new(),
+                shutdown_listen,
+                ChannelProtocol {
+                    sent: sent_sender,
+                    reconnects: reconnect_sender,
+                },
+                ConnectionTracker::new(state_change_receiver, None, None, connection_state_sender),
+            ));
+            Self {
+                requests: request_sender,
+                responses: response_sender,
+                sent: sent_receiver,
+                reconnects: reconnect_receiver,
+                version: StateVersion::initial(),
+                _watch: watch_receiver,
+                _state_changes: state_change_sender,
+                _shutdown: shutdown_sender,
+            }
+        }
+
+        async fn subscribe(&self, udf_path: &str) -> QuerySubscription {
+            let (sender, subscription) = oneshot::channel();
+            let request = SubscribeRequest {
+                udf_path: udf_path.parse().unwrap(),
+                args: json!({}),
+                seed: None,
+            };
+            self.requests
+                .unbounded_send(ClientRequest::Subscribe(
+                    request,
+                    sender,
+                    self.requests.clone(),
+                ))
+                .unwrap();
+            subscription.await.unwrap()
+        }
+
+        async fn mutation(&self, udf_path: &str) -> (RequestId, oneshot::Receiver<FunctionResult>) {
+            let (sender, pending) = oneshot::channel();
+            let request = MutationRequest {
+                udf_path: udf_path.parse().unwrap(),
+                args: json!({}),
+                optimistic_update: None,
+                idempotency_key: None,
+            };
+            self.requests
+                .unbounded_send(ClientRequest::Mutation(request, sender))
+                .unwrap();
+            pending.await.unwrap()
+        }
+
+        async fn next_sent(&mut self) -> ClientMessage {
+            self.sent.next().await.expect("the worker is running")
+        }
+
+        async fn respond(&mut self, message: ServerMessage) {
+            self.responses
+                .send(ProtocolResponse::ServerMessage(message))
+                .await
+                .unwrap();
+        }
+
+        /// Move the server to `ts` with `modifications`, as of the query set
+        /// `query_set`.
+        async fn transition(
+            &mut self,
+            query_set: u32,
+            ts: u64,
+            modifications: Vec<StateModification>,
+        ) {
+            let start_version = self.version;
+            self.version = StateVersion {
+                query_set,
+                identity: 0,
+                ts: Timestamp::try_from(ts).unwrap(),
+            };
+            self.respond(ServerMessage::Transition {
+                start_version,
+                end_version: self.version,
+                modifications,
+            })
+            .await;
+        }
+    }
+
+    fn query_added(message: ClientMessage) -> convex_sync_types::QueryId {
+        match message {
+            ClientMessage::ModifyQuerySet { modifications, .. } => match &modifications[..] {
+                [QuerySetModification::Add(query)] => query.query_id,
+                _ => panic!("expected one added query, got {modifications:?}"),
+            },
+            message => panic!("expected a query set modification, got {message:?}"),
+        }
+    }
+
+    fn updated(
+        query_id: convex_sync_types::QueryId,
+        value: serde_json::Value,
+    ) -> StateModification {
+        StateModification::QueryUpdated {
+            query_id,
+            value,
+            log_lines: LogLinesMessage(vec![]),
+            journal: None,
+        }
+    }
+
+    async fn query_results_reach_subscribers<R: Runtime>() {
+        let mut harness = Harness::start::<R>();
+        let mut subscription = harness.subscribe("counter:get").await;
+        let query_id = query_added(harness.next_sent().await);
+
+        harness
+            .transition(1, 1, vec![updated(query_id, json!(1))])
+            .await;
+
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(json!(1)))
+        );
+    }
+
+    async fn mutation_results_reach_callers<R: Runtime>() {
+        let mut harness = Harness::start::<R>();
+        let (request_id, result) = harness.mutation("counter:increment").await;
+        let ClientMessage::Mutation {
+            request_id: sent_id,
+            ..
+        } = harness.next_sent().await
+        else {
+            panic!("expected the mutation to be sent");
+        };
+        assert_eq!(RequestId::new(sent_id), request_id);
+
+        harness
+            .respond(ServerMessage::MutationResponse {
+                request_id: sent_id,
+                result: Ok(json!("done")),
+                ts: Some(Timestamp::try_from(2u64).unwrap()),
+                log_lines: LogLinesMessage(vec![]),
+            })
+            .await;
+        // The result is handed over once the client has seen its effects.
+        harness.transition(0, 2, vec![]).await;
+
+        assert_eq!(result.await.unwrap(), FunctionResult::Value(json!("done")));
+    }
+
+    async fn protocol_failure_reconnects_and_resubscribes<R: Runtime>() {
+        let mut harness = Harness::start::<R>();
+        let _subscription = harness.subscribe("counter:get").await;
+        let query_id = query_added(harness.next_sent().await);
+
+        harness
+            .responses
+            .send(ProtocolResponse::Failure)
+            .await
+            .unwrap();
+
+        let reconnect = harness.reconnects.next().await.unwrap();
+        assert_eq!(reconnect.reason, "ProtocolFailure");
+        let resent = harness.next_sent().await;
+        assert!(matches!(
+            resent,
+            ClientMessage::ModifyQuerySet {
+                base_version: 0,
+                new_version: 1,
+                ..
+            }
+        ));
+        assert_eq!(query_added(resent), query_id);
+    }
+
+    /// The scenarios above, as tests on `$runtime` run by `$test`.
+    macro_rules! runtime_tests {
+        ($test:meta, $runtime:ty) => {
+            #[$test]
+            async fn query_results_reach_subscribers() {
+                super::query_results_reach_subscribers::<$runtime>().await;
+            }
+
+            #[$test]
+            async fn mutation_results_reach_callers() {
+                super::mutation_results_reach_callers::<$runtime>().await;
+            }
+
+            #[$test]
+            async fn protocol_failure_reconnects_and_resubscribes() {
+                super::protocol_failure_reconnects_and_resubscribes::<$runtime>().await;
+            }
+        };
+    }
+
+    #[cfg(feature = "ssr")]
+    mod tokio_runtime {
+        runtime_tests!(tokio::test, crate::runtime::TokioRuntime);
+    }
+
+    /// Run in a browser with `wasm-pack test --headless --firefox`.
+    #[cfg(target_arch = "wasm32")]
+    mod browser_runtime {
+        use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+        wasm_bindgen_test_configure!(run_in_browser);
+
+        runtime_tests!(wasm_bindgen_test, crate::runtime::BrowserRuntime);
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::{Arc, Mutex};
+
+use convex_client::leptos::{Mutation, Query};
+use convex_client::logs::{FunctionKind, LogLevel, LogLine, LogSink};
+use convex_client::server::ConvexHttpClient;
+use convex_client::{ConvexClientError, ConvexError};
+use convex_test_server::MockHttpServer;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+struct GetCount {}
+
+impl Query<f64> for GetCount {
+    fn name(&self) -> String {
+        "counter:get".into()
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+struct Increment {
+    by: f64,
+}
+
+impl Mutation for Increment {
+    type Output = ();
+
+    fn name(&self) -> String {
+        "counter:increment".into()
+    }
+}
+
+/// A log sink the test can read back.
+#[derive(Clone, Default)]
+struct LogCapture(Arc<Mutex<Vec<LogLine>>>);
+
+impl LogSink for LogCapture {
+    fn log(&self, line: LogLine) {
+        self.0.lock().unwrap().push(line);
+    }
+}
+
+#[tokio::test]
+async fn query_is_posted_with_auth() -> anyhow::Result<()> {
+    let mut server = MockHttpServer::start().await?;
+    let logs = LogCapture::default();
+    let mut client = ConvexHttpClient::new(server.deployment_url())?.with_log_sink(logs.clone());
+    client.set_auth(Some("token".into()));
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let request = server.receive().await?;
+    assert_eq!(request.path, "/api/query");
+    assert_eq!(request.authorization.as_deref(), Some("Bearer token"));
+    assert_eq!(
+        request.body,
+        json!({ "path": "counter:get", "args": {}, "format": "json" })
+    );
+    request.success(json!(42.0), &["[INFO] counted"]).await?;
+    assert_eq!(query.await??, 42.0);
+
+    let lines = logs.0.lock().unwrap().clone();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].kind, FunctionKind::Query);
+    assert_eq!(lines[0].udf_path.to_string(), "counter.js:get");
+    assert_eq!(
+        (lines[0].level, lines[0].message.as_str()),
+        (LogLevel::Info, "counted")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn mutations_and_actions_report_function_errors() -> anyhow::Result<()> {
+    let mut server = MockHttpServer::start().await?;
+    let client = ConvexHttpClient::new(server.deployment_url())?;
+
+    let mutation = tokio::spawn({
+        let client = client.clone();
+        async move { client.mutation(Increment { by: 1.0 }).await }
+    });
+    let request = server.receive().await?;
+    assert_eq!(request.path, "/api/mutation");
+    assert_eq!(request.authorization, None);
+    assert_eq!(request.body["args"], json!({ "by": 1.0 }));
+    request.error("too big", Some(json!({ "max": 0 }))).await?;
+    assert_eq!(
+        mutation.await??.into_result(),
+        Err(ConvexClientError::Convex(ConvexError {
+            message: "too big".into(),
+            data: json!({ "max": 0 }),
+        }))
+    );
+
+    let action = tokio::spawn(async move { client.action("gifs:send", json!({})).await });
+    let request = server.receive().await?;
+    assert_eq!(request.path, "/api/action");
+    request.error("no gifs", None).await?;
+    assert_eq!(
+        action.await??.into_result(),
+        Err(ConvexClientError::Server("no gifs".into()))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_requests_are_transport_errors() -> anyhow::Result<()> {
+    let mut server = MockHttpServer::start().await?;
+    let client = ConvexHttpClient::new(server.deployment_url())?;
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    server
+        .receive()
+        .await?
+        .respond(
+            401,
+            json!({ "code": "Unauthenticated", "message": "bad token" }),
+        )
+        .await?;
+    let err = ConvexClientError::from(query.await?.unwrap_err());
+    assert!(
+        matches!(&err, ConvexClientError::Transport(message) if message.contains("401")),
+        "{err:?}"
+    );
+    Ok(())
+}
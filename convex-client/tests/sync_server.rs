@@ -0,0 +1,1109 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use convex_client::leptos::{
+    InMemoryMutationStore, Mutation, MutationStore, PaginatedQuery, PersistedMutation, Query,
+};
+use convex_client::logs::{FunctionKind, LogLevel, LogLine, LogSink};
+use convex_client::observer::{
+    CanonicalizedUdfPath, ClientObserver, QueryId, RequestId, Timestamp,
+};
+use convex_client::recording::{ProtocolEvent, ProtocolRecorder, REDACTED_TOKEN, Recording};
+use convex_client::server::{ConvexClient, ConvexClientBuilder, ConvexClientPool};
+use convex_client::value::Bytes;
+use convex_client::{AuthError, ConvexClientError, ConvexError, TimeoutError};
+use convex_sync_types::{
+    AuthenticationToken, ClientMessage, ErrorPayload, LogLinesMessage, QuerySetModification,
+    SerializedQueryJournal, ServerMessage, StateModification, UdfPath,
+};
+use convex_test_server::{MockConnection, MockConvexServer};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+struct GetCount {}
+
+impl Query<f64> for GetCount {
+    fn name(&self) -> String {
+        "counter:get".into()
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+struct GetBlob {}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Blob {
+    size: i64,
+    data: Bytes,
+}
+
+impl Query<Blob> for GetBlob {
+    fn name(&self) -> String {
+        "blobs:get".into()
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct Increment {
+    by: f64,
+}
+
+impl Mutation for Increment {
+    type Output = ();
+
+    fn name(&self) -> String {
+        "counter:increment".into()
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+struct ListNumbers {}
+
+impl PaginatedQuery<f64> for ListNumbers {
+    fn name(&self) -> String {
+        "numbers:list".into()
+    }
+}
+
+/// Serve the first page of the sorted `items` like Convex does: a new page
+/// holds `numItems` items, while one resumed from a journal ends at the same
+/// item as before.
+fn first_page(items: &[f64], query: &convex_sync_types::Query) -> (Value, SerializedQueryJournal) {
+    let page: Vec<f64> = match &query.journal {
+        Some(Some(end)) => {
+            let end: f64 = end.parse().unwrap();
+            items.iter().copied().filter(|item| *item <= end).collect()
+        }
+        _ => {
+            let num_items = query.args[0]["paginationOpts"]["numItems"]
+                .as_f64()
+                .unwrap();
+            items.iter().copied().take(num_items as usize).collect()
+        }
+    };
+    let end = page.last().unwrap().to_string();
+    let result = json!({ "page": page, "isDone": false, "continueCursor": end });
+    (result, Some(end))
+}
+
+/// A store the test can inspect while the client uses it.
+#[derive(Clone, Default)]
+struct SharedStore(Arc<Mutex<InMemoryMutationStore>>);
+
+impl MutationStore for SharedStore {
+    fn load(&self) -> Vec<PersistedMutation> {
+        self.0.lock().unwrap().load()
+    }
+
+    fn insert(&mut self, mutation: PersistedMutation) {
+        self.0.lock().unwrap().insert(mutation)
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.0.lock().unwrap().remove(key)
+    }
+}
+
+/// The events a client reported, as text.
+#[derive(Clone, Default)]
+struct EventLog(Arc<Mutex<Vec<String>>>);
+
+impl EventLog {
+    fn push(&self, event: String) {
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+impl ClientObserver for EventLog {
+    fn on_subscribe(&self, _query_id: QueryId, udf_path: &CanonicalizedUdfPath) {
+        self.push(format!("subscribe {udf_path}"));
+    }
+
+    fn on_first_result(&self, _query_id: QueryId) {
+        self.push("first result".into());
+    }
+
+    fn on_transition(&self, _ts: Timestamp, updated_queries: usize) {
+        self.push(format!("transition updating {updated_queries}"));
+    }
+
+    fn on_mutation_sent(&self, _request_id: RequestId, udf_path: &CanonicalizedUdfPath) {
+        self.push(format!("mutation sent {udf_path}"));
+    }
+
+    fn on_mutation_acknowledged(&self, _request_id: RequestId) {
+        self.push("mutation acknowledged".into());
+    }
+
+    fn on_mutation_observed(&self, _request_id: RequestId) {
+        self.push("mutation observed".into());
+    }
+
+    fn on_reconnect(&self, _reason: &str) {
+        self.push("reconnect".into());
+    }
+
+    fn on_log_line(&self, line: &LogLine) {
+        self.push(format!(
+            "{} {} logged [{}] {}",
+            line.kind, line.udf_path, line.level, line.message
+        ));
+    }
+}
+
+/// A log sink the test can read back.
+#[derive(Clone, Default)]
+struct LogCapture(Arc<Mutex<Vec<LogLine>>>);
+
+impl LogSink for LogCapture {
+    fn log(&self, line: LogLine) {
+        self.0.lock().unwrap().push(line);
+    }
+}
+
+/// A recording the test can read back.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Receive messages until the next user `Authenticate` and return its base
+/// version and token.
+async fn receive_token(connection: &mut MockConnection) -> anyhow::Result<(u32, String)> {
+    connection
+        .receive_until(|message| match message {
+            ClientMessage::Authenticate {
+                base_version,
+                token: AuthenticationToken::User(token),
+            } => Some((base_version, token)),
+            _ => None,
+        })
+        .await
+}
+
+fn is_path(udf_path: &UdfPath, expected: &str) -> bool {
+    let expected: UdfPath = expected.parse().unwrap();
+    udf_path.clone().canonicalize() == expected.canonicalize()
+}
+
+#[tokio::test]
+async fn query_resolves_with_transition_value() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let (connection_count, _) = connection.receive_connect().await?;
+    assert_eq!(connection_count, 0);
+    let queries = connection.receive_query_set().await?;
+    assert_eq!(queries.len(), 1);
+    assert!(is_path(&queries[0].udf_path, "counter:get"));
+    assert_eq!(queries[0].args, vec![json!({})]);
+
+    connection
+        .query_updated(queries[0].query_id, json!(42.0))
+        .await?;
+    assert_eq!(query.await??, 42.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_decodes_int64_and_bytes() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let query = tokio::spawn(async move { client.query(GetBlob {}).await });
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    connection
+        .query_updated(
+            queries[0].query_id,
+            json!({ "size": { "$integer": "BwAAAAAAAAA=" }, "data": { "$bytes": "AQI=" } }),
+        )
+        .await?;
+    assert_eq!(
+        query.await??,
+        Blob {
+            size: 7,
+            data: Bytes(vec![1, 2]),
+        }
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_query_returns_error() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    connection
+        .query_failed(queries[0].query_id, "Counter not found")
+        .await?;
+    let err = query.await?.unwrap_err();
+    assert!(err.to_string().contains("Counter not found"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_query_keeps_the_convex_error() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    connection
+        .transition(vec![StateModification::QueryFailed {
+            query_id: queries[0].query_id,
+            error_message: "Counter is locked".into(),
+            log_lines: LogLinesMessage(vec![]),
+            journal: None,
+            error_data: Some(json!({ "until": 5 })),
+        }])
+        .await?;
+    let err = query.await?.unwrap_err();
+    assert_eq!(
+        err,
+        ConvexClientError::Convex(ConvexError {
+            message: "Counter is locked".into(),
+            data: json!({ "until": 5 }),
+        })
+    );
+    assert_eq!(err.data_as::<Value>(), Some(json!({ "until": 5 })));
+    Ok(())
+}
+
+#[tokio::test]
+async fn mutation_completes_once_its_timestamp_is_observed() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let mutation = tokio::spawn(async move { client.mutation(Increment { by: 2.0 }).await });
+
+    let mut connection = server.accept().await?;
+    let (request_id, udf_path, args) = connection.receive_mutation().await?;
+    assert!(is_path(&udf_path.parse()?, "counter:increment"));
+    assert_eq!(args, vec![json!({ "by": 2.0 })]);
+
+    connection
+        .mutation_response(request_id, Ok(json!(null)))
+        .await?;
+    assert!(!mutation.is_finished());
+    connection.transition(vec![]).await?;
+    let result = mutation.await??;
+    assert_eq!(
+        result,
+        convex_client::leptos::FunctionResult::Value(json!(null))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn action_completes_immediately() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let action = tokio::spawn(async move { client.action("counter:reset", json!({})).await });
+
+    let mut connection = server.accept().await?;
+    let (request_id, _, _) = connection.receive_action().await?;
+    connection
+        .action_response(request_id, Ok(json!("done")))
+        .await?;
+    let result = action.await??;
+    assert_eq!(
+        result,
+        convex_client::leptos::FunctionResult::Value(json!("done"))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn unanswered_action_times_out() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let timeout = Duration::from_millis(100);
+    let action = tokio::spawn(async move {
+        client
+            .action_with_timeout("counter:reset", json!({}), Some(timeout))
+            .await
+    });
+
+    let mut connection = server.accept().await?;
+    connection.receive_action().await?;
+    let err = action.await?.unwrap_err();
+    let err = err.downcast::<TimeoutError>()?;
+    assert_eq!(err.timeout, timeout);
+    assert_eq!(err.udf_path, "counter:reset");
+    Ok(())
+}
+
+#[tokio::test]
+async fn unsubscribed_query_is_kept_alive() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClientBuilder::new(server.deployment_url())
+        .with_query_keep_alive(Duration::from_millis(200), 8)
+        .build()
+        .await?;
+    let mut second_client = client.clone();
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    connection
+        .query_updated(queries[0].query_id, json!(5.0))
+        .await?;
+    assert_eq!(query.await??, 5.0);
+
+    // Subscribing again within the keep-alive is answered from the cache.
+    let cached =
+        tokio::time::timeout(Duration::from_secs(1), second_client.query(GetCount {})).await??;
+    assert_eq!(cached, 5.0);
+
+    // Once it runs out, the client unsubscribes.
+    let modifications = connection
+        .receive_until(|message| match message {
+            ClientMessage::ModifyQuerySet { modifications, .. } => Some(modifications),
+            _ => None,
+        })
+        .await?;
+    assert_eq!(
+        modifications,
+        vec![QuerySetModification::Remove {
+            query_id: queries[0].query_id
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn queries_subscribed_together_share_a_query_set_modification() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let client = ConvexClient::new(server.deployment_url()).await?;
+    let (mut first, mut second) = (client.clone(), client.clone());
+    let _count = tokio::spawn(async move { first.query(GetCount {}).await });
+    let _blob = tokio::spawn(async move { second.query(GetBlob {}).await });
+
+    let mut connection = server.accept().await?;
+    let (base_version, modifications) = connection
+        .receive_until(|message| match message {
+            ClientMessage::ModifyQuerySet {
+                base_version,
+                modifications,
+                ..
+            } => Some((base_version, modifications)),
+            _ => None,
+        })
+        .await?;
+    assert_eq!(base_version, 0);
+    assert_eq!(modifications.len(), 2);
+    assert!(
+        modifications
+            .iter()
+            .all(|modification| matches!(modification, QuerySetModification::Add(_)))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn resubscribing_before_the_unsubscribe_is_sent_keeps_the_query() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let client = ConvexClient::new(server.deployment_url()).await?;
+    let mut reader = client.clone();
+    let queries = tokio::spawn(async move {
+        let first = reader.query(GetCount {}).await?;
+        // The first query unsubscribes as it returns, in the same tick as
+        // this one subscribes again.
+        let second = reader.query(GetCount {}).await?;
+        anyhow::Ok((first, second))
+    });
+
+    let mut connection = server.accept().await?;
+    let queries_added = connection.receive_query_set().await?;
+    connection
+        .query_updated(queries_added[0].query_id, json!(5.0))
+        .await?;
+    // Still subscribed, the query answers the second read from its result.
+    let results = tokio::time::timeout(Duration::from_secs(1), queries).await???;
+    assert_eq!(results, (5.0, 5.0));
+
+    // Only the last unsubscribe reaches the server.
+    let (base_version, modifications) = connection
+        .receive_until(|message| match message {
+            ClientMessage::ModifyQuerySet {
+                base_version,
+                modifications,
+                ..
+            } => Some((base_version, modifications)),
+            _ => None,
+        })
+        .await?;
+    assert_eq!(base_version, 1);
+    assert_eq!(
+        modifications,
+        vec![QuerySetModification::Remove {
+            query_id: queries_added[0].query_id
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn reconnect_resends_queries_and_mutations() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let mut mutation_client = client.clone();
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    connection.receive_connect().await?;
+    connection.receive_query_set().await?;
+    let mutation =
+        tokio::spawn(async move { mutation_client.mutation(Increment { by: 1.0 }).await });
+    let (request_id, _, _) = connection.receive_mutation().await?;
+    connection.disconnect();
+
+    let mut connection = server.accept().await?;
+    let (connection_count, last_close_reason) = connection.receive_connect().await?;
+    assert_eq!(connection_count, 1);
+    assert_ne!(last_close_reason, "InitialConnect");
+    let queries = connection.receive_query_set().await?;
+    assert_eq!(queries.len(), 1);
+    let (resent_request_id, _, _) = connection.receive_mutation().await?;
+    assert_eq!(resent_request_id, request_id);
+
+    connection
+        .mutation_response(request_id, Ok(json!(null)))
+        .await?;
+    connection
+        .query_updated(queries[0].query_id, json!(1.0))
+        .await?;
+    assert_eq!(query.await??, 1.0);
+    mutation.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_results_carry_the_version_of_the_latest_transition() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let client = ConvexClient::new(server.deployment_url()).await?;
+    let mut watch = client.watch_all();
+    let _pages = client.paginated_query(ListNumbers {}, 2);
+    let mut connection = server.accept().await?;
+    let page_query = connection.receive_query_set().await?.remove(0);
+    let mut reader = client.clone();
+    let count = tokio::spawn(async move { reader.query(GetCount {}).await });
+    let count_query_id = connection.receive_query_set().await?[0].query_id;
+
+    let (page, journal) = first_page(&[1.0, 2.0], &page_query);
+    let ts = connection
+        .query_updated_with_journal(page_query.query_id, page, journal)
+        .await?;
+    let snapshot = watch.next().await.expect("client stopped");
+    let versions: Vec<_> = snapshot
+        .iter()
+        .map(|(id, _)| snapshot.version(id).map(|version| version.ts))
+        .collect();
+    // The count has no result yet.
+    assert_eq!(versions, vec![Some(ts), None]);
+
+    // The page didn't change, but its result is current at the new version
+    // too.
+    let ts = connection.query_updated(count_query_id, json!(3.0)).await?;
+    let snapshot = watch.next().await.expect("client stopped");
+    let versions: Vec<_> = snapshot
+        .iter()
+        .map(|(id, _)| snapshot.version(id).map(|version| version.ts))
+        .collect();
+    assert_eq!(versions, vec![Some(ts), Some(ts)]);
+    assert_eq!(count.await??, 3.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reconnect_keeps_page_boundaries() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let client = ConvexClient::new(server.deployment_url()).await?;
+    let mut pages = client.paginated_query(ListNumbers {}, 2);
+    // The results after the next change, skipping the loading states.
+    let mut next_results = async |previous: &[f64]| -> anyhow::Result<Vec<f64>> {
+        loop {
+            let update = tokio::time::timeout(Duration::from_secs(5), pages.next()).await?;
+            let results = update.expect("subscription ended")?.results;
+            if !results.is_empty() && results != previous {
+                return Ok(results);
+            }
+        }
+    };
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    assert_eq!(queries[0].journal, None);
+    let (page, journal) = first_page(&[2.0, 3.0, 4.0], &queries[0]);
+    connection
+        .query_updated_with_journal(queries[0].query_id, page, journal)
+        .await?;
+    assert_eq!(next_results(&[]).await?, vec![2.0, 3.0]);
+    connection.disconnect();
+
+    // An item is inserted before the end of the page while the client is
+    // away. Resumed from its journal, the page grows to keep its end rather
+    // than dropping its last item.
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    assert_eq!(queries[0].journal, Some(Some("3".to_string())));
+    let (page, journal) = first_page(&[1.0, 2.0, 3.0, 4.0], &queries[0]);
+    connection
+        .query_updated_with_journal(queries[0].query_id, page, journal)
+        .await?;
+    assert_eq!(next_results(&[2.0, 3.0]).await?, vec![1.0, 2.0, 3.0]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn start_version_mismatch_forces_reconnect() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    // Skip a version: the client must reject the transition and reconnect.
+    connection.transition(vec![]).await?;
+    let mut skipped = connection.version();
+    skipped.ts = skipped.ts.succ()?;
+    connection
+        .send(convex_test_server::ServerMessage::Transition {
+            start_version: skipped,
+            end_version: skipped,
+            modifications: vec![],
+        })
+        .await?;
+
+    let mut connection = server.accept().await?;
+    connection.receive_connect().await?;
+    let resent = connection.receive_until(|message| match message {
+        ClientMessage::ModifyQuerySet { modifications, .. } => Some(modifications),
+        _ => None,
+    });
+    assert_eq!(resent.await?.len(), 1);
+    connection
+        .query_updated(queries[0].query_id, json!(3.0))
+        .await?;
+    assert_eq!(query.await??, 3.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejected_auth_is_reported_and_dropped() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let auth_errors = Arc::new(Mutex::new(Vec::new()));
+    let on_auth_error = auth_errors.clone();
+    let mut client = ConvexClientBuilder::new(server.deployment_url())
+        .with_on_auth_error(move |error| on_auth_error.lock().unwrap().push(error))
+        .build()
+        .await?;
+    client.set_auth(Some("expired".to_string())).await;
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    connection.receive_query_set().await?;
+    connection
+        .send(convex_test_server::ServerMessage::AuthError {
+            error_message: "Token expired".to_string(),
+            base_version: Some(0),
+            auth_update_attempted: Some(false),
+        })
+        .await?;
+
+    // The client reconnects without the rejected token.
+    let mut connection = server.accept().await?;
+    connection.receive_connect().await?;
+    let ClientMessage::ModifyQuerySet { modifications, .. } = connection.receive().await? else {
+        panic!("Expected the query set to be resent without authenticating first");
+    };
+    let QuerySetModification::Add(resent) = &modifications[0] else {
+        panic!("Expected the query to be re-added");
+    };
+    connection
+        .query_updated(resent.query_id, json!(2.0))
+        .await?;
+    assert_eq!(query.await??, 2.0);
+    assert_eq!(
+        *auth_errors.lock().unwrap(),
+        vec![AuthError {
+            message: "Token expired".to_string(),
+            auth_update_attempted: Some(false),
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn fatal_error_stops_the_client() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    let mut mutation_client = client.clone();
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    connection.receive_query_set().await?;
+    connection
+        .send(convex_test_server::ServerMessage::FatalError {
+            error_message: "Deployment deleted".to_string(),
+        })
+        .await?;
+
+    assert!(query.await?.is_err());
+    assert!(
+        mutation_client
+            .mutation(Increment { by: 1.0 })
+            .await
+            .is_err()
+    );
+    let reconnect = tokio::time::timeout(Duration::from_millis(500), server.accept()).await;
+    assert!(
+        reconnect.is_err(),
+        "The client reconnected after a fatal error"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejected_fetched_token_is_fetched_again_once() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let auth_errors = Arc::new(Mutex::new(Vec::new()));
+    let on_auth_error = auth_errors.clone();
+    let mut client = ConvexClientBuilder::new(server.deployment_url())
+        .with_on_auth_error(move |error| on_auth_error.lock().unwrap().push(error))
+        .build()
+        .await?;
+    let fetches = Arc::new(Mutex::new(Vec::new()));
+    let fetched = fetches.clone();
+    client
+        .set_auth_fetcher(move |force_refresh| {
+            let mut fetched = fetched.lock().unwrap();
+            fetched.push(force_refresh);
+            let token = format!("token-{}", fetched.len());
+            async move { Some(token) }
+        })
+        .await;
+    let rejected = || convex_test_server::ServerMessage::AuthError {
+        error_message: "Token expired".to_string(),
+        base_version: Some(0),
+        auth_update_attempted: Some(false),
+    };
+
+    let mut connection = server.accept().await?;
+    assert_eq!(receive_token(&mut connection).await?, (0, "token-1".into()));
+    connection.send(rejected()).await?;
+
+    // The client reconnects with a freshly fetched token.
+    let mut connection = server.accept().await?;
+    assert_eq!(receive_token(&mut connection).await?, (0, "token-2".into()));
+    assert!(auth_errors.lock().unwrap().is_empty());
+    connection.send(rejected()).await?;
+
+    // The fresh token is rejected too, so the client gives up.
+    let mut connection = server.accept().await?;
+    connection.receive_connect().await?;
+    let message = connection.receive().await?;
+    assert!(
+        matches!(message, ClientMessage::ModifyQuerySet { .. }),
+        "Expected the client to reconnect unauthenticated, got {message:?}"
+    );
+    assert_eq!(*fetches.lock().unwrap(), vec![false, true]);
+    assert_eq!(auth_errors.lock().unwrap().len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetched_token_is_refreshed_before_it_expires() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let mut client = ConvexClient::new(server.deployment_url()).await?;
+    // Expires in just over the 10 second leeway.
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + 12;
+    let claims = base64::encode_config(json!({ "exp": exp }).to_string(), base64::URL_SAFE_NO_PAD);
+    let jwt = format!("e30.{claims}.signature");
+    client
+        .set_auth_fetcher(move |force_refresh| {
+            let token = if force_refresh {
+                "refreshed".to_string()
+            } else {
+                jwt.clone()
+            };
+            async move { Some(token) }
+        })
+        .await;
+
+    let mut connection = server.accept().await?;
+    let (base_version, _) = receive_token(&mut connection).await?;
+    assert_eq!(base_version, 0);
+    assert_eq!(
+        receive_token(&mut connection).await?,
+        (1, "refreshed".into())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn pooled_clients_authenticate_as_their_user() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let pool = ConvexClientPool::new(server.deployment_url(), |user_id, _| {
+        let token = format!("token-{user_id}");
+        async move { Some(token) }
+    });
+
+    let _alice = pool.client("alice").await?;
+    let mut connection = server.accept().await?;
+    assert_eq!(
+        receive_token(&mut connection).await?,
+        (0, "token-alice".into())
+    );
+
+    // Alice's client is reused, Bob gets one of his own.
+    let _alice_again = pool.client("alice").await?;
+    let _bob = pool.client("bob").await?;
+    let mut connection = server.accept().await?;
+    assert_eq!(
+        receive_token(&mut connection).await?,
+        (0, "token-bob".into())
+    );
+    assert_eq!(pool.num_clients().await, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn full_pool_closes_an_idle_client_to_make_room() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let pool =
+        ConvexClientPool::new(server.deployment_url(), |_, _| async { None }).with_max_clients(1);
+    let alice = pool.client("alice").await?;
+    let mut alice_connection = server.accept().await?;
+
+    let Err(err) = pool.client("bob").await else {
+        panic!("Expected the pool to be full");
+    };
+    assert!(err.to_string().contains("in use"), "{err}");
+
+    drop(alice);
+    let _bob = pool.client("bob").await?;
+    let _bob_connection = server.accept().await?;
+    let closed = tokio::time::timeout(
+        Duration::from_secs(1),
+        alice_connection.receive_until(|_| None::<()>),
+    )
+    .await?;
+    assert!(closed.is_err(), "Alice's client is still connected");
+    assert_eq!(pool.num_clients().await, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_closes_idle_clients() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let pool = ConvexClientPool::new(server.deployment_url(), |_, _| async { None })
+        .with_idle_timeout(Duration::from_millis(50));
+    let _alice = pool.client("alice").await?;
+    let _alice_connection = server.accept().await?;
+    // Only the pool holds Bob's client.
+    pool.client("bob").await?;
+    let mut bob_connection = server.accept().await?;
+
+    let closed = tokio::time::timeout(
+        Duration::from_secs(1),
+        bob_connection.receive_until(|_| None::<()>),
+    )
+    .await?;
+    assert!(closed.is_err(), "Bob's client is still connected");
+    assert_eq!(pool.num_clients().await, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_keeps_clients_with_live_subscriptions() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let pool = ConvexClientPool::new(server.deployment_url(), |_, _| async { None })
+        .with_idle_timeout(Duration::from_millis(50));
+    // Only the subscription and the stream outlive the client.
+    let mut subscription = pool
+        .client("alice")
+        .await?
+        .subscribe("counter:get", json!({}))
+        .await?;
+    let watch = pool.client("alice").await?.watch_all();
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(pool.num_clients().await, 1);
+    connection
+        .query_updated(queries[0].query_id, json!(1.0))
+        .await?;
+    let result = tokio::time::timeout(Duration::from_secs(1), subscription.next()).await?;
+    assert_eq!(
+        result.map(|result| result.into_result()),
+        Some(Ok(json!(1.0)))
+    );
+
+    drop((subscription, watch));
+    let closed = tokio::time::timeout(
+        Duration::from_secs(1),
+        connection.receive_until(|_| None::<()>),
+    )
+    .await?;
+    assert!(closed.is_err(), "Alice's client is still connected");
+    assert_eq!(pool.num_clients().await, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn concurrent_calls_for_a_user_connect_once() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let pool = ConvexClientPool::new(server.deployment_url(), |_, _| async { None });
+
+    let (alice, alice_again) = tokio::join!(pool.client("alice"), pool.client("alice"));
+    let (_alice, _alice_again) = (alice?, alice_again?);
+    let _connection = server.accept().await?;
+
+    let second = tokio::time::timeout(Duration::from_millis(200), server.accept()).await;
+    assert!(second.is_err(), "Alice's client connected twice");
+    assert_eq!(pool.num_clients().await, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_emptied_and_refilled_keeps_closing_idle_clients() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let pool = ConvexClientPool::new(server.deployment_url(), |_, _| async { None })
+        .with_idle_timeout(Duration::from_millis(50));
+    pool.client("alice").await?;
+    let _alice_connection = server.accept().await?;
+    pool.remove("alice").await;
+
+    // Refilled before the first reaper wakes up.
+    pool.client("bob").await?;
+    let mut bob_connection = server.accept().await?;
+    let closed = tokio::time::timeout(
+        Duration::from_secs(1),
+        bob_connection.receive_until(|_| None::<()>),
+    )
+    .await?;
+    assert!(closed.is_err(), "Bob's client is still connected");
+    assert_eq!(pool.num_clients().await, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn observer_sees_queries_mutations_and_reconnects() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let events = EventLog::default();
+    let client = ConvexClientBuilder::new(server.deployment_url())
+        .with_observer(events.clone())
+        .build()
+        .await?;
+    let mut query_client = client.clone();
+    let mut mutation_client = client.clone();
+    let query = tokio::spawn(async move { query_client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    connection
+        .transition(vec![StateModification::QueryUpdated {
+            query_id: queries[0].query_id,
+            value: json!(1.0),
+            log_lines: LogLinesMessage(vec!["[LOG] counted".into()]),
+            journal: None,
+        }])
+        .await?;
+    assert_eq!(query.await??, 1.0);
+    assert_eq!(
+        events.take(),
+        vec![
+            "subscribe counter.js:get",
+            "transition updating 1",
+            "query counter.js:get logged [LOG] counted",
+            "first result",
+        ]
+    );
+
+    let mutation =
+        tokio::spawn(async move { mutation_client.mutation(Increment { by: 1.0 }).await });
+    let (request_id, _, _) = connection.receive_mutation().await?;
+    let ts = connection
+        .mutation_response(request_id, Ok(json!(null)))
+        .await?;
+    connection.transition_at(ts.unwrap(), vec![]).await?;
+    mutation.await??;
+    assert_eq!(
+        events.take(),
+        vec![
+            "mutation sent counter.js:increment",
+            "mutation acknowledged",
+            "transition updating 0",
+            "mutation observed",
+        ]
+    );
+
+    connection.disconnect();
+    server.accept().await?.receive_query_set().await?;
+    assert_eq!(events.take(), vec!["reconnect"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn mutation_log_lines_reach_the_log_sink() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let logs = LogCapture::default();
+    let client = ConvexClientBuilder::new(server.deployment_url())
+        .with_log_sink(logs.clone())
+        .build()
+        .await?;
+    let mut mutation_client = client.clone();
+    let mutation =
+        tokio::spawn(async move { mutation_client.mutation(Increment { by: 2.0 }).await });
+
+    let mut connection = server.accept().await?;
+    let (request_id, _, _) = connection.receive_mutation().await?;
+    connection
+        .send(ServerMessage::MutationResponse {
+            request_id,
+            result: Err(ErrorPayload::Message("too big".into())),
+            ts: None,
+            log_lines: LogLinesMessage(vec!["[WARN] checking 2".into(), "unprefixed".into()]),
+        })
+        .await?;
+    assert_eq!(
+        mutation.await??,
+        convex_client::leptos::FunctionResult::ErrorMessage("too big".into())
+    );
+
+    let lines = logs.0.lock().unwrap().clone();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.kind == FunctionKind::Mutation
+        && line.udf_path.to_string() == "counter.js:increment"
+        && line.args_hash == lines[0].args_hash));
+    assert_eq!(
+        (lines[0].level, lines[0].message.as_str()),
+        (LogLevel::Warn, "checking 2")
+    );
+    assert_eq!(
+        (lines[1].level, lines[1].message.as_str()),
+        (LogLevel::Log, "unprefixed")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn persisted_mutations_are_replayed_in_order() -> anyhow::Result<()> {
+    let mut store = SharedStore::default();
+    for (key, by) in [("first", 1.0), ("second", 2.0)] {
+        store.insert(PersistedMutation {
+            key: key.into(),
+            udf_path: "counter:increment".into(),
+            args: json!({ "by": by }),
+        });
+    }
+    let mut server = MockConvexServer::start().await?;
+    let _client = ConvexClientBuilder::new(server.deployment_url())
+        .with_mutation_store(store.clone())
+        .build()
+        .await?;
+
+    let mut connection = server.accept().await?;
+    let (first, _, args) = connection.receive_mutation().await?;
+    assert_eq!(args, vec![json!({ "by": 1.0 })]);
+    let (second, _, args) = connection.receive_mutation().await?;
+    assert_eq!(args, vec![json!({ "by": 2.0 })]);
+    assert!(first < second);
+
+    // Acknowledged mutations leave the store, the others stay for the next
+    // client.
+    connection.mutation_response(first, Ok(json!(null))).await?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while store.load().len() != 1 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    assert_eq!(store.load()[0].key, "second");
+    Ok(())
+}
+
+#[tokio::test]
+async fn recordings_redact_auth_tokens() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let buffer = SharedBuffer::default();
+    let mut client = ConvexClientBuilder::new(server.deployment_url())
+        .with_protocol_recorder(ProtocolRecorder::new(buffer.clone()))
+        .build()
+        .await?;
+    client.set_auth(Some("secret-jwt".to_string())).await;
+    let _query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let (_, token) = receive_token(&mut connection).await?;
+    assert_eq!(token, "secret-jwt");
+
+    let recording = String::from_utf8(buffer.0.lock().unwrap().clone())?;
+    assert!(!recording.contains("secret-jwt"), "{recording}");
+    let recording: Recording = recording.parse()?;
+    let authenticate = recording
+        .frames
+        .iter()
+        .find_map(|frame| match &frame.event {
+            ProtocolEvent::Sent { message } if message["type"] == "Authenticate" => Some(message),
+            _ => None,
+        })
+        .expect("the Authenticate message is recorded");
+    assert_eq!(authenticate["value"], REDACTED_TOKEN);
+    Ok(())
+}
+
+#[tokio::test]
+async fn recorded_session_replays() -> anyhow::Result<()> {
+    let mut server = MockConvexServer::start().await?;
+    let buffer = SharedBuffer::default();
+    let mut client = ConvexClientBuilder::new(server.deployment_url())
+        .with_protocol_recorder(ProtocolRecorder::new(buffer.clone()))
+        .build()
+        .await?;
+    // Keep the worker running once the query completes.
+    let _reader = client.clone();
+    let query = tokio::spawn(async move { client.query(GetCount {}).await });
+
+    let mut connection = server.accept().await?;
+    let queries = connection.receive_query_set().await?;
+    connection
+        .query_updated(queries[0].query_id, json!(42.0))
+        .await?;
+    assert_eq!(query.await??, 42.0);
+    // Wait for the unsubscription, the last frame of the session.
+    connection.receive_query_set().await?;
+
+    let recording: Recording = String::from_utf8(buffer.0.lock().unwrap().clone())?.parse()?;
+    assert_eq!(recording.frames.len(), 3);
+    let mut replayed = ConvexClientBuilder::new(server.deployment_url())
+        .replay(recording)
+        .await;
+    let result =
+        tokio::time::timeout(Duration::from_secs(1), replayed.query(GetCount {})).await??;
+    assert_eq!(result, 42.0);
+    Ok(())
+}
@@ -0,0 +1,656 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use anyhow::{Context, bail};
+use serde_json::Value;
+
+use crate::spec::{Field, FunctionSpec, FunctionType, Spec, Validator};
+
+const HEADER: &str = "\
+// @generated by convex-codegen from `npx convex function-spec`. Do not edit;
+// regenerate it after changing the functions in `convex/`.
+";
+
+/// The argument `paginationOptsValidator` adds to paginated queries.
+const PAGINATION_OPTS: &str = "paginationOpts";
+
+const DERIVES: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+/// Render the public queries and mutations in `spec` as one Rust module per
+/// Convex module.
+///
+/// Actions are skipped: the client has no typed trait for them.
+pub fn generate(spec: &Spec) -> anyhow::Result<String> {
+    let mut modules = BTreeMap::<String, Module>::new();
+    for function in &spec.functions {
+        if !function.is_public()
+            || !matches!(
+                function.function_type,
+                FunctionType::Query | FunctionType::Mutation
+            )
+        {
+            continue;
+        }
+        let Some(identifier) = &function.identifier else {
+            continue;
+        };
+        let (path, export) = identifier
+            .split_once(':')
+            .with_context(|| format!("Malformed function identifier {identifier}"))?;
+        let path = path
+            .rsplit_once('.')
+            .map_or(path, |(path, _extension)| path);
+        modules
+            .entry(module_ident(path))
+            .or_default()
+            .add_function(&format!("{path}:{export}"), export, function)
+            .with_context(|| format!("Failed to generate bindings for {identifier}"))?;
+    }
+
+    let mut out = HEADER.to_string();
+    for (ident, module) in modules {
+        out.push('\n');
+        module.render(&ident, &mut out);
+    }
+    Ok(out)
+}
+
+struct Module {
+    /// Type names taken in this module.
+    names: BTreeSet<String>,
+    items: Vec<String>,
+    /// The client traits implemented in this module.
+    traits: BTreeSet<&'static str>,
+    /// Whether any query is generic over its output, which needs
+    /// `DeserializeOwned`.
+    has_untyped_queries: bool,
+    has_ids: bool,
+}
+
+impl Default for Module {
+    fn default() -> Self {
+        Self {
+            // Imported into every module that has ids.
+            names: BTreeSet::from(["Id".to_string()]),
+            items: Vec::new(),
+            traits: BTreeSet::new(),
+            has_untyped_queries: false,
+            has_ids: false,
+        }
+    }
+}
+
+impl Module {
+    fn add_function(
+        &mut self,
+        udf_path: &str,
+        export: &str,
+        function: &FunctionSpec,
+    ) -> anyhow::Result<()> {
+        let name = self.claim_name(&pascal_case(export));
+        let kind = match function.function_type {
+            FunctionType::Query => "query",
+            FunctionType::Mutation => "mutation",
+            _ => bail!("Only queries and mutations are generated"),
+        };
+        let doc = format!("/// The `{udf_path}` {kind}.\n");
+        // Paginated queries get their `paginationOpts` from `PaginatedQuery`.
+        let paginated = function.function_type == FunctionType::Query
+            && matches!(
+                &function.args,
+                Some(Validator::Object { value }) if value.contains_key(PAGINATION_OPTS)
+            );
+        match &function.args {
+            Some(Validator::Object { value }) if paginated => {
+                let mut fields = value.clone();
+                fields.remove(PAGINATION_OPTS);
+                self.push_struct(&name, &doc, &fields)?
+            }
+            Some(Validator::Object { value }) => self.push_struct(&name, &doc, value)?,
+            // Functions without an args validator take anything.
+            None | Some(Validator::Any) => self.items.push(format!(
+                "{doc}{DERIVES}\npub struct {name}(pub serde_json::Value);\n"
+            )),
+            Some(other) => bail!("Unsupported args validator {other:?}"),
+        }
+        self.items.push(format!(
+            "impl {name} {{\n    pub const PATH: &str = \"{udf_path}\";\n}}\n"
+        ));
+
+        let returns = match &function.returns {
+            None | Some(Validator::Any) => None,
+            Some(returns) => Some(returns),
+        };
+        let name_fn = "    fn name(&self) -> String {\n        Self::PATH.to_string()\n    }\n";
+        let item = if function.function_type == FunctionType::Query {
+            let (query_trait, output) = if paginated {
+                let output = match returns.and_then(page_item) {
+                    Some(item) => Some(self.rust_type(item, &format!("{name}Item"))?),
+                    None => None,
+                };
+                ("PaginatedQuery", output)
+            } else {
+                let output = match returns {
+                    Some(returns) => Some(self.rust_type(returns, &format!("{name}Output"))?),
+                    None => None,
+                };
+                ("Query", output)
+            };
+            self.traits.insert(query_trait);
+            match output {
+                Some(output) => {
+                    format!("impl {query_trait}<{output}> for {name} {{\n{name_fn}}}\n")
+                }
+                // Without a `returns` validator the caller picks the type to
+                // read the result as.
+                None => {
+                    self.has_untyped_queries = true;
+                    format!(
+                        "impl<T> {query_trait}<T> for {name}\nwhere\n    T: DeserializeOwned + Send + Sync + 'static,\n{{\n{name_fn}}}\n"
+                    )
+                }
+            }
+        } else {
+            self.traits.insert("Mutation");
+            // Without a `returns` validator the output is left as JSON.
+            let output = match returns {
+                Some(returns) => self.rust_type(returns, &format!("{name}Output"))?,
+                None => "serde_json::Value".to_string(),
+            };
+            format!("impl Mutation for {name} {{\n    type Output = {output};\n\n{name_fn}}}\n")
+        };
+        self.items.push(item);
+        Ok(())
+    }
+
+    /// The Rust type of values matching `validator`, generating a struct or
+    /// enum named after `hint` if it needs one.
+    fn rust_type(&mut self, validator: &Validator, hint: &str) -> anyhow::Result<String> {
+        Ok(match validator {
+            Validator::Null => "()".to_string(),
+            Validator::Number => "f64".to_string(),
+            Validator::Bigint => "i64".to_string(),
+            Validator::Boolean => "bool".to_string(),
+            Validator::String => "String".to_string(),
+            Validator::Id { table_name } => {
+                self.has_ids = true;
+                format!("Id<tables::{}>", pascal_case(table_name))
+            }
+            Validator::Bytes => "convex_client::value::Bytes".to_string(),
+            Validator::Any => "serde_json::Value".to_string(),
+            Validator::Literal { value } => match value {
+                Value::String(_) => "String".to_string(),
+                Value::Number(_) => "f64".to_string(),
+                Value::Bool(_) => "bool".to_string(),
+                _ => "serde_json::Value".to_string(),
+            },
+            Validator::Array { value } => {
+                format!("Vec<{}>", self.rust_type(value, &format!("{hint}Item"))?)
+            }
+            Validator::Record { values } => format!(
+                "std::collections::BTreeMap<String, {}>",
+                self.rust_type(&values.field_type, &format!("{hint}Value"))?
+            ),
+            Validator::Object { value } => {
+                let name = self.claim_name(hint);
+                self.push_struct(&name, "", value)?;
+                name
+            }
+            Validator::Union { value } => self.union_type(value, hint)?,
+        })
+    }
+
+    /// Unions of a type and null become options, and unions of string
+    /// literals become enums. Anything else is left as JSON.
+    fn union_type(&mut self, variants: &[Validator], hint: &str) -> anyhow::Result<String> {
+        let non_null: Vec<_> = variants
+            .iter()
+            .filter(|variant| !matches!(variant, Validator::Null))
+            .collect();
+        let nullable = non_null.len() < variants.len();
+        let literals: Option<Vec<&str>> = non_null
+            .iter()
+            .map(|variant| match variant {
+                Validator::Literal {
+                    value: Value::String(literal),
+                } => Some(literal.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let inner = match (non_null.as_slice(), literals) {
+            ([], _) => return Ok("()".to_string()),
+            ([single], _) => self.rust_type(single, hint)?,
+            (_, Some(literals)) => {
+                let name = self.claim_name(hint);
+                self.push_enum(&name, &literals);
+                name
+            }
+            _ => return Ok("serde_json::Value".to_string()),
+        };
+        Ok(if nullable {
+            format!("Option<{inner}>")
+        } else {
+            inner
+        })
+    }
+
+    fn push_struct(
+        &mut self,
+        name: &str,
+        doc: &str,
+        fields: &BTreeMap<String, Field>,
+    ) -> anyhow::Result<()> {
+        let mut body = String::new();
+        for (field_name, field) in fields {
+            let ident = field_ident(field_name);
+            let mut ty = self.rust_type(
+                &field.field_type,
+                &format!("{name}{}", pascal_case(field_name)),
+            )?;
+            let mut serde_attrs = Vec::new();
+            if ident.trim_start_matches("r#") != field_name {
+                serde_attrs.push(format!("rename = \"{field_name}\""));
+            }
+            if field.optional {
+                serde_attrs.push("default, skip_serializing_if = \"Option::is_none\"".to_string());
+                if !ty.starts_with("Option<") {
+                    ty = format!("Option<{ty}>");
+                }
+            }
+            if !serde_attrs.is_empty() {
+                writeln!(body, "    #[serde({})]", serde_attrs.join(", ")).unwrap();
+            }
+            writeln!(body, "    pub {ident}: {ty},").unwrap();
+        }
+        let body = if body.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{\n{body}}}")
+        };
+        self.items
+            .push(format!("{doc}{DERIVES}\npub struct {name} {body}\n"));
+        Ok(())
+    }
+
+    fn push_enum(&mut self, name: &str, literals: &[&str]) {
+        let mut taken = BTreeSet::new();
+        let mut body = String::new();
+        for literal in literals {
+            let mut variant = pascal_case(literal);
+            if !variant.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                variant = format!("V{variant}");
+            }
+            let variant = unique(&mut taken, &variant);
+            writeln!(body, "    #[serde(rename = \"{literal}\")]\n    {variant},").unwrap();
+        }
+        self.items
+            .push(format!("{DERIVES}\npub enum {name} {{\n{body}}}\n"));
+    }
+
+    fn claim_name(&mut self, name: &str) -> String {
+        unique(&mut self.names, name)
+    }
+
+    fn render(&self, ident: &str, out: &mut String) {
+        writeln!(out, "pub mod {ident} {{").unwrap();
+        if self.has_ids {
+            writeln!(out, "    use common::convex::{{Id, tables}};").unwrap();
+        }
+        let traits: Vec<_> = self.traits.iter().copied().collect();
+        match traits.as_slice() {
+            [] => {}
+            [single] => writeln!(out, "    use convex_client::leptos::{single};").unwrap(),
+            _ => writeln!(
+                out,
+                "    use convex_client::leptos::{{{}}};",
+                traits.join(", ")
+            )
+            .unwrap(),
+        }
+        if self.has_untyped_queries {
+            writeln!(out, "    use serde::de::DeserializeOwned;").unwrap();
+        }
+        writeln!(out, "    use serde::{{Deserialize, Serialize}};").unwrap();
+        for item in &self.items {
+            out.push('\n');
+            for line in item.lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    writeln!(out, "    {line}").unwrap();
+                }
+            }
+        }
+        writeln!(out, "}}").unwrap();
+    }
+}
+
+/// The validator of the items in a paginated query's `page`.
+fn page_item(returns: &Validator) -> Option<&Validator> {
+    let Validator::Object { value } = returns else {
+        return None;
+    };
+    match &value.get("page")?.field_type {
+        Validator::Array { value } => Some(value),
+        _ => None,
+    }
+}
+
+/// `name`, or `name` with the first free numeric suffix if it is taken.
+fn unique(taken: &mut BTreeSet<String>, name: &str) -> String {
+    let mut candidate = name.to_string();
+    let mut suffix = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{name}{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out.trim_end_matches('_').to_string()
+}
+
+/// The Rust field name for the Convex field `name`, such as `creation_time`
+/// for `_creationTime`.
+fn field_ident(name: &str) -> String {
+    let mut ident = snake_case(name);
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident = format!("field_{ident}");
+    }
+    if matches!(ident.as_str(), "self" | "super" | "crate") {
+        format!("{ident}_")
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{ident}")
+    } else {
+        ident
+    }
+}
+
+/// The Rust module for the Convex module at `path`, such as `admin_users` for
+/// `admin/users`.
+fn module_ident(path: &str) -> String {
+    field_ident(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rust_type(validator: Value) -> (String, Module) {
+        let mut module = Module::default();
+        let validator = serde_json::from_value(validator).unwrap();
+        let ty = module.rust_type(&validator, "Hint").unwrap();
+        (ty, module)
+    }
+
+    fn ty(validator: Value) -> String {
+        rust_type(validator).0
+    }
+
+    fn spec(functions: Value) -> String {
+        generate(&serde_json::from_value(json!({ "functions": functions })).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(ty(json!({ "type": "null" })), "()");
+        assert_eq!(ty(json!({ "type": "number" })), "f64");
+        assert_eq!(ty(json!({ "type": "bigint" })), "i64");
+        assert_eq!(ty(json!({ "type": "boolean" })), "bool");
+        assert_eq!(ty(json!({ "type": "string" })), "String");
+        assert_eq!(
+            ty(json!({ "type": "bytes" })),
+            "convex_client::value::Bytes"
+        );
+        assert_eq!(ty(json!({ "type": "any" })), "serde_json::Value");
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(ty(json!({ "type": "literal", "value": "a" })), "String");
+        assert_eq!(ty(json!({ "type": "literal", "value": 1 })), "f64");
+        assert_eq!(ty(json!({ "type": "literal", "value": true })), "bool");
+    }
+
+    #[test]
+    fn ids_name_their_table() {
+        let (ty, module) = rust_type(json!({ "type": "id", "tableName": "messageReactionCounts" }));
+        assert_eq!(ty, "Id<tables::MessageReactionCounts>");
+        assert!(module.has_ids);
+        assert_eq!(
+            self::ty(json!({ "type": "id", "tableName": "_storage" })),
+            "Id<tables::Storage>"
+        );
+    }
+
+    #[test]
+    fn arrays_and_records() {
+        assert_eq!(
+            ty(json!({ "type": "array", "value": { "type": "string" } })),
+            "Vec<String>"
+        );
+        assert_eq!(
+            ty(json!({
+                "type": "record",
+                "keys": { "type": "string" },
+                "values": { "fieldType": { "type": "number" }, "optional": false },
+            })),
+            "std::collections::BTreeMap<String, f64>"
+        );
+    }
+
+    #[test]
+    fn objects_become_structs() {
+        let (ty, module) = rust_type(json!({
+            "type": "object",
+            "value": {
+                "_creationTime": { "fieldType": { "type": "number" }, "optional": false },
+                "type": { "fieldType": { "type": "string" }, "optional": true },
+            },
+        }));
+        assert_eq!(ty, "Hint");
+        let [item] = module.items.as_slice() else {
+            panic!("expected one item, got {:?}", module.items);
+        };
+        assert!(item.contains("pub struct Hint {"), "{item}");
+        assert!(
+            item.contains("#[serde(rename = \"_creationTime\")]\n    pub creation_time: f64,"),
+            "{item}"
+        );
+        assert!(
+            item.contains(
+                "#[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub r#type: Option<String>,"
+            ),
+            "{item}"
+        );
+    }
+
+    #[test]
+    fn nullable_unions_become_options() {
+        assert_eq!(
+            ty(json!({ "type": "union", "value": [{ "type": "string" }, { "type": "null" }] })),
+            "Option<String>"
+        );
+        assert_eq!(
+            ty(json!({ "type": "union", "value": [{ "type": "null" }] })),
+            "()"
+        );
+    }
+
+    #[test]
+    fn literal_unions_become_enums() {
+        let (ty, module) = rust_type(json!({
+            "type": "union",
+            "value": [
+                { "type": "literal", "value": "Online" },
+                { "type": "literal", "value": "Do not disturb" },
+            ],
+        }));
+        assert_eq!(ty, "Hint");
+        assert!(
+            module.items[0].contains("#[serde(rename = \"Do not disturb\")]\n    DoNotDisturb,"),
+            "{}",
+            module.items[0]
+        );
+    }
+
+    #[test]
+    fn other_unions_stay_json() {
+        assert_eq!(
+            ty(json!({ "type": "union", "value": [{ "type": "string" }, { "type": "number" }] })),
+            "serde_json::Value"
+        );
+    }
+
+    #[test]
+    fn typed_returns_implement_the_trait() {
+        let code = spec(json!([{
+            "identifier": "files.js:generateUploadUrl",
+            "functionType": "Mutation",
+            "visibility": { "kind": "public" },
+            "args": { "type": "object", "value": {} },
+            "returns": { "type": "string" },
+        }]));
+        assert!(code.contains("pub mod files {"), "{code}");
+        assert!(
+            code.contains("pub const PATH: &str = \"files:generateUploadUrl\";"),
+            "{code}"
+        );
+        assert!(
+            code.contains("impl Mutation for GenerateUploadUrl {\n        type Output = String;"),
+            "{code}"
+        );
+    }
+
+    #[test]
+    fn untyped_queries_are_generic_over_their_output() {
+        let code = spec(json!([{
+            "identifier": "user.js:getMembers",
+            "functionType": "Query",
+            "visibility": { "kind": "public" },
+            "args": {
+                "type": "object",
+                "value": {
+                    "user": { "fieldType": { "type": "id", "tableName": "users" }, "optional": false },
+                },
+            },
+            "returns": { "type": "any" },
+        }]));
+        assert!(code.contains("use common::convex::{Id, tables};"), "{code}");
+        assert!(code.contains("use serde::de::DeserializeOwned;"), "{code}");
+        assert!(code.contains("pub user: Id<tables::Users>,"), "{code}");
+        assert!(
+            code.contains(
+                "impl<T> Query<T> for GetMembers\n    where\n        T: DeserializeOwned + Send + Sync + 'static,"
+            ),
+            "{code}"
+        );
+    }
+
+    #[test]
+    fn untyped_mutations_output_json() {
+        let code = spec(json!([{
+            "identifier": "task.js:add",
+            "functionType": "Mutation",
+            "visibility": { "kind": "public" },
+        }]));
+        assert!(
+            code.contains("impl Mutation for Add {\n        type Output = serde_json::Value;"),
+            "{code}"
+        );
+        assert!(!code.contains("DeserializeOwned"), "{code}");
+    }
+
+    #[test]
+    fn paginated_queries_leave_out_their_options() {
+        let code = spec(json!([{
+            "identifier": "messages.js:list",
+            "functionType": "Query",
+            "visibility": { "kind": "public" },
+            "args": {
+                "type": "object",
+                "value": {
+                    "channel": { "fieldType": { "type": "string" }, "optional": false },
+                    "paginationOpts": {
+                        "fieldType": { "type": "object", "value": {} },
+                        "optional": false,
+                    },
+                },
+            },
+            "returns": {
+                "type": "object",
+                "value": {
+                    "page": {
+                        "fieldType": { "type": "array", "value": { "type": "string" } },
+                        "optional": false,
+                    },
+                    "isDone": { "fieldType": { "type": "boolean" }, "optional": false },
+                },
+            },
+        }]));
+        assert!(
+            code.contains("pub struct List {\n        pub channel: String,\n    }"),
+            "{code}"
+        );
+        assert!(
+            code.contains("impl PaginatedQuery<String> for List {"),
+            "{code}"
+        );
+        assert!(
+            code.contains("use convex_client::leptos::PaginatedQuery;"),
+            "{code}"
+        );
+    }
+
+    #[test]
+    fn internal_functions_and_actions_are_skipped() {
+        let code = spec(json!([
+            {
+                "identifier": "user.js:sync",
+                "functionType": "Action",
+                "visibility": { "kind": "public" },
+            },
+            {
+                "identifier": "user.js:purge",
+                "functionType": "Mutation",
+                "visibility": { "kind": "internal" },
+            },
+        ]));
+        assert_eq!(code, HEADER);
+    }
+}
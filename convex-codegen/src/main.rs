@@ -0,0 +1,90 @@
+//! Generates typed [`Query`] and [`Mutation`] bindings for the public
+//! functions of a Convex deployment, so that a change to a validator in
+//! `convex/` becomes a compile error instead of a deserialization failure.
+//!
+//! ```sh
+//! npx convex function-spec > convex/function-spec.json
+//! cargo run -p convex-codegen -- convex/function-spec.json api/src/generated.rs
+//! ```
+//!
+//! Either path may be `-` for stdin or stdout. With `--check`, nothing is
+//! written and the command fails if the output file is out of date; the
+//! crate's tests run it against the checked-in spec and bindings. The check
+//! compares tokens, so it doesn't depend on rustfmt being installed.
+//!
+//! [`Query`]: ../convex_client/leptos/trait.Query.html
+//! [`Mutation`]: ../convex_client/leptos/trait.Mutation.html
+
+mod generate;
+mod spec;
+
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, anyhow, bail};
+
+use crate::spec::Spec;
+
+fn main() -> anyhow::Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let check = args.first().is_some_and(|arg| arg == "--check");
+    if check {
+        args.remove(0);
+    }
+    let [input, output] = args.as_slice() else {
+        bail!("Usage: convex-codegen [--check] <function-spec.json> <output.rs>");
+    };
+
+    let spec = if input == "-" {
+        let mut spec = String::new();
+        std::io::stdin().read_to_string(&mut spec)?;
+        spec
+    } else {
+        std::fs::read_to_string(input).with_context(|| format!("Failed to read {input}"))?
+    };
+    let spec: Spec = serde_json::from_str(&spec).context("Failed to parse the function spec")?;
+    let code = format(generate::generate(&spec)?);
+
+    if check {
+        let current =
+            std::fs::read_to_string(output).with_context(|| format!("Failed to read {output}"))?;
+        if tokens(&current)? != tokens(&code)? {
+            bail!("{output} is out of date; rerun convex-codegen without --check");
+        }
+    } else if output == "-" {
+        std::io::stdout().write_all(code.as_bytes())?;
+    } else {
+        std::fs::write(output, code).with_context(|| format!("Failed to write {output}"))?;
+    }
+    Ok(())
+}
+
+/// The tokens of `code`, ignoring how it is formatted.
+fn tokens(code: &str) -> anyhow::Result<String> {
+    let tokens: proc_macro2::TokenStream = code
+        .parse()
+        .map_err(|err| anyhow!("Failed to parse the bindings: {err}"))?;
+    Ok(tokens.to_string())
+}
+
+/// Run `code` through rustfmt if it is installed, so the output passes
+/// `cargo fmt --check`.
+fn format(code: String) -> String {
+    let formatted = (|| {
+        let mut rustfmt = Command::new("rustfmt")
+            .args(["--edition", "2024", "--emit", "stdout"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        rustfmt.stdin.take()?.write_all(code.as_bytes()).ok()?;
+        let output = rustfmt.wait_with_output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8(output.stdout).ok())
+            .flatten()
+    })();
+    formatted.unwrap_or(code)
+}
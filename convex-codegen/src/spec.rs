@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The output of `npx convex function-spec`.
+#[derive(Deserialize)]
+pub struct Spec {
+    pub functions: Vec<FunctionSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionSpec {
+    /// `"<module path>.js:<export>"`, missing for HTTP actions.
+    pub identifier: Option<String>,
+    pub function_type: FunctionType,
+    pub visibility: Option<Visibility>,
+    pub args: Option<Validator>,
+    pub returns: Option<Validator>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Deserialize)]
+pub enum FunctionType {
+    Query,
+    Mutation,
+    Action,
+    HttpAction,
+}
+
+#[derive(Deserialize)]
+pub struct Visibility {
+    pub kind: String,
+}
+
+impl FunctionSpec {
+    pub fn is_public(&self) -> bool {
+        self.visibility
+            .as_ref()
+            .is_some_and(|visibility| visibility.kind == "public")
+    }
+}
+
+/// A Convex validator, as exported in function specs.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Validator {
+    Null,
+    Number,
+    Bigint,
+    Boolean,
+    String,
+    Bytes,
+    Any,
+    Literal {
+        value: Value,
+    },
+    Id {
+        #[serde(rename = "tableName")]
+        table_name: String,
+    },
+    Array {
+        value: Box<Validator>,
+    },
+    /// Record keys are always strings on the wire, so only the values are
+    /// read.
+    Record {
+        values: Box<Field>,
+    },
+    Object {
+        value: BTreeMap<String, Field>,
+    },
+    Union {
+        value: Vec<Validator>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Field {
+    pub field_type: Validator,
+    pub optional: bool,
+}
@@ -0,0 +1,39 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn check(rustfmt: bool) -> Output {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let mut command = Command::new(env!("CARGO_BIN_EXE_convex-codegen"));
+    command
+        .arg("--check")
+        .arg(root.join("convex/function-spec.json"))
+        .arg(root.join("api/src/generated.rs"));
+    if !rustfmt {
+        command.env("PATH", "");
+    }
+    command.output().unwrap()
+}
+
+/// The checked-in bindings must match what the checked-in function spec
+/// generates.
+#[test]
+fn checked_in_bindings_are_up_to_date() {
+    let output = check(true);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Toolchains without rustfmt produce unformatted bindings, which still
+/// have to pass the check.
+#[test]
+fn check_does_not_need_rustfmt() {
+    let output = check(false);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
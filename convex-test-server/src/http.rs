@@ -0,0 +1,149 @@
+//! A stub of a Convex deployment's HTTP function endpoints.
+//!
+//! [`MockHttpServer`] hands every request the client sends to
+//! `/api/query`, `/api/mutation` or `/api/action` to the test as a
+//! [`MockHttpRequest`], which the test answers by hand.
+
+use anyhow::Context;
+use futures::StreamExt;
+use futures::channel::mpsc;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::TIMEOUT;
+
+/// A local HTTP server answering function calls as scripted by the test.
+///
+/// Every connection carries a single request. The server stops listening
+/// when dropped.
+pub struct MockHttpServer {
+    deployment_url: String,
+    requests: mpsc::UnboundedReceiver<MockHttpRequest>,
+    accept_loop: JoinHandle<()>,
+}
+
+impl MockHttpServer {
+    /// Start listening on a random local port.
+    pub async fn start() -> anyhow::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let deployment_url = format!("http://{}", listener.local_addr()?);
+        let (request_sender, requests) = mpsc::unbounded();
+        let accept_loop = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let request_sender = request_sender.clone();
+                tokio::spawn(async move {
+                    if let Ok(request) = MockHttpRequest::read(stream).await {
+                        let _ = request_sender.unbounded_send(request);
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            deployment_url,
+            requests,
+            accept_loop,
+        })
+    }
+
+    /// The url to build a client with, as for a real deployment.
+    pub fn deployment_url(&self) -> &str {
+        &self.deployment_url
+    }
+
+    /// Wait for the next request.
+    pub async fn receive(&mut self) -> anyhow::Result<MockHttpRequest> {
+        tokio::time::timeout(TIMEOUT, self.requests.next())
+            .await
+            .context("Timed out waiting for a request")?
+            .context("Mock server stopped listening")
+    }
+}
+
+impl Drop for MockHttpServer {
+    fn drop(&mut self) {
+        self.accept_loop.abort();
+    }
+}
+
+/// A request received by a [`MockHttpServer`], waiting for its response.
+pub struct MockHttpRequest {
+    /// The request path, e.g. `/api/query`.
+    pub path: String,
+    /// The `Authorization` header, if any.
+    pub authorization: Option<String>,
+    /// The JSON body.
+    pub body: Value,
+    stream: BufReader<TcpStream>,
+}
+
+impl MockHttpRequest {
+    async fn read(stream: TcpStream) -> anyhow::Result<Self> {
+        let mut stream = BufReader::new(stream);
+        let mut line = String::new();
+        stream.read_line(&mut line).await?;
+        let path = line
+            .split_whitespace()
+            .nth(1)
+            .context("Malformed request line")?
+            .to_string();
+        let mut authorization = None;
+        let mut content_length = 0;
+        loop {
+            line.clear();
+            stream.read_line(&mut line).await?;
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':').context("Malformed header")?;
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse()?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_string());
+            }
+        }
+        let mut body = vec![0; content_length];
+        stream.read_exact(&mut body).await?;
+        Ok(Self {
+            path,
+            authorization,
+            body: serde_json::from_slice(&body)?,
+            stream,
+        })
+    }
+
+    /// Answer with `status` and a JSON `body`.
+    pub async fn respond(mut self, status: u16, body: Value) -> anyhow::Result<()> {
+        let body = body.to_string();
+        let response = format!(
+            "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        self.stream.write_all(response.as_bytes()).await?;
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// Answer that the function returned `value` after logging `log_lines`.
+    pub async fn success(self, value: Value, log_lines: &[&str]) -> anyhow::Result<()> {
+        self.respond(
+            200,
+            json!({ "status": "success", "value": value, "logLines": log_lines }),
+        )
+        .await
+    }
+
+    /// Answer that the function failed with `error_message`, and the
+    /// `ConvexError` data `error_data` if any.
+    pub async fn error(self, error_message: &str, error_data: Option<Value>) -> anyhow::Result<()> {
+        let mut body = json!({ "status": "error", "errorMessage": error_message, "logLines": [] });
+        if let Some(error_data) = error_data {
+            body["errorData"] = error_data;
+        }
+        self.respond(560, body).await
+    }
+}
@@ -0,0 +1,371 @@
+//! An in-process mock of a Convex deployment's sync endpoint.
+//!
+//! [`MockConvexServer`] listens on a local port and hands every incoming
+//! WebSocket to the test as a [`MockConnection`]. The test then drives the
+//! protocol by hand: it reads the [`ClientMessage`]s the client sends and
+//! answers with scripted [`ServerMessage`]s, or drops the connection to
+//! exercise the client's reconnect logic.
+//!
+//! ```no_run
+//! # use convex_test_server::MockConvexServer;
+//! # async fn test() -> anyhow::Result<()> {
+//! let mut server = MockConvexServer::start().await?;
+//! // Point a client at `server.deployment_url()`, then:
+//! let mut connection = server.accept().await?;
+//! connection.receive_connect().await?;
+//! let queries = connection.receive_query_set().await?;
+//! connection
+//!     .query_updated(queries[0].query_id, serde_json::json!(42))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`MockHttpServer`] does the same for the HTTP function endpoints.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use convex_sync_types::{
+    ClientMessage, ErrorPayload, IdentityVersion, LogLinesMessage, Query, QueryId,
+    QuerySetModification, QuerySetVersion, SerializedQueryJournal, SessionRequestSeqNumber,
+    StateModification, StateVersion, Timestamp,
+};
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+pub use convex_sync_types::ServerMessage;
+
+pub use self::http::{MockHttpRequest, MockHttpServer};
+
+mod http;
+
+/// How long [`MockConvexServer::accept`] and [`MockConnection::receive`]
+/// wait before failing the test.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A local WebSocket server speaking the Convex sync protocol.
+///
+/// The server stops listening when dropped.
+pub struct MockConvexServer {
+    deployment_url: String,
+    connections: mpsc::UnboundedReceiver<MockConnection>,
+    accept_loop: JoinHandle<()>,
+}
+
+impl MockConvexServer {
+    /// Start listening on a random local port.
+    pub async fn start() -> anyhow::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let deployment_url = format!("http://{}", listener.local_addr()?);
+        let (connection_sender, connections) = mpsc::unbounded();
+        let accept_loop = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+                    continue;
+                };
+                if connection_sender
+                    .unbounded_send(MockConnection::new(ws))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+        Ok(Self {
+            deployment_url,
+            connections,
+            accept_loop,
+        })
+    }
+
+    /// The url to build a client with, as for a real deployment.
+    pub fn deployment_url(&self) -> &str {
+        &self.deployment_url
+    }
+
+    /// Wait for the next client connection.
+    pub async fn accept(&mut self) -> anyhow::Result<MockConnection> {
+        tokio::time::timeout(TIMEOUT, self.connections.next())
+            .await
+            .context("Timed out waiting for a client to connect")?
+            .context("Mock server stopped listening")
+    }
+}
+
+impl Drop for MockConvexServer {
+    fn drop(&mut self) {
+        self.accept_loop.abort();
+    }
+}
+
+/// A single client WebSocket accepted by a [`MockConvexServer`].
+///
+/// The connection follows the query set and identity versions sent by the
+/// client so that transitions sent through it always line up with the
+/// client's view of the protocol.
+pub struct MockConnection {
+    ws: WebSocketStream<TcpStream>,
+    version: StateVersion,
+    query_set: QuerySetVersion,
+    identity: IdentityVersion,
+}
+
+impl MockConnection {
+    fn new(ws: WebSocketStream<TcpStream>) -> Self {
+        Self {
+            ws,
+            version: StateVersion::initial(),
+            query_set: 0,
+            identity: 0,
+        }
+    }
+
+    /// The version the next transition starts from.
+    pub fn version(&self) -> StateVersion {
+        self.version
+    }
+
+    /// Receive the next message from the client.
+    pub async fn receive(&mut self) -> anyhow::Result<ClientMessage> {
+        loop {
+            let frame = tokio::time::timeout(TIMEOUT, self.ws.next())
+                .await
+                .context("Timed out waiting for a client message")?
+                .context("Client closed the connection")??;
+            let Message::Text(text) = frame else {
+                // Pings are answered by tungstenite itself.
+                continue;
+            };
+            let json: Value = serde_json::from_str(&text)?;
+            let message = ClientMessage::try_from(json)?;
+            match &message {
+                ClientMessage::ModifyQuerySet { new_version, .. } => {
+                    self.query_set = *new_version;
+                }
+                ClientMessage::Authenticate { base_version, .. } => {
+                    self.identity = base_version + 1;
+                }
+                _ => {}
+            }
+            return Ok(message);
+        }
+    }
+
+    /// Receive messages until `f` returns `Some`, discarding the others.
+    pub async fn receive_until<T>(
+        &mut self,
+        mut f: impl FnMut(ClientMessage) -> Option<T>,
+    ) -> anyhow::Result<T> {
+        loop {
+            if let Some(value) = f(self.receive().await?) {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Receive the `Connect` message opening the session and return its
+    /// `connection_count` and `last_close_reason`.
+    pub async fn receive_connect(&mut self) -> anyhow::Result<(u32, String)> {
+        match self.receive().await? {
+            ClientMessage::Connect {
+                connection_count,
+                last_close_reason,
+                ..
+            } => Ok((connection_count, last_close_reason)),
+            message => anyhow::bail!("Expected Connect, got {message:?}"),
+        }
+    }
+
+    /// Receive messages until the next `ModifyQuerySet` and return the
+    /// queries it adds.
+    pub async fn receive_query_set(&mut self) -> anyhow::Result<Vec<Query>> {
+        self.receive_until(|message| match message {
+            ClientMessage::ModifyQuerySet { modifications, .. } => Some(
+                modifications
+                    .into_iter()
+                    .filter_map(|modification| match modification {
+                        QuerySetModification::Add(query) => Some(query),
+                        QuerySetModification::Remove { .. } => None,
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Receive messages until the next `Mutation` and return its request id,
+    /// path and arguments.
+    pub async fn receive_mutation(
+        &mut self,
+    ) -> anyhow::Result<(SessionRequestSeqNumber, String, Vec<Value>)> {
+        self.receive_until(|message| match message {
+            ClientMessage::Mutation {
+                request_id,
+                udf_path,
+                args,
+                ..
+            } => Some((request_id, udf_path.to_string(), args)),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Receive messages until the next `Action` and return its request id,
+    /// path and arguments.
+    pub async fn receive_action(
+        &mut self,
+    ) -> anyhow::Result<(SessionRequestSeqNumber, String, Vec<Value>)> {
+        self.receive_until(|message| match message {
+            ClientMessage::Action {
+                request_id,
+                udf_path,
+                args,
+                ..
+            } => Some((request_id, udf_path.to_string(), args)),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Send a raw message to the client.
+    pub async fn send(&mut self, message: ServerMessage) -> anyhow::Result<()> {
+        let json = Value::from(message).to_string();
+        self.ws.send(Message::text(json)).await?;
+        Ok(())
+    }
+
+    fn next_ts(&self) -> anyhow::Result<Timestamp> {
+        self.version.ts.succ()
+    }
+
+    /// Send a transition from the current version to the client's latest
+    /// query set and identity, at the next timestamp.
+    pub async fn transition(
+        &mut self,
+        modifications: Vec<StateModification>,
+    ) -> anyhow::Result<Timestamp> {
+        let ts = self.next_ts()?;
+        self.transition_at(ts, modifications).await?;
+        Ok(ts)
+    }
+
+    /// Like [`transition`](Self::transition), ending at `ts`.
+    pub async fn transition_at(
+        &mut self,
+        ts: Timestamp,
+        modifications: Vec<StateModification>,
+    ) -> anyhow::Result<()> {
+        let start_version = self.version;
+        let end_version = StateVersion {
+            query_set: self.query_set,
+            identity: self.identity,
+            ts,
+        };
+        self.send(ServerMessage::Transition {
+            start_version,
+            end_version,
+            modifications,
+        })
+        .await?;
+        self.version = end_version;
+        Ok(())
+    }
+
+    /// Send a transition setting the value of `query_id`.
+    pub async fn query_updated(
+        &mut self,
+        query_id: QueryId,
+        value: Value,
+    ) -> anyhow::Result<Timestamp> {
+        self.query_updated_with_journal(query_id, value, None).await
+    }
+
+    /// Like [`query_updated`](Self::query_updated), with the query journal
+    /// the client should send back when it re-adds the query.
+    pub async fn query_updated_with_journal(
+        &mut self,
+        query_id: QueryId,
+        value: Value,
+        journal: SerializedQueryJournal,
+    ) -> anyhow::Result<Timestamp> {
+        self.transition(vec![StateModification::QueryUpdated {
+            query_id,
+            value,
+            log_lines: LogLinesMessage(vec![]),
+            journal,
+        }])
+        .await
+    }
+
+    /// Send a transition failing `query_id` with `error_message`.
+    pub async fn query_failed(
+        &mut self,
+        query_id: QueryId,
+        error_message: &str,
+    ) -> anyhow::Result<Timestamp> {
+        self.transition(vec![StateModification::QueryFailed {
+            query_id,
+            error_message: error_message.to_string(),
+            log_lines: LogLinesMessage(vec![]),
+            journal: None,
+            error_data: None,
+        }])
+        .await
+    }
+
+    /// Answer the mutation `request_id`.
+    ///
+    /// A successful mutation is committed at the next timestamp but only
+    /// completes on the client once a transition reaches that timestamp, which
+    /// is returned.
+    pub async fn mutation_response(
+        &mut self,
+        request_id: SessionRequestSeqNumber,
+        result: Result<Value, String>,
+    ) -> anyhow::Result<Option<Timestamp>> {
+        let ts = match &result {
+            Ok(_) => Some(self.next_ts()?),
+            Err(_) => None,
+        };
+        self.send(ServerMessage::MutationResponse {
+            request_id,
+            result: result.map_err(ErrorPayload::Message),
+            ts,
+            log_lines: LogLinesMessage(vec![]),
+        })
+        .await?;
+        Ok(ts)
+    }
+
+    /// Answer the action `request_id`.
+    pub async fn action_response(
+        &mut self,
+        request_id: SessionRequestSeqNumber,
+        result: Result<Value, String>,
+    ) -> anyhow::Result<()> {
+        self.send(ServerMessage::ActionResponse {
+            request_id,
+            result: result.map_err(ErrorPayload::Message),
+            log_lines: LogLinesMessage(vec![]),
+        })
+        .await
+    }
+
+    /// Drop the socket without a close handshake, as a network failure would.
+    pub fn disconnect(self) {
+        drop(self.ws);
+    }
+
+    /// Close the socket cleanly.
+    pub async fn close(mut self) -> anyhow::Result<()> {
+        self.ws.close(None).await?;
+        Ok(())
+    }
+}
@@ -17,6 +17,7 @@ import type * as category from "../category.js";
 import type * as channel from "../channel.js";
 import type * as files from "../files.js";
 import type * as friends from "../friends.js";
+import type * as identity from "../identity.js";
 import type * as invitations from "../invitations.js";
 import type * as member from "../member.js";
 import type * as messages from "../messages.js";
@@ -42,6 +43,7 @@ declare const fullApi: ApiFromModules<{
   channel: typeof channel;
   files: typeof files;
   friends: typeof friends;
+  identity: typeof identity;
   invitations: typeof invitations;
   member: typeof member;
   messages: typeof messages;
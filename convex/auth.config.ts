@@ -0,0 +1,14 @@
+// The Rust server signs identity tokens for the signed-in user and serves
+// the keys to check them at `${AUTH_ISSUER}/.well-known/jwks.json`.
+// AUTH_ISSUER must be set both in the server's .env and in this deployment.
+export default {
+  providers: [
+    {
+      type: "customJwt",
+      applicationID: "convex",
+      issuer: process.env.AUTH_ISSUER,
+      jwks: `${process.env.AUTH_ISSUER}/.well-known/jwks.json`,
+      algorithm: "RS256",
+    },
+  ],
+};
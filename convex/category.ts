@@ -1,18 +1,17 @@
 import { v } from "convex/values";
 import { mutation, query } from "./_generated/server";
 import { ConvexError } from "convex/values";
+import { currentUser } from "./identity";
 
 export const create = mutation({
   args: {
-    auth: v.int64(),
     server: v.id("servers"),
     name: v.string(),
   },
-  handler: async ({ db }, { server, name, auth }) => {
-    const user = await db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.id("categories"),
+  handler: async (ctx, { server, name }) => {
+    const { db } = ctx;
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
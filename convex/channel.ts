@@ -1,14 +1,15 @@
 import { v } from "convex/values";
 import { mutation, query } from "./_generated/server";
 import { ConvexError } from "convex/values";
+import { currentUser } from "./identity";
 
 export const get = query({
   args: {
     channelId: v.id("channels"),
     serverId: v.id("servers"),
-    auth: v.int64(), // Changed from memberId to auth
   },
-  handler: async ({ db }, { channelId, serverId, auth }) => {
+  handler: async (ctx, { channelId, serverId }) => {
+    const { db } = ctx;
     const channel = await db.get(channelId);
 
     if (!channel) {
@@ -19,11 +20,8 @@ export const get = query({
       throw new ConvexError("Channel does not belong to the specified server");
     }
 
-    // Authenticate user by authId
-    const user = await db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+    // The signed-in user
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -47,16 +45,14 @@ export const get = query({
 
 export const create = mutation({
   args: {
-    auth: v.int64(),
     server: v.id("servers"),
     category: v.optional(v.id("categories")),
     name: v.string(),
   },
-  handler: async ({ db }, { server, category, name, auth }) => {
-    const user = await db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.id("channels"),
+  handler: async (ctx, { server, category, name }) => {
+    const { db } = ctx;
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
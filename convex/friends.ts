@@ -1,17 +1,18 @@
 import { v } from "convex/values";
 import { mutation, query } from "./_generated/server";
 import { api } from "./_generated/api.js";
+import { currentUser as getCurrentUser } from "./identity";
 
 export const sendFriendRequest = mutation({
   args: {
     receiverId: v.id("users"),
-    auth: v.int64(),
   },
+  returns: v.union(
+    v.object({ message: v.string(), friendshipId: v.id("friends") }),
+    v.id("friends"),
+  ),
   handler: async (ctx, args) => {
-    const sender = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .unique();
+    const sender = await getCurrentUser(ctx);
 
     if (!sender) {
       throw new Error("Sender not found");
@@ -70,13 +71,10 @@ export const sendFriendRequest = mutation({
 export const acceptFriendRequest = mutation({
   args: {
     requestId: v.id("friends"),
-    auth: v.int64(),
   },
+  returns: v.object({ success: v.boolean() }),
   handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .unique();
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       throw new Error("Current user not found");
@@ -115,7 +113,6 @@ export const acceptFriendRequest = mutation({
 
     await ctx.runMutation(api.privateConversations.createOrGetConversation, {
       member2Id: request.sender,
-      auth: args.auth,
     });
 
     return { success: true };
@@ -126,13 +123,10 @@ export const acceptFriendRequest = mutation({
 export const declineFriendRequest = mutation({
   args: {
     friendshipId: v.id("friends"),
-    auth: v.int64(),
   },
+  returns: v.object({ success: v.boolean() }),
   handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .unique();
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       throw new Error("Current user not found");
@@ -171,14 +165,9 @@ export const declineFriendRequest = mutation({
 
 // Get all accepted friends for the current user
 export const getFriends = query({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .unique();
+  args: {},
+  handler: async (ctx) => {
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       return [];
@@ -211,14 +200,9 @@ export const getFriends = query({
 });
 
 export const getPendingFriendRequests = query({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+  args: {},
+  handler: async (ctx) => {
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       return [];
@@ -251,14 +235,9 @@ export const getPendingFriendRequests = query({
 
 // Get outgoing pending friend requests from the current user
 export const getSentFriendRequests = query({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+  args: {},
+  handler: async (ctx) => {
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       return [];
@@ -292,13 +271,9 @@ export const getSentFriendRequests = query({
 export const getFriendshipStatus = query({
   args: {
     userId: v.id("users"),
-    auth: v.int64(),
   },
   handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       return "user_not_found";
@@ -0,0 +1,50 @@
+import { ConvexError } from "convex/values";
+import type { QueryCtx } from "./_generated/server";
+import type { Id } from "./_generated/dataModel";
+
+// The auth id of the user the client is authenticated as, from the `sub` of
+// the identity token the Rust server minted, or null when signed out.
+export async function authId(ctx: QueryCtx): Promise<bigint | null> {
+  const identity = await ctx.auth.getUserIdentity();
+  if (identity === null) {
+    return null;
+  }
+  return BigInt(identity.subject);
+}
+
+// The user the client is authenticated as, or null when signed out or the
+// user hasn't been created yet.
+export async function currentUser(ctx: QueryCtx) {
+  const auth = await authId(ctx);
+  if (auth === null) {
+    return null;
+  }
+  return await ctx.db
+    .query("users")
+    .withIndex("by_auth", (q) => q.eq("authId", auth))
+    .unique();
+}
+
+// Like `authId`, for functions that can't run signed out.
+export async function requireAuthId(ctx: QueryCtx): Promise<bigint> {
+  const auth = await authId(ctx);
+  if (auth === null) {
+    throw new ConvexError("You need to be signed in");
+  }
+  return auth;
+}
+
+// The member `memberId` names, if it belongs to the user the client is
+// authenticated as; functions that act as a member check this before trusting
+// the id.
+export async function requireOwnMember(
+  ctx: QueryCtx,
+  memberId: Id<"members">,
+) {
+  const user = await currentUser(ctx);
+  const member = await ctx.db.get(memberId);
+  if (user === null || member === null || member.user !== user._id) {
+    throw new ConvexError("Member not found");
+  }
+  return member;
+}
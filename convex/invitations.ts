@@ -1,6 +1,7 @@
 import { mutation, query } from "./_generated/server";
 import { v } from "convex/values";
 import { Id } from "./_generated/dataModel.js";
+import { currentUser } from "./identity";
 
 export const createInvitation = mutation({
   args: {
@@ -8,10 +9,17 @@ export const createInvitation = mutation({
     member: v.id("members"),
     expiresInMinutes: v.number(), // Duration until the invitation expires in minutes
   },
+  returns: v.union(
+    v.object({
+      invitationCode: v.string(),
+      invitationId: v.id("invitations"),
+    }),
+    v.null(),
+  ),
   handler: async (ctx, { server, member, expiresInMinutes }) => {
     const m = await ctx.db.get(member);
     if (m === null || m.server !== server) {
-      return;
+      return null;
     }
 
     const now = Date.now();
@@ -107,6 +115,7 @@ export const deleteInvitation = mutation({
   args: {
     invitationId: v.id("invitations"),
   },
+  returns: v.null(),
   handler: async (ctx, args) => {
     // Optional: Add permission checks here (e.g., only server owner/admin can delete)
     const invitation = await ctx.db.get(args.invitationId);
@@ -120,9 +129,9 @@ export const deleteInvitation = mutation({
 export const joinServerWithInvitation = mutation({
   args: {
     invitationCode: v.string(),
-    userId: v.id("users"),
   },
-  handler: async (ctx, { invitationCode, userId }) => {
+  returns: v.union(v.id("members"), v.null()),
+  handler: async (ctx, { invitationCode }) => {
     const invitation = await ctx.db
       .query("invitations")
       .withIndex("by_invitation", (q) => q.eq("invitation", invitationCode))
@@ -132,10 +141,11 @@ export const joinServerWithInvitation = mutation({
       return null; // Invitation not found or expired
     }
 
-    const user = await ctx.db.get(userId);
+    const user = await currentUser(ctx);
     if (user === null) {
       throw new Error("User not found.");
     }
+    const userId = user._id;
 
     const existingMember = await ctx.db
       .query("members")
@@ -1,6 +1,7 @@
 import { v } from "convex/values";
 import type { Doc } from "./_generated/dataModel";
 import { mutation, query } from "./_generated/server";
+import { currentUser } from "./identity";
 
 export const getOnlineMembersByRole = query({
   args: {
@@ -73,15 +74,13 @@ export const getMembersByIds = query({
 
 export const setLastVisitedChannel = mutation({
   args: {
-    auth: v.int64(),
     member: v.id("members"),
     channel: v.id("channels"),
   },
-  handler: async ({ db }, { auth, channel, member }) => {
-    let user = await db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.null(),
+  handler: async (ctx, { channel, member }) => {
+    const { db } = ctx;
+    const user = await currentUser(ctx);
     if (user === null) {
       return;
     }
@@ -118,13 +117,10 @@ export const setLastVisitedChannel = mutation({
 export const getLastVisitedChannel = query({
   args: {
     member: v.id("members"),
-    auth: v.int64(),
   },
-  handler: async ({ db }, { member, auth }) => {
-    let user = await db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  handler: async (ctx, { member }) => {
+    const { db } = ctx;
+    const user = await currentUser(ctx);
     if (user === null) {
       return;
     }
@@ -1,34 +1,72 @@
 import { mutation, query } from "./_generated/server";
 import { Id, Doc } from "./_generated/dataModel.js";
 import { v } from "convex/values";
+import { paginationOptsValidator } from "convex/server";
 import { api } from "./_generated/api.js";
 
 import { ConvexError } from "convex/values";
 import type { QueryCtx } from "./_generated/server";
+import { currentUser, requireOwnMember } from "./identity";
+import { reactionResult } from "./schema.js";
 
 export const createMessage = mutation({
   args: {
     channelId: v.id("channels"),
-    senderId: v.id("members"),
     content: v.string(),
     referenceId: v.optional(v.id("messages")),
     pinned: v.optional(v.boolean()),
     mention_everyone: v.optional(v.boolean()),
     mention_roles: v.optional(v.array(v.id("roles"))),
+    clientMessageId: v.optional(v.string()),
   },
+  returns: v.id("messages"),
   handler: async (ctx, args) => {
+    const user = await currentUser(ctx);
+    if (!user) {
+      throw new ConvexError("User not found");
+    }
+
+    const channel = await ctx.db.get(args.channelId);
+    if (!channel) {
+      throw new ConvexError("Channel not found");
+    }
+
+    const sender = await ctx.db
+      .query("members")
+      .withIndex("by_server_and_user", (q) =>
+        q.eq("server", channel.server).eq("user", user._id),
+      )
+      .unique();
+    if (!sender) {
+      throw new ConvexError("Member not found in this server");
+    }
+
+    // A client replaying a message it already sent gets the original back.
+    if (args.clientMessageId !== undefined) {
+      const existing = await ctx.db
+        .query("messages")
+        .withIndex("by_client_message_id", (q) =>
+          q.eq("clientMessageId", args.clientMessageId),
+        )
+        .unique();
+      if (existing) {
+        return existing._id;
+      }
+    }
+
     const newMessage = {
       channel: args.channelId,
-      sender: args.senderId,
+      sender: sender._id,
       content: args.content,
       reference: args.referenceId,
       mention_everyone: args.mention_everyone ?? false,
       mention_roles: args.mention_roles ?? [],
+      clientMessageId: args.clientMessageId,
     };
     const messageId = await ctx.db.insert("messages", newMessage);
 
     await ctx.runMutation(api.unreadMessages.updateMemberChannelLastRead, {
-      memberId: args.senderId,
+      memberId: sender._id,
       channelId: args.channelId,
       messageId: messageId,
     });
@@ -157,6 +195,7 @@ export const getMessagesInChannel = query({
     memberId: v.id("members"),
   },
   handler: async (ctx, { channelId, memberId }) => {
+    await requireOwnMember(ctx, memberId);
     const messages = await ctx.db
       .query("messages")
       .withIndex("by_channel", (q) => q.eq("channel", channelId))
@@ -172,12 +211,37 @@ export const getMessagesInChannel = query({
   },
 });
 
+export const getPaginatedMessagesInChannel = query({
+  args: {
+    channelId: v.id("channels"),
+    memberId: v.id("members"),
+    paginationOpts: paginationOptsValidator,
+  },
+  handler: async (ctx, { channelId, memberId, paginationOpts }) => {
+    await requireOwnMember(ctx, memberId);
+    const results = await ctx.db
+      .query("messages")
+      .withIndex("by_channel", (q) => q.eq("channel", channelId))
+      .order("desc")
+      .paginate(paginationOpts);
+
+    const page = await Promise.all(
+      results.page.map((message) =>
+        getFullMessageDetails(ctx, message, channelId, memberId),
+      ),
+    );
+
+    return { ...results, page };
+  },
+});
+
 export const addAttachmentToMessage = mutation({
   args: {
     messageId: v.id("messages"),
     storageId: v.id("_storage"),
     name: v.string(),
   },
+  returns: v.id("attachments"),
   handler: async (ctx, args) => {
     const newAttachment = {
       message: args.messageId,
@@ -192,6 +256,7 @@ export const deleteMessage = mutation({
   args: {
     messageId: v.id("messages"),
   },
+  returns: v.null(),
   handler: async (ctx, args) => {
     const memberReactionsToDelete = await ctx.db
       .query("memberReactions")
@@ -242,6 +307,7 @@ export const updateMessage = mutation({
     pinned: v.optional(v.boolean()),
     content: v.optional(v.string()),
   },
+  returns: v.null(),
   handler: async (ctx, args) => {
     const updateFields: { pinned?: boolean; content?: string } = {};
     if (args.pinned !== undefined) {
@@ -260,7 +326,9 @@ export const addReaction = mutation({
     memberId: v.id("members"),
     emoji: v.string(),
   },
+  returns: reactionResult,
   handler: async (ctx, { messageId, memberId, emoji }) => {
+    await requireOwnMember(ctx, memberId);
     const existingMemberReaction = await ctx.db
       .query("memberReactions")
       .withIndex("by_message_member_emoji", (q) =>
@@ -307,7 +375,9 @@ export const removeReaction = mutation({
     memberId: v.id("members"),
     emoji: v.string(),
   },
+  returns: reactionResult,
   handler: async (ctx, { messageId, memberId, emoji }) => {
+    await requireOwnMember(ctx, memberId);
     const memberReactionToDelete = await ctx.db
       .query("memberReactions")
       .withIndex("by_message_member_emoji", (q) =>
@@ -344,15 +414,12 @@ export const removeReaction = mutation({
 
 export const pinMessage = mutation({
   args: {
-    auth: v.int64(),
     messageId: v.id("messages"),
     channelId: v.id("channels"),
   },
-  handler: async (ctx, { auth, messageId, channelId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.id("pinnedMessages"),
+  handler: async (ctx, { messageId, channelId }) => {
+    const user = await currentUser(ctx);
     if (!user) {
       throw new ConvexError("User not found");
     }
@@ -405,15 +472,12 @@ export const pinMessage = mutation({
 
 export const unpinMessage = mutation({
   args: {
-    auth: v.int64(),
     messageId: v.id("messages"),
     channelId: v.id("channels"),
   },
-  handler: async (ctx, { auth, messageId, channelId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.boolean(),
+  handler: async (ctx, { messageId, channelId }) => {
+    const user = await currentUser(ctx);
     if (!user) {
       throw new ConvexError("User not found");
     }
@@ -463,6 +527,7 @@ export const getPinnedMessages = query({
     memberId: v.id("members"),
   },
   handler: async (ctx, { channelId, memberId }) => {
+    await requireOwnMember(ctx, memberId);
     const pinnedMessageEntries = await ctx.db
       .query("pinnedMessages")
       .withIndex("by_channel", (q) => q.eq("channel", channelId))
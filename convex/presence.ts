@@ -1,8 +1,15 @@
 import { v } from "convex/values";
-import { mutation, query, QueryCtx, MutationCtx } from "./_generated/server.js";
-import { api } from "./_generated/api.js";
+import {
+  internalMutation,
+  mutation,
+  query,
+  QueryCtx,
+  MutationCtx,
+} from "./_generated/server.js";
+import { internal } from "./_generated/api.js";
 import { Id } from "./_generated/dataModel.js";
 import { presenceStatus } from "./schema.js";
+import { currentUser } from "./identity";
 
 type PresenceStatus = typeof presenceStatus.type;
 
@@ -36,11 +43,17 @@ async function updateMembersOnlineStatus(
 
 export const heartbeat = mutation({
   args: {
-    user: v.id("users"),
     sessionId: v.string(),
     interval: v.optional(v.number()),
   },
-  handler: async (ctx, { user, sessionId, interval = 10000 }) => {
+  returns: v.null(),
+  handler: async (ctx, { sessionId, interval = 10000 }) => {
+    const currentUserDoc = await currentUser(ctx);
+    if (currentUserDoc === null) {
+      return;
+    }
+    const user = currentUserDoc._id;
+
     let sessionRecord = await ctx.db
       .query("sessions")
       .withIndex("by_sessionId", (q) => q.eq("sessionId", sessionId))
@@ -68,7 +81,7 @@ export const heartbeat = mutation({
 
     const timeoutScheduledId = await ctx.scheduler.runAfter(
       interval * 2.5,
-      api.presence.disconnectSession,
+      internal.presence.disconnectSession,
       { userId: user, sessionId },
     );
 
@@ -79,7 +92,8 @@ export const heartbeat = mutation({
   },
 });
 
-export const disconnectSession = mutation({
+// Only scheduled by `heartbeat`, so clients can't sign other users out.
+export const disconnectSession = internalMutation({
   args: { userId: v.id("users"), sessionId: v.string() },
   handler: async (ctx, { userId, sessionId }) => {
     const sessionRecord = await ctx.db
@@ -124,14 +138,11 @@ export const getStatus = query({
 
 export const patchUserStatus = mutation({
   args: {
-    auth: v.int64(),
     status: presenceStatus,
   },
-  handler: async (ctx, { auth, status }) => {
-    let user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.null(),
+  handler: async (ctx, { status }) => {
+    const user = await currentUser(ctx);
     if (user === null) {
       return;
     }
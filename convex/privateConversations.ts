@@ -1,18 +1,16 @@
 import { v } from "convex/values";
 import { mutation, query } from "./_generated/server";
 import { type Id } from "./_generated/dataModel";
+import { currentUser as getCurrentUser } from "./identity";
 
 // Create or get a private conversation
 export const createOrGetConversation = mutation({
   args: {
     member2Id: v.id("users"),
-    auth: v.int64(),
   },
+  returns: v.id("conversations"),
   handler: async (ctx, args) => {
-    const member1 = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+    const member1 = await getCurrentUser(ctx);
 
     if (!member1) {
       throw new Error("Member 1 not found");
@@ -56,13 +54,10 @@ export const sendPrivateMessage = mutation({
     conversationId: v.id("conversations"),
     content: v.string(),
     referenceId: v.optional(v.id("privateMessages")),
-    auth: v.int64(),
   },
+  returns: v.id("privateMessages"),
   handler: async (ctx, args) => {
-    const sender = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+    const sender = await getCurrentUser(ctx);
 
     if (!sender) {
       throw new Error("Sender not found");
@@ -91,13 +86,9 @@ export const sendPrivateMessage = mutation({
 export const getPrivateMessages = query({
   args: {
     conversationId: v.id("conversations"),
-    auth: v.int64(),
   },
   handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       return [];
@@ -142,13 +133,10 @@ export const markPrivateMessagesRead = mutation({
   args: {
     conversationId: v.id("conversations"),
     lastReadMessageId: v.id("privateMessages"),
-    auth: v.int64(),
   },
+  returns: v.object({ success: v.boolean() }),
   handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       throw new Error("Current user not found");
@@ -191,13 +179,9 @@ export const markPrivateMessagesRead = mutation({
 export const getLastReadMessage = query({
   args: {
     conversationId: v.id("conversations"),
-    auth: v.int64(),
   },
   handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       return null;
@@ -216,14 +200,9 @@ export const getLastReadMessage = query({
 
 // Get all private conversations for the current user
 export const getMyConversations = query({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, args) => {
-    const currentUser = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", args.auth))
-      .first();
+  args: {},
+  handler: async (ctx) => {
+    const currentUser = await getCurrentUser(ctx);
 
     if (!currentUser) {
       return [];
@@ -1,11 +1,14 @@
 import { query, mutation } from "./_generated/server";
 import { v } from "convex/values";
+import { requireOwnMember } from "./identity";
+import { reactionResult } from "./schema.js";
 
 export const getMemberEmojis = query({
   args: {
     memberId: v.id("members"),
   },
   handler: async (ctx, args) => {
+    await requireOwnMember(ctx, args.memberId);
     const memberReactions = await ctx.db
       .query("memberReactions")
       .withIndex("by_member", (q) => q.eq("member", args.memberId))
@@ -26,7 +29,9 @@ export const addReaction = mutation({
     memberId: v.id("members"),
     emoji: v.string(),
   },
+  returns: reactionResult,
   handler: async (ctx, { messageId, memberId, emoji }) => {
+    await requireOwnMember(ctx, memberId);
     const existingMemberReaction = await ctx.db
       .query("memberReactions")
       .withIndex("by_message_member_emoji", (q) =>
@@ -73,7 +78,9 @@ export const removeReaction = mutation({
     memberId: v.id("members"),
     emoji: v.string(),
   },
+  returns: reactionResult,
   handler: async (ctx, { messageId, memberId, emoji }) => {
+    await requireOwnMember(ctx, memberId);
     const memberReactionToDelete = await ctx.db
       .query("memberReactions")
       .withIndex("by_message_member_emoji", (q) =>
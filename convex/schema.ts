@@ -8,6 +8,12 @@ export const presenceStatus = v.union(
   v.literal("Invisible"),
 );
 
+// What adding or removing a reaction returns.
+export const reactionResult = v.object({
+  success: v.boolean(),
+  reason: v.optional(v.string()),
+});
+
 export default defineSchema({
   channels: defineTable({
     name: v.string(),
@@ -26,7 +32,10 @@ export default defineSchema({
     content: v.string(),
     mention_everyone: v.boolean(),
     mention_roles: v.array(v.id("roles")),
-  }).index("by_channel", ["channel"]),
+    clientMessageId: v.optional(v.string()),
+  })
+    .index("by_channel", ["channel"])
+    .index("by_client_message_id", ["clientMessageId"]),
   memberReactions: defineTable({
     message: v.id("messages"),
     member: v.id("members"),
@@ -3,22 +3,18 @@ import { mutation, query } from "./_generated/server";
 import { ConvexError } from "convex/values";
 import { Id } from "./_generated/dataModel";
 import { api } from "./_generated/api.js";
+import { currentUser } from "./identity";
 
 export const create = mutation({
   args: {
     name: v.string(),
-    auth: v.int64(),
     storage: v.optional(v.id("_storage")),
     type: v.union(v.literal("public"), v.literal("private")),
   },
-  handler: async (
-    { db, storage: storageCtx },
-    { name, storage: storageId, auth, type },
-  ) => {
-    const user = await db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.id("servers"),
+  handler: async (ctx, { name, storage: storageId, type }) => {
+    const { db, storage: storageCtx } = ctx;
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -115,15 +111,12 @@ export const getCategories = query({
 
 export const setServerBannerUrl = mutation({
   args: {
-    auth: v.int64(),
     serverId: v.id("servers"),
     storageId: v.id("_storage"),
   },
-  handler: async (ctx, { auth, serverId, storageId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.union(v.string(), v.null()),
+  handler: async (ctx, { serverId, storageId }) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -183,15 +176,12 @@ export const setServerBannerUrl = mutation({
 
 export const setServerImageUrl = mutation({
   args: {
-    auth: v.int64(),
     serverId: v.id("servers"),
     storageId: v.id("_storage"),
   },
-  handler: async (ctx, { auth, serverId, storageId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.union(v.string(), v.null()),
+  handler: async (ctx, { serverId, storageId }) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -251,15 +241,12 @@ export const setServerImageUrl = mutation({
 
 export const updateServerDescription = mutation({
   args: {
-    auth: v.int64(),
     serverId: v.id("servers"),
     description: v.string(),
   },
-  handler: async (ctx, { auth, serverId, description }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.boolean(),
+  handler: async (ctx, { serverId, description }) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -303,14 +290,11 @@ export const updateServerDescription = mutation({
 
 export const removeServerImage = mutation({
   args: {
-    auth: v.int64(),
     serverId: v.id("servers"),
   },
-  handler: async (ctx, { auth, serverId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.union(v.boolean(), v.null()),
+  handler: async (ctx, { serverId }) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -364,14 +348,11 @@ export const removeServerImage = mutation({
 
 export const removeServerBanner = mutation({
   args: {
-    auth: v.int64(),
     serverId: v.id("servers"),
   },
-  handler: async (ctx, { auth, serverId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.union(v.boolean(), v.null()),
+  handler: async (ctx, { serverId }) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -424,14 +405,10 @@ export const removeServerBanner = mutation({
 });
 
 export const getPublicServers = query({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async ({ db }, { auth }) => {
-    const user = await db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  args: {},
+  handler: async (ctx) => {
+    const { db } = ctx;
+    const user = await currentUser(ctx);
 
     if (!user) {
       throw new ConvexError("User not found");
@@ -446,13 +423,14 @@ export const getPublicServers = query({
 export const joinServer = mutation({
   args: {
     serverId: v.id("servers"),
-    userId: v.id("users"),
   },
-  handler: async (ctx, { serverId, userId }) => {
-    const user = await ctx.db.get(userId);
+  returns: v.id("members"),
+  handler: async (ctx, { serverId }) => {
+    const user = await currentUser(ctx);
     if (user === null) {
       throw new Error("User not found.");
     }
+    const userId = user._id;
 
     const serverDoc = await ctx.db.get(serverId);
     if (!serverDoc) {
@@ -1,13 +1,16 @@
 import { mutation, query } from "./_generated/server";
 import { v } from "convex/values";
 import { Id } from "./_generated/dataModel";
+import { requireOwnMember } from "./identity";
 
 export const initializeMemberChannelLastReadOnJoin = mutation({
   args: {
     memberId: v.id("members"),
     channelId: v.id("channels"),
   },
+  returns: v.null(),
   handler: async (ctx, { memberId, channelId }) => {
+    await requireOwnMember(ctx, memberId);
     const existingLastRead = await ctx.db
       .query("memberChannelLastReads")
       .withIndex("by_member_and_channel", (q) =>
@@ -35,7 +38,9 @@ export const updateMemberChannelLastRead = mutation({
     channelId: v.id("channels"),
     messageId: v.id("messages"),
   },
+  returns: v.null(),
   handler: async (ctx, { memberId, channelId, messageId }) => {
+    await requireOwnMember(ctx, memberId);
     const existingLastRead = await ctx.db
       .query("memberChannelLastReads")
       .withIndex("by_member_and_channel", (q) =>
@@ -63,6 +68,7 @@ export const getUnreadMessagesCountInChannel = query({
     channelId: v.id("channels"),
   },
   handler: async (ctx, { memberId, channelId }) => {
+    await requireOwnMember(ctx, memberId);
     const memberLastRead = await ctx.db
       .query("memberChannelLastReads")
       .withIndex("by_member_and_channel", (q) =>
@@ -106,6 +112,7 @@ export const getUnreadCountsForAllChannelsForMember = query({
     memberId: v.id("members"),
   },
   handler: async (ctx, { memberId }) => {
+    await requireOwnMember(ctx, memberId);
     const memberChannelReads = await ctx.db
       .query("memberChannelLastReads")
       .withIndex("by_member", (q) => q.eq("member", memberId))
@@ -159,6 +166,7 @@ export const getLastReadMessageId = query({
     channelId: v.id("channels"),
   },
   handler: async (ctx, { memberId, channelId }) => {
+    await requireOwnMember(ctx, memberId);
     const memberLastRead = await ctx.db
       .query("memberChannelLastReads")
       .withIndex("by_member_and_channel", (q) =>
@@ -1,16 +1,12 @@
 import { v } from "convex/values";
 import { mutation, query } from "./_generated/server";
 import type { Doc } from "./_generated/dataModel";
+import { currentUser, requireAuthId } from "./identity";
 
 export const getServers = query({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, { auth }) => {
-    let user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  args: {},
+  handler: async (ctx) => {
+    const user = await currentUser(ctx);
     if (user === null) {
       return [];
     }
@@ -38,30 +34,21 @@ export const getServers = query({
 });
 
 export const getUser = query({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, { auth }) => {
-    return await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  args: {},
+  handler: async (ctx) => {
+    return await currentUser(ctx);
   },
 });
 
 export const getMemberForServerByUser = query({
   args: {
-    auth: v.int64(), // Changed from user to auth
     serverId: v.id("servers"),
   },
-  handler: async (ctx, { auth, serverId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  handler: async (ctx, { serverId }) => {
+    const user = await currentUser(ctx);
 
     if (user === null) {
-      return null; // User not found for auth ID
+      return null; // Signed out, or the user hasn't been created yet
     }
 
     const member = await ctx.db
@@ -102,11 +89,12 @@ export const getMembers = query({
 
 export const create = mutation({
   args: {
-    auth: v.int64(),
     name: v.string(),
     image_url: v.optional(v.string()),
   },
-  handler: async (ctx, { auth, name, image_url }) => {
+  returns: v.null(),
+  handler: async (ctx, { name, image_url }) => {
+    const auth = await requireAuthId(ctx);
     let user = await ctx.db.insert("users", { authId: auth, name, image_url });
     await ctx.db.insert("userStatus", {
       user: user,
@@ -118,14 +106,11 @@ export const create = mutation({
 
 export const setBannerUrl = mutation({
   args: {
-    auth: v.int64(),
     storageId: v.id("_storage"),
   },
-  handler: async (ctx, { auth, storageId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.union(v.string(), v.null()),
+  handler: async (ctx, { storageId }) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       return null;
@@ -176,14 +161,11 @@ export const setBannerUrl = mutation({
 
 export const setImageUrl = mutation({
   args: {
-    auth: v.int64(),
     storageId: v.id("_storage"),
   },
-  handler: async (ctx, { auth, storageId }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  returns: v.union(v.string(), v.null()),
+  handler: async (ctx, { storageId }) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       return null;
@@ -233,14 +215,10 @@ export const setImageUrl = mutation({
 });
 
 export const removeUserImage = mutation({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, { auth }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  args: {},
+  returns: v.union(v.boolean(), v.null()),
+  handler: async (ctx) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       return null;
@@ -278,14 +256,10 @@ export const removeUserImage = mutation({
 });
 
 export const removeUserBanner = mutation({
-  args: {
-    auth: v.int64(),
-  },
-  handler: async (ctx, { auth }) => {
-    const user = await ctx.db
-      .query("users")
-      .withIndex("by_auth", (q) => q.eq("authId", auth))
-      .unique();
+  args: {},
+  returns: v.union(v.boolean(), v.null()),
+  handler: async (ctx) => {
+    const user = await currentUser(ctx);
 
     if (!user) {
       return null;
@@ -1,11 +1,20 @@
+use std::time::Duration;
+
 use app::*;
-use auth::AuthUser;
-use axum::Router;
+use auth::tokens::{IdentityTokens, SigningKey, JWKS_PATH};
+use auth::{AuthSession, AuthUser};
+use axum::http::request::Parts;
+use axum::routing::get;
+use axum::{Json, Router};
 use axum_session::{SessionConfig, SessionLayer, SessionStore};
 use axum_session_auth::{AuthConfig, AuthSessionLayer};
 use axum_session_sqlx::SessionPgPool;
 use common::state::AppState;
-use convex_client::server::ConvexClient;
+use convex_client::logs::TracingLogSink;
+use convex_client::observer::TracingObserver;
+use convex_client::server::{
+    ConvexClientBuilder, ConvexClientPool, ConvexHttpClient, RenderingUser,
+};
 use dotenv::dotenv;
 use dotenv_codegen::dotenv;
 use leptos::logging::log;
@@ -14,6 +23,9 @@ use leptos_axum::{generate_route_list, LeptosRoutes};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 
+/// How often identity tokens start being signed with a new key.
+const KEY_ROTATION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
@@ -25,9 +37,38 @@ async fn main() {
         .await
         .expect("should make a PG pool.");
 
-    let convex_client = ConvexClient::new(convex_url)
+    let convex_client = ConvexClientBuilder::new(convex_url)
+        .with_observer(TracingObserver::default())
+        .with_log_sink(TracingLogSink)
+        .build()
         .await
         .expect("should make a Convec client");
+    let convex_http = ConvexHttpClient::new(convex_url).expect("should make a Convex HTTP client");
+
+    // Keep the key across restarts by setting it, or tokens minted before one
+    // are rejected until they are refreshed.
+    let signing_key = match std::env::var("IDENTITY_SIGNING_KEY") {
+        Ok(pem) => SigningKey::from_pem(&pem).expect("should read the identity signing key"),
+        Err(_) => SigningKey::generate().expect("should generate an identity signing key"),
+    };
+    let identity_tokens = IdentityTokens::new(dotenv!("AUTH_ISSUER"), signing_key);
+    tokio::spawn(rotate_signing_keys(identity_tokens.clone()));
+    // Clients authenticated as the users pages are rendered for.
+    let convex_pool = ConvexClientPool::new(convex_url, {
+        let identity_tokens = identity_tokens.clone();
+        move |user_id, _| {
+            let token = user_id
+                .parse()
+                .ok()
+                .and_then(|user_id| identity_tokens.mint(user_id).ok());
+            async move { token }
+        }
+    })
+    .with_builder(|builder| {
+        builder
+            .with_observer(TracingObserver::default())
+            .with_log_sink(TracingLogSink)
+    });
 
     let session_config = SessionConfig::default().with_table_name("axum_sessions");
     let auth_config = AuthConfig::<i64>::default();
@@ -50,13 +91,42 @@ async fn main() {
         pool: pool.clone(),
         routes: routes.clone(),
         convex: convex_client,
+        convex_http,
     };
 
     let app = Router::new()
-        .leptos_routes(&app_state, routes, {
-            let options = app_state.leptos_options.clone();
-            move || shell(options.clone())
-        })
+        .leptos_routes_with_context(
+            &app_state,
+            routes,
+            {
+                // Lets `UseQuery` render query results on the server.
+                let convex = app_state.convex.clone();
+                let identity_tokens = identity_tokens.clone();
+                move || {
+                    provide_context(convex.clone());
+                    provide_context(convex_pool.clone());
+                    provide_context(identity_tokens.clone());
+                    // Render the queries of a signed-in user as that user.
+                    let user = use_context::<Parts>().and_then(|parts| {
+                        parts.extensions.get::<AuthSession>()?.current_user.clone()
+                    });
+                    if let Some(user) = user {
+                        provide_context(RenderingUser(user.user().id.to_string()));
+                    }
+                }
+            },
+            {
+                let options = app_state.leptos_options.clone();
+                move || shell(options.clone())
+            },
+        )
+        .route(
+            JWKS_PATH,
+            get(move || {
+                let jwks = identity_tokens.jwks();
+                async move { Json(jwks) }
+            }),
+        )
         .fallback(leptos_axum::file_and_error_handler::<AppState, _>(shell))
         .layer(
             AuthSessionLayer::<AuthUser, i64, SessionPgPool, PgPool>::new(Some(pool.clone()))
@@ -73,3 +143,18 @@ async fn main() {
         .await
         .unwrap();
 }
+
+/// Sign identity tokens with a new key every [`KEY_ROTATION_INTERVAL`].
+async fn rotate_signing_keys(identity_tokens: IdentityTokens) {
+    let mut interval = tokio::time::interval(KEY_ROTATION_INTERVAL);
+    // The first tick completes immediately.
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match tokio::task::spawn_blocking(SigningKey::generate).await {
+            Ok(Ok(key)) => identity_tokens.rotate(key),
+            Ok(Err(e)) => log!("Failed to generate an identity signing key: {e:?}"),
+            Err(e) => log!("Failed to generate an identity signing key: {e:?}"),
+        }
+    }
+}
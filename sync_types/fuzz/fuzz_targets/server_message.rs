@@ -0,0 +1,18 @@
+#![no_main]
+
+use convex_sync_types::ServerMessage;
+use libfuzzer_sys::fuzz_target;
+use serde_json::Value as JsonValue;
+
+// Parse arbitrary frames the way the websocket worker does: malformed ones
+// must fail to parse, never panic.
+fuzz_target!(|frame: &[u8]| {
+    let Ok(json) = serde_json::from_slice::<JsonValue>(frame) else {
+        return;
+    };
+    if let Ok(message) = ServerMessage::try_from(json) {
+        // Whatever parses must also encode back to the same message.
+        let json = JsonValue::from(message.clone());
+        assert_eq!(ServerMessage::try_from(json).unwrap(), message);
+    }
+});
@@ -5,6 +5,8 @@ pub mod identifier;
 pub mod json;
 pub mod module_path;
 pub mod path;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod timestamp;
 pub mod types;
 pub mod udf_path;
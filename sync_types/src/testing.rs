@@ -0,0 +1,110 @@
+//! Proptest strategies for the protocol types, for round-trip tests of their
+//! JSON encoding.
+
+use std::collections::BTreeMap;
+
+use proptest::prelude::*;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::{SessionId, Timestamp, UdfPath, types::ErrorPayload};
+
+/// An arbitrary JSON value.
+///
+/// Numbers are integers, so that values also survive a trip through JSON
+/// text: serde_json may parse a float to a neighbour of the one it printed.
+pub fn arb_json() -> impl Strategy<Value = JsonValue> {
+    let leaf = prop_oneof![
+        Just(JsonValue::Null),
+        any::<bool>().prop_map(JsonValue::from),
+        any::<i64>().prop_map(JsonValue::from),
+        any::<u64>().prop_map(JsonValue::from),
+        ".*".prop_map(JsonValue::from),
+    ];
+    leaf.prop_recursive(3, 32, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(JsonValue::from),
+            prop::collection::btree_map(".*", inner, 0..4)
+                .prop_map(|fields| JsonValue::Object(fields.into_iter().collect())),
+        ]
+    })
+}
+
+/// Arbitrary arguments of a function call.
+pub fn arb_json_args() -> impl Strategy<Value = Vec<JsonValue>> {
+    prop::collection::vec(arb_json(), 0..4)
+}
+
+/// A short list of query set or state modifications.
+pub fn arb_modifications<T: Arbitrary>() -> impl Strategy<Value = Vec<T>> {
+    prop::collection::vec(any::<T>(), 0..4)
+}
+
+/// The result of a mutation or action, either a value or an error.
+pub fn arb_function_result() -> impl Strategy<Value = Result<JsonValue, ErrorPayload<JsonValue>>> {
+    prop_oneof![
+        arb_json().prop_map(Ok),
+        any::<String>().prop_map(|message| Err(ErrorPayload::Message(message))),
+        (any::<String>(), arb_json())
+            .prop_map(|(message, data)| Err(ErrorPayload::ErrorData { message, data })),
+    ]
+}
+
+/// Custom claims of a user identity, as JSON-encoded values.
+///
+/// The claims are prefixed so that they don't collide with the standard ones,
+/// which are fields of their own.
+pub fn arb_custom_claims() -> impl Strategy<Value = BTreeMap<String, String>> {
+    prop::collection::btree_map(
+        "custom_[a-zA-Z0-9_]{1,16}",
+        arb_json().prop_map(|value| value.to_string()),
+        0..4,
+    )
+}
+
+impl Arbitrary for Timestamp {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (u64::from(Timestamp::MIN)..=u64::from(Timestamp::MAX))
+            .prop_map(|ts| Timestamp::try_from(ts).expect("ts is in range"))
+            .boxed()
+    }
+}
+
+impl Arbitrary for SessionId {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<u128>()
+            .prop_map(|id| SessionId::new(Uuid::from_u128(id)))
+            .boxed()
+    }
+}
+
+impl Arbitrary for UdfPath {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let component = "[a-zA-Z][a-zA-Z0-9_]{0,15}";
+        (
+            prop::collection::vec(component, 1..4),
+            any::<bool>(),
+            prop::option::of(component),
+        )
+            .prop_map(|(components, js_extension, function)| {
+                let mut path = components.join("/");
+                if js_extension {
+                    path.push_str(".js");
+                }
+                if let Some(function) = function {
+                    path = format!("{path}:{function}");
+                }
+                path.parse().expect("path is valid")
+            })
+            .boxed()
+    }
+}
@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 use crate::{Timestamp, UdfPath};
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(
     Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Hash,
 )]
@@ -32,10 +33,15 @@ impl Display for QueryId {
 pub type QuerySetVersion = u32;
 pub type IdentityVersion = u32;
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Query {
     pub query_id: QueryId,
     pub udf_path: UdfPath,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "crate::testing::arb_json_args()")
+    )]
     pub args: Vec<JsonValue>,
 
     /// Query journals are only specified on reconnect. Also old clients
@@ -47,12 +53,14 @@ pub struct Query {
     pub component_path: Option<String>,
 }
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum QuerySetModification {
     Add(Query),
     Remove { query_id: QueryId },
 }
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ClientMessage {
     Connect {
@@ -64,11 +72,19 @@ pub enum ClientMessage {
     ModifyQuerySet {
         base_version: QuerySetVersion,
         new_version: QuerySetVersion,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_modifications()")
+        )]
         modifications: Vec<QuerySetModification>,
     },
     Mutation {
         request_id: SessionRequestSeqNumber,
         udf_path: UdfPath,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_json_args()")
+        )]
         args: Vec<JsonValue>,
         /// For internal use by Convex dashboard. Only works with admin auth.
         /// Allows calling a mutation within a component directly.
@@ -77,6 +93,10 @@ pub enum ClientMessage {
     Action {
         request_id: SessionRequestSeqNumber,
         udf_path: UdfPath,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_json_args()")
+        )]
         args: Vec<JsonValue>,
         /// For internal use by Convex dashboard. Only works with admin auth.
         /// Allows calling an action within a component directly.
@@ -89,12 +109,18 @@ pub enum ClientMessage {
     Event(ClientEvent),
 }
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ClientEvent {
     pub event_type: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "crate::testing::arb_json()")
+    )]
     pub event: JsonValue,
 }
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct UserIdentifier(pub String);
@@ -115,6 +141,7 @@ impl Deref for UserIdentifier {
 // TODO: Make issuer and subject not optional to match TypeScript
 // type and runtime behavior. Requires all FunctionTesters
 // to require them.
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct UserIdentityAttributes {
     pub token_identifier: UserIdentifier,
@@ -140,6 +167,10 @@ pub struct UserIdentityAttributes {
     /// Stored as RFC3339 string
     pub updated_at: Option<String>,
 
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "crate::testing::arb_custom_claims()")
+    )]
     pub custom_claims: BTreeMap<String, String>,
 }
 
@@ -172,6 +203,7 @@ impl Default for UserIdentityAttributes {
     }
 }
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub enum AuthenticationToken {
     /// Admin key issued by a KeyBroker, potentially acting as a user.
@@ -186,10 +218,15 @@ pub enum AuthenticationToken {
 /// The serialized representation of the query journal for pagination.
 pub type SerializedQueryJournal = Option<String>;
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum StateModification {
     QueryUpdated {
         query_id: QueryId,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_json()")
+        )]
         value: Value,
         log_lines: LogLinesMessage,
         journal: SerializedQueryJournal,
@@ -199,6 +236,10 @@ pub enum StateModification {
         error_message: String,
         log_lines: LogLinesMessage,
         journal: SerializedQueryJournal,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "proptest::option::of(crate::testing::arb_json())")
+        )]
         error_data: Option<Value>,
     },
     QueryRemoved {
@@ -206,6 +247,7 @@ pub enum StateModification {
     },
 }
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct StateVersion {
     pub query_set: QuerySetVersion,
@@ -223,21 +265,34 @@ impl StateVersion {
     }
 }
 
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ServerMessage {
     Transition {
         start_version: StateVersion,
         end_version: StateVersion,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_modifications()")
+        )]
         modifications: Vec<StateModification>,
     },
     MutationResponse {
         request_id: SessionRequestSeqNumber,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_function_result()")
+        )]
         result: Result<Value, ErrorPayload<Value>>,
         ts: Option<Timestamp>,
         log_lines: LogLinesMessage,
     },
     ActionResponse {
         request_id: SessionRequestSeqNumber,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_function_result()")
+        )]
         result: Result<Value, ErrorPayload<Value>>,
         log_lines: LogLinesMessage,
     },
@@ -280,6 +335,7 @@ impl<V: 'static> ErrorPayload<V> {
 }
 
 /// List of log lines from a Convex function execution.
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct LogLinesMessage(pub Vec<String>);
 
@@ -0,0 +1,105 @@
+use convex_sync_types::testing::arb_json;
+use convex_sync_types::{ClientMessage, ServerMessage, StateModification};
+use proptest::prelude::*;
+use serde_json::{Value as JsonValue, json};
+
+/// A JSON object tagged as one of the server's message types, with fields
+/// named like theirs but holding anything.
+fn arb_server_message_json() -> impl Strategy<Value = JsonValue> {
+    let message_type = prop_oneof![
+        Just("Transition"),
+        Just("MutationResponse"),
+        Just("ActionResponse"),
+        Just("AuthError"),
+        Just("FatalError"),
+        Just("Ping"),
+    ];
+    let field = prop_oneof![
+        Just("startVersion"),
+        Just("endVersion"),
+        Just("modifications"),
+        Just("requestId"),
+        Just("success"),
+        Just("result"),
+        Just("ts"),
+        Just("logLines"),
+        Just("errorData"),
+        Just("error"),
+        Just("baseVersion"),
+        Just("authUpdateAttempted"),
+    ];
+    (
+        message_type,
+        prop::collection::btree_map(field, arb_json(), 0..8),
+    )
+        .prop_map(|(message_type, fields)| {
+            let mut message: serde_json::Map<_, _> = fields
+                .into_iter()
+                .map(|(field, value)| (field.to_string(), value))
+                .collect();
+            message.insert("type".to_string(), message_type.into());
+            JsonValue::Object(message)
+        })
+}
+
+/// Parse a frame the way the websocket worker does.
+fn parse_server_frame(frame: &str) -> anyhow::Result<ServerMessage> {
+    let json: JsonValue = serde_json::from_str(frame)?;
+    json.try_into()
+}
+
+proptest! {
+    #[test]
+    fn client_message_roundtrips(message in any::<ClientMessage>()) {
+        let json = JsonValue::try_from(message.clone()).unwrap();
+        prop_assert_eq!(ClientMessage::try_from(json).unwrap(), message);
+    }
+
+    #[test]
+    fn server_message_roundtrips(message in any::<ServerMessage>()) {
+        let json = JsonValue::from(message.clone());
+        prop_assert_eq!(ServerMessage::try_from(json).unwrap(), message);
+    }
+
+    #[test]
+    fn server_message_roundtrips_through_text(message in any::<ServerMessage>()) {
+        let frame = JsonValue::from(message.clone()).to_string();
+        prop_assert_eq!(parse_server_frame(&frame).unwrap(), message);
+    }
+
+    #[test]
+    fn state_modification_roundtrips(modification in any::<StateModification>()) {
+        let json = JsonValue::from(modification.clone());
+        prop_assert_eq!(StateModification::try_from(json).unwrap(), modification);
+    }
+
+    #[test]
+    fn malformed_server_frames_dont_panic(frame in ".*") {
+        let _ = parse_server_frame(&frame);
+    }
+
+    #[test]
+    fn malformed_server_messages_dont_panic(json in arb_server_message_json()) {
+        let _ = ServerMessage::try_from(json);
+    }
+}
+
+#[test]
+fn server_message_rejects_bad_timestamps() {
+    let version = |ts: &str| json!({ "querySet": 0, "identity": 0, "ts": ts });
+    let transition = |ts: &str| {
+        json!({
+            "type": "Transition",
+            "startVersion": version("AAAAAAAAAAA="),
+            "endVersion": version(ts),
+            "modifications": [],
+        })
+    };
+    assert!(ServerMessage::try_from(transition("AAAAAAAAAAA=")).is_ok());
+    // Not base64.
+    assert!(ServerMessage::try_from(transition("not a timestamp")).is_err());
+    // Four bytes instead of eight.
+    assert!(ServerMessage::try_from(transition("AAAAAA==")).is_err());
+    // u64::MAX is past Timestamp::MAX.
+    assert!(ServerMessage::try_from(transition("//////////8=")).is_err());
+}